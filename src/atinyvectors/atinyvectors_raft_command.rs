@@ -1,290 +1,828 @@
-use serde_json::Value;
-use std::sync::Arc;
-use tracing;
-use rocksdb::{Options, DB};
-use async_std::fs;
-use async_std::fs::File;
-use async_std::path::Path;
-use async_std::path::PathBuf;
-use async_std::io::WriteExt;
-use reqwest;
-use reqwest::StatusCode;
-use regex::Regex;
-
-use crate::{atinyvectors::atinyvectors_bo::ATinyVectorsBO, config::Config};
-
-#[derive(Clone, Debug)]
-pub struct ATinyVectorsRaftCommand {
-    pub atinyvectors_bo: Arc<ATinyVectorsBO>,
-}
-
-impl ATinyVectorsRaftCommand {
-    pub fn new(atinyvectors_bo: Arc<ATinyVectorsBO>) -> Self {
-        Self { atinyvectors_bo: atinyvectors_bo.clone() }
-    }
-
-    pub async fn process_command(
-        &self,
-        command: &str,
-        request_obj: &Value,
-        key: &str,
-        value: &str,
-    ) {
-        match command {
-            "space" => self.process_space_command(request_obj, key, value).await,
-            "update_space" => self.process_update_space_command(request_obj).await,
-            "delete_space" => self.process_delete_space_command(request_obj).await,
-            "version" => self.process_version_command(request_obj).await,
-            "delete_version" => self.process_delete_version_command(request_obj).await,
-            "vector" => self.process_vector_command(request_obj).await,
-            "vector_with_version" => self.process_vector_with_version_command(request_obj).await,
-            "create_snapshot" => self.process_create_snapshot_command(request_obj).await,
-            "snapshot_restore" => self.process_snapshot_restore_command(request_obj).await,
-            "snapshot_delete" => self.process_snapshot_delete_command(request_obj).await,
-            "snapshot_sync" => self.process_snapshot_sync_command(request_obj).await,
-            "create_rbac_token" => self.process_create_rbac_token_command(request_obj).await,
-            "storage_put_key" => self.process_storage_put_key_command(request_obj).await,
-            "storage_remove_key" => self.process_storage_remove_key_command(request_obj).await,
-            _ => {
-                tracing::warn!("Unknown command: {}", command);
-            }
-        }
-    }
-
-    async fn process_space_command(
-        &self,
-        request_obj: &Value,
-        key: &str,
-        value: &str,
-    ) {
-        tracing::info!("Processing space command");
-        if let Some(space_value) = request_obj.get("value") {
-            if let Err(e) = self.atinyvectors_bo.space.create_space(&space_value.to_string()) {
-                tracing::error!("Failed to create space: {}", e);
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }
-
-    async fn process_update_space_command(&self, request_obj: &Value) {
-        tracing::info!("Processing update space command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        if let Some(space_value) = request_obj.get("value") {
-            if let Err(e) = self.atinyvectors_bo.space.update_space(space_name, &space_value.to_string()) {
-                tracing::error!("Failed to update space: {}", e);
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }
-
-    async fn process_delete_space_command(&self, request_obj: &Value) {
-        tracing::info!("Processing delete space command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let value = request_obj.get("value").and_then(|v| v.as_str()).unwrap_or("{}");
-
-        if let Err(e) = self.atinyvectors_bo.space.delete_space(space_name, &value.to_string()) {
-            tracing::error!("Failed to delete space: {}", e);
-        }
-    }
-
-    async fn process_version_command(&self, request_obj: &Value) {
-        tracing::info!("Processing version command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-
-        if let Some(version_value) = request_obj.get("value") {
-            if let Err(e) = self.atinyvectors_bo.version.create_version(space_name, &version_value.to_string()) {
-                tracing::error!("Failed to version vector: {}", e);
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }
-
-    async fn process_delete_version_command(&self, request_obj: &Value) {
-        tracing::info!("Processing delete version command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let version_id = request_obj.get("version_id").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-
-        if let Err(e) = self.atinyvectors_bo.version.delete_by_version_id(space_name, version_id) {
-            tracing::error!("Failed to delete version: {}", e);
-        }
-    }
-
-    async fn process_vector_command(&self, request_obj: &Value) {
-        tracing::debug!("Processing vector command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let version_id = 0; // default
-
-        if let Some(version_value) = request_obj.get("value") {
-            if let Err(e) = self.atinyvectors_bo.vector.upsert_vectors(space_name, version_id, &version_value.to_string()) {
-                tracing::error!("Failed to upsert vector: {}", e);
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }
-
-    async fn process_vector_with_version_command(&self, request_obj: &Value) {
-        tracing::debug!("Processing vector_with_version command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let version_id = request_obj.get("version_id").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-
-        if let Some(vector_value) = request_obj.get("value") {
-            if let Err(e) = self.atinyvectors_bo.vector.upsert_vectors(space_name, version_id, &vector_value.to_string()) {
-                tracing::error!("Failed to upsert vector with version: {}", e);
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }
-
-    async fn process_create_snapshot_command(&self, request_obj: &Value) {
-        if let Some(snapshot_value) = request_obj.get("value") {
-            tracing::debug!("Processing process_create_snapshot_command command: {}", snapshot_value);
-            if let Err(e) = self.atinyvectors_bo.snapshot.create_snapshot(&snapshot_value.to_string()) {
-                tracing::error!("Failed to create snapshot: {}", e);
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }
-
-    async fn process_snapshot_delete_command(&self, request_obj: &Value) {
-        let file_name = request_obj.get("file_name").and_then(|v| v.as_str()).unwrap_or("default");
-
-        tracing::debug!("Processing process_snapshot_delete_command command: {}", file_name);
-
-        if let Err(e) = self.atinyvectors_bo.snapshot.delete_snapshot(file_name) {
-            tracing::error!("Failed to delete snapshot: {}", e);
-        }
-    }
-
-    async fn process_snapshot_restore_command(&self, request_obj: &Value) {
-        let file_name = request_obj.get("file_name").and_then(|v| v.as_str()).unwrap_or("default");
-
-        tracing::debug!("Processing snapshot_restore command: {}", file_name);
-
-        if let Err(e) = self.atinyvectors_bo.snapshot.restore_snapshot(file_name) {
-            tracing::error!("Failed to restore snapshot: {}", e);
-        }
-    }
-
-    async fn process_snapshot_sync_command(&self, request_obj: &Value) {
-        let current_addr = Config::http_addr().clone();
-        let file_name = request_obj.get("file_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let leader_id = request_obj.get("leader_id").and_then(|v| v.as_u64()).unwrap_or(Config::instance_id());
-        let leader_addr =  request_obj.get("leader_addr").and_then(|v| v.as_str()).unwrap_or(current_addr.as_str());
-
-        tracing::info!("Processing snapshot_sync command: file_name={} / leader_addr={}", file_name, leader_addr);
-
-        if leader_id != Config::instance_id() {
-            tracing::debug!("Not Leader Node: Trying to download snapshot file");
-    
-            let snapshot_dir = PathBuf::from(Config::data_path()).join("snapshot");
-            tracing::debug!("Snapshot directory path: {:?}", snapshot_dir);
-
-            if !snapshot_dir.exists().await {
-                tracing::debug!("Snapshot directory does not exist. Creating...");
-                fs::create_dir_all(&snapshot_dir).await.map_err(|e| {
-                    tracing::error!("Failed to create snapshot directory: {}", e);
-                });
-                tracing::debug!("Snapshot directory created successfully");
-            }
-            
-            // download from leader_addr
-            let date = self.extract_date_from_file_name(file_name).unwrap_or_else(|| "unknown_date".to_string());
-            let download_url = format!("{}/snapshot/{}/download", leader_addr, date);
-            tracing::debug!("Download Endpoint: {}", download_url);
-
-            let file_path = snapshot_dir.join(file_name);
-            match self.download_file(&download_url, &file_path).await {
-                Ok(_) => tracing::debug!("File downloaded successfully: {:?}", file_path),
-                Err(e) => tracing::error!("Failed to download file: {}", e),
-            }
-        }
-
-        if let Err(e) = self.atinyvectors_bo.snapshot.restore_snapshot(file_name) {
-            tracing::error!("Failed to restore snapshot: {}", e);
-        }
-    }
-
-    async fn process_create_rbac_token_command(&self, request_obj: &Value) {
-        tracing::debug!("Processing create_rbac_token command");
-
-        let token = request_obj.get("token").and_then(|v| v.as_str()).unwrap_or("");
-        let json_str = request_obj.get("value").and_then(|v| v.as_str()).unwrap_or("");
-
-        if let Err(e) = self.atinyvectors_bo.rbac_token.new_token(json_str, token) {
-            tracing::error!("Failed to create RBAC token: {}", e);
-        }
-    }
-
-    async fn process_storage_put_key_command(&self, request_obj: &Value) {
-        tracing::debug!("Processing storage_put_key command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let key = request_obj.get("key").and_then(|v| v.as_str()).unwrap_or("");
-    
-        if let Some(value) = request_obj.get("value") {
-            if let Some(value_str) = value.as_str() {
-                let target_directory = format!("{}/space/{}", Config::data_path(), space_name);
-            
-                // Create target directory if it does not exist
-                if !std::path::Path::new(&target_directory).exists() {
-                    let _ = fs::create_dir_all(&target_directory).await;
-                }
-    
-                let path = target_directory + "storage.rocksdb";
-                let mut db_opts = Options::default();
-                db_opts.create_if_missing(true);
-    
-                let db = DB::open(&db_opts, path).unwrap();
-                let _ = db.put(key, value_str);
-            } else {
-                tracing::error!("'value' is not a string.");
-            }
-        } else {
-            tracing::error!("No 'value' field found in 'request'");
-        }
-    }    
-
-    async fn process_storage_remove_key_command(&self, request_obj: &Value) {
-        tracing::debug!("Processing storage_remove_key command");
-        let space_name = request_obj.get("space_name").and_then(|v| v.as_str()).unwrap_or("default");
-        let key = request_obj.get("key").and_then(|v| v.as_str()).unwrap_or("");
-
-        let target_directory = format!("{}/space/{}", Config::data_path(), space_name);
-
-        // Create target directory if it does not exist
-        if !std::path::Path::new(&target_directory).exists() {
-            let _ = fs::create_dir_all(&target_directory).await;
-        }
-
-        let path = target_directory + "storage.rocksdb";
-        if !std::path::Path::new(&path).exists() {
-            tracing::debug!("No database found at path: {}. Returning early.", path);
-            return; // No database, no key to remove
-        }
-
-        let mut db_opts = Options::default();
-        db_opts.create_if_missing(true);
-
-        let db = DB::open(&db_opts, path).unwrap();
-        let _ = db.delete(key);
-    }
-
-    fn extract_date_from_file_name(&self, file_name: &str) -> Option<String> {
-        let re = Regex::new(r"snapshot-(\d{8})\.zip").ok()?;
-        re.captures(file_name).and_then(|cap| cap.get(1).map(|date| date.as_str().to_string()))
-    }
-
-    async fn download_file(&self, url: &str, file_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        let response = reqwest::get(url).await?;
-        let mut file = File::create(file_path).await?;
-        let content = response.bytes().await?;
-    
-        file.write_all(&content).await?;
-        Ok(())
-    }
-}
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tracing;
+use rocksdb::{Options, DB};
+use async_std::fs;
+use async_std::path::Path;
+use async_std::path::PathBuf;
+use reqwest::StatusCode;
+
+use crate::{atinyvectors::atinyvectors_bo::ATinyVectorsBO, config::Config};
+use crate::atinyvectors::snapshot_sync;
+use crate::atinyvectors::vector_index;
+use crate::atinyvectors::vector_index::IndexError;
+use crate::atinyvectors::snapshot_sync::{SnapshotSyncStatus, SyncState};
+use crate::raft_cluster::tls;
+
+/// Schema version of [`Command`] written into new Raft log entries.
+///
+/// Bump this whenever a variant is added or a field changes meaning, so that nodes running an
+/// older binary refuse entries they cannot interpret instead of applying them incorrectly.
+pub const COMMAND_SCHEMA_VERSION: u32 = 6;
+
+/// Every write that is replicated through Raft and applied to atinyvectors on each node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    CreateSpace { value: Value },
+    UpdateSpace { space_name: String, value: Value },
+    DeleteSpace { space_name: String, value: Value },
+    CreateVersion { space_name: String, value: Value },
+    DeleteVersion { space_name: String, version_id: i32 },
+    /// `version_id` 0 targets the default version of the space, and a missing `index_name` its
+    /// default index.
+    UpsertVectors {
+        space_name: String,
+        version_id: i32,
+        value: Value,
+        #[serde(default)]
+        index_name: Option<String>,
+    },
+    CreateSnapshot { value: Value },
+    RestoreSnapshot { file_name: String },
+    DeleteSnapshot { file_name: String },
+    /// `sha256` and `size` of the uploaded file, missing in commands of schema version 1.
+    SyncSnapshot {
+        file_name: String,
+        leader_id: u64,
+        leader_addr: String,
+        #[serde(default)]
+        sha256: Option<String>,
+        #[serde(default)]
+        size: Option<u64>,
+    },
+    CreateRbacToken { token: String, value: String },
+    DeleteRbacToken { token: String },
+    UpdateRbacToken { token: String, value: String },
+    StoragePutKey { space_name: String, key: String, value: String },
+    StorageRemoveKey { space_name: String, key: String },
+}
+
+impl Command {
+    /// Short name used in logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::CreateSpace { .. } => "create_space",
+            Command::UpdateSpace { .. } => "update_space",
+            Command::DeleteSpace { .. } => "delete_space",
+            Command::CreateVersion { .. } => "create_version",
+            Command::DeleteVersion { .. } => "delete_version",
+            Command::UpsertVectors { .. } => "upsert_vectors",
+            Command::CreateSnapshot { .. } => "create_snapshot",
+            Command::RestoreSnapshot { .. } => "restore_snapshot",
+            Command::DeleteSnapshot { .. } => "delete_snapshot",
+            Command::SyncSnapshot { .. } => "sync_snapshot",
+            Command::CreateRbacToken { .. } => "create_rbac_token",
+            Command::DeleteRbacToken { .. } => "delete_rbac_token",
+            Command::UpdateRbacToken { .. } => "update_rbac_token",
+            Command::StoragePutKey { .. } => "storage_put_key",
+            Command::StorageRemoveKey { .. } => "storage_remove_key",
+        }
+    }
+
+    /// Checks the command before it is proposed, so malformed writes never reach the log.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Command::CreateSpace { value } => {
+                match value.get("name").and_then(|v| v.as_str()) {
+                    Some(name) if !name.is_empty() => Self::require_free_index_names(value),
+                    _ => Err("Missing 'name' field".to_string()),
+                }
+            }
+            Command::UpdateSpace { space_name, value }
+            | Command::CreateVersion { space_name, value } => {
+                Self::require_name("space_name", space_name)?;
+                Self::require_object(value)?;
+                Self::require_free_index_names(value)
+            }
+            Command::DeleteSpace { space_name, .. } => Self::require_name("space_name", space_name),
+            Command::DeleteVersion { space_name, version_id } => {
+                Self::require_name("space_name", space_name)?;
+                if *version_id <= 0 {
+                    return Err(format!("Invalid 'version_id': {}", version_id));
+                }
+                Ok(())
+            }
+            Command::UpsertVectors { space_name, version_id, value, index_name } => {
+                Self::require_name("space_name", space_name)?;
+                if let Some(index_name) = index_name {
+                    Self::require_name("index_name", index_name)?;
+                }
+                if *version_id < 0 {
+                    return Err(format!("Invalid 'version_id': {}", version_id));
+                }
+                let vectors = value.get("vectors").and_then(|v| v.as_array())
+                    .ok_or_else(|| "Missing 'vectors' array".to_string())?;
+                for (i, vector) in vectors.iter().enumerate() {
+                    if vector.get("id").and_then(|v| v.as_u64()).is_none() {
+                        return Err(format!("vectors[{}] has no numeric 'id'", i));
+                    }
+                    let data = vector.get("data").and_then(|v| v.as_array()).into_iter().flatten();
+                    if data.filter_map(|v| v.as_f64()).any(|f| f.abs() > f32::MAX as f64) {
+                        return Err(format!("vectors[{}] has 'data' beyond the f32 range", i));
+                    }
+                }
+                Ok(())
+            }
+            Command::CreateSnapshot { value } => Self::require_object(value),
+            Command::RestoreSnapshot { file_name }
+            | Command::DeleteSnapshot { file_name }
+            | Command::SyncSnapshot { file_name, .. } => Self::require_snapshot_name(file_name),
+            Command::CreateRbacToken { token, value }
+            | Command::UpdateRbacToken { token, value } => {
+                Self::require_name("token", token)?;
+                serde_json::from_str::<Value>(value)
+                    .map(|_| ())
+                    .map_err(|e| format!("Invalid token body: {}", e))
+            }
+            Command::DeleteRbacToken { token } => Self::require_name("token", token),
+            Command::StoragePutKey { space_name, key, .. }
+            | Command::StorageRemoveKey { space_name, key } => {
+                Self::require_name("space_name", space_name)?;
+                Self::require_name("key", key)
+            }
+        }
+    }
+
+    /// Converts a pre-typed `{"request": {"command": ..}}` payload, as found in logs written by
+    /// older versions, into a [`Command`].
+    pub fn from_legacy_json(value: &str) -> Result<Command, String> {
+        let parsed: Value = serde_json::from_str(value).map_err(|e| format!("Failed to parse value as JSON: {}", e))?;
+        let request = parsed.get("request").ok_or_else(|| "No 'request' field found in JSON".to_string())?;
+        let command = request.get("command").and_then(|v| v.as_str())
+            .ok_or_else(|| "No 'command' field found in 'request'".to_string())?;
+
+        let str_field = |name: &str| request.get(name).and_then(|v| v.as_str()).unwrap_or("default").to_string();
+        let value_field = || request.get("value").cloned().unwrap_or(Value::Null);
+        // handlers used to send `version_id` as either a number or a string
+        let version_id = request.get("version_id")
+            .and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+            .unwrap_or(0) as i32;
+
+        let command = match command {
+            "space" => Command::CreateSpace { value: value_field() },
+            "update_space" => Command::UpdateSpace { space_name: str_field("space_name"), value: value_field() },
+            "delete_space" => Command::DeleteSpace { space_name: str_field("space_name"), value: value_field() },
+            "version" => Command::CreateVersion { space_name: str_field("space_name"), value: value_field() },
+            "delete_version" => Command::DeleteVersion { space_name: str_field("space_name"), version_id },
+            "vector" => Command::UpsertVectors {
+                space_name: str_field("space_name"),
+                version_id: 0,
+                value: value_field(),
+                index_name: None,
+            },
+            "vector_with_version" => Command::UpsertVectors {
+                space_name: str_field("space_name"),
+                version_id,
+                value: value_field(),
+                index_name: None,
+            },
+            "create_snapshot" => Command::CreateSnapshot { value: value_field() },
+            "snapshot_restore" => Command::RestoreSnapshot { file_name: str_field("file_name") },
+            "snapshot_delete" => Command::DeleteSnapshot { file_name: str_field("file_name") },
+            "snapshot_sync" => Command::SyncSnapshot {
+                file_name: str_field("file_name"),
+                leader_id: request.get("leader_id").and_then(|v| v.as_u64()).unwrap_or(Config::instance_id()),
+                leader_addr: request.get("leader_addr").and_then(|v| v.as_str())
+                    .map(|s| s.to_string()).unwrap_or_else(Config::http_addr),
+                sha256: None,
+                size: None,
+            },
+            "create_rbac_token" => Command::CreateRbacToken {
+                token: request.get("token").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                value: request.get("value").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            },
+            "storage_put_key" => Command::StoragePutKey {
+                space_name: str_field("space_name"),
+                key: request.get("key").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                value: request.get("value").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            },
+            "storage_remove_key" => Command::StorageRemoveKey {
+                space_name: str_field("space_name"),
+                key: request.get("key").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            },
+            _ => return Err(format!("Unknown command: {}", command)),
+        };
+
+        Ok(command)
+    }
+
+    /// Rounds the `data` of upserted vectors to f32, the precision the engine stores them in, so
+    /// that the log holds the values every node applies. Integers become floats as well.
+    pub fn round_vector_data(&mut self) {
+        let Command::UpsertVectors { value, .. } = self else {
+            return;
+        };
+        let vectors = value.get_mut("vectors").and_then(|v| v.as_array_mut()).into_iter().flatten();
+        for vector in vectors {
+            for v in vector.get_mut("data").and_then(|d| d.as_array_mut()).into_iter().flatten() {
+                if let Some(f) = v.as_f64() {
+                    *v = Value::from(f as f32);
+                }
+            }
+        }
+    }
+
+    fn require_name(field: &str, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return Err(format!("'{}' cannot be empty", field));
+        }
+        Ok(())
+    }
+
+    /// Snapshot files are named `snapshot-<12 digits>.zip`; a name is checked before any path is
+    /// built from it, so that commands cannot reach files outside the snapshot directory.
+    fn require_snapshot_name(file_name: &str) -> Result<(), String> {
+        match snapshot_sync::snapshot_stamp(file_name) {
+            Some(_) => Ok(()),
+            None => Err(format!("Invalid snapshot file name: {}", file_name)),
+        }
+    }
+
+    /// Refuses indexes named like [`vector_index::RESERVED_INDEX`], which routes could not tell
+    /// apart from the batch search.
+    fn require_free_index_names(value: &Value) -> Result<(), String> {
+        if vector_index::declared_names(value).contains(&vector_index::RESERVED_INDEX) {
+            return Err(format!("'{}' is reserved and cannot name an index", vector_index::RESERVED_INDEX));
+        }
+        Ok(())
+    }
+
+    fn require_object(value: &Value) -> Result<(), String> {
+        if !value.is_object() {
+            return Err("Request body must be a JSON object".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Error category of a command that failed while being applied to the state machine.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandErrorCode {
+    InvalidArgument,
+    NotFound,
+    Conflict,
+    Internal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommandError {
+    pub code: CommandErrorCode,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn new(code: CommandErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(CommandErrorCode::InvalidArgument, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(CommandErrorCode::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(CommandErrorCode::Conflict, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(CommandErrorCode::Internal, message)
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+/// What a successfully applied command changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CommandOutput {
+    /// Number of spaces, versions, vectors, tokens or keys written or removed.
+    pub affected: u64,
+}
+
+impl CommandOutput {
+    pub fn affected(affected: u64) -> Self {
+        Self { affected }
+    }
+}
+
+pub type CommandResult = Result<CommandOutput, CommandError>;
+
+#[derive(Clone, Debug)]
+pub struct ATinyVectorsRaftCommand {
+    pub atinyvectors_bo: Arc<ATinyVectorsBO>,
+    /// Progress of the last `snapshot_sync` applied on this node.
+    pub snapshot_sync: Arc<Mutex<Option<SnapshotSyncStatus>>>,
+    /// Held while a shard group applies a space or version change, so that the groups of a
+    /// node see each other's changes, see [`Self::process_shared_command`].
+    schema_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ATinyVectorsRaftCommand {
+    pub fn new(atinyvectors_bo: Arc<ATinyVectorsBO>) -> Self {
+        Self {
+            atinyvectors_bo: atinyvectors_bo.clone(),
+            snapshot_sync: Arc::new(Mutex::new(None)),
+            schema_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    pub fn snapshot_sync_status(&self) -> Option<SnapshotSyncStatus> {
+        self.snapshot_sync.lock().unwrap().clone()
+    }
+
+    pub async fn process_command(&self, command: Command) -> CommandResult {
+        tracing::debug!("Processing {} command", command.name());
+
+        let result = match command {
+            Command::CreateSpace { value } => self.process_space_command(&value).await,
+            Command::UpdateSpace { space_name, value } => self.process_update_space_command(&space_name, &value).await,
+            Command::DeleteSpace { space_name, value } => self.process_delete_space_command(&space_name, &value).await,
+            Command::CreateVersion { space_name, value } => self.process_version_command(&space_name, &value).await,
+            Command::DeleteVersion { space_name, version_id } => self.process_delete_version_command(&space_name, version_id).await,
+            Command::UpsertVectors { space_name, version_id, value, index_name } => {
+                self.process_vector_command(&space_name, version_id, index_name.as_deref(), &value).await
+            }
+            Command::CreateSnapshot { value } => self.process_create_snapshot_command(&value).await,
+            Command::RestoreSnapshot { file_name } => self.process_snapshot_restore_command(&file_name).await,
+            Command::DeleteSnapshot { file_name } => self.process_snapshot_delete_command(&file_name).await,
+            Command::SyncSnapshot { file_name, leader_id, leader_addr, sha256, size } => {
+                self.process_snapshot_sync_command(&file_name, leader_id, &leader_addr, sha256.as_deref(), size).await
+            }
+            Command::CreateRbacToken { token, value } => self.process_create_rbac_token_command(&token, &value).await,
+            Command::DeleteRbacToken { token } => self.process_delete_rbac_token_command(&token).await,
+            Command::UpdateRbacToken { token, value } => self.process_update_rbac_token_command(&token, &value).await,
+            Command::StoragePutKey { space_name, key, value } => self.process_storage_put_key_command(&space_name, &key, &value).await,
+            Command::StorageRemoveKey { space_name, key } => self.process_storage_remove_key_command(&space_name, &key).await,
+        };
+
+        if let Err(e) = &result {
+            tracing::error!("Failed to apply command: {}", e);
+        }
+        result
+    }
+
+    /// Applies a command that may already have taken effect before the node restarted.
+    ///
+    /// atinyvectors persists every write on its own, so a command re-applied after a crash must
+    /// not create a second space, version or token. Only those creations are skipped when their
+    /// object exists; any other failure is returned as is, so that a replay that does not match
+    /// the engine state shows up in the logs. The outcome only matters for logging: no client is
+    /// waiting for it anymore. Replayed `create_snapshot` commands are handled by the store.
+    pub async fn replay_command(&self, command: Command) -> CommandResult {
+        let applied = match &command {
+            Command::CreateSpace { value } => {
+                let name = value.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                !name.is_empty() && self.space_exists(name)
+            }
+            Command::CreateVersion { space_name, value } => {
+                let name = value.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                !name.is_empty() && self.version_exists(space_name, name)
+            }
+            Command::CreateRbacToken { token, .. } => self.token_exists(token),
+            _ => false,
+        };
+        if applied {
+            tracing::info!("Skipping replayed {} command, already applied", command.name());
+            return Ok(CommandOutput::default());
+        }
+
+        self.process_command(command).await
+    }
+
+    /// Applies a command of a shard group whose nodes serve other groups as well.
+    ///
+    /// Spaces and versions are created and deleted on every group, and the groups of a node
+    /// share its engine, so the first group to apply such a change makes it for all of them.
+    /// The others find the space or version already created or already gone and skip it.
+    /// Vector writes only carry the ids of their own group and are applied as usual.
+    pub async fn process_shared_command(&self, command: Command) -> CommandResult {
+        let deletes = matches!(command, Command::DeleteSpace { .. } | Command::DeleteVersion { .. });
+        let schema = deletes || matches!(command, Command::CreateSpace { .. } | Command::UpdateSpace { .. } | Command::CreateVersion { .. });
+        if !schema {
+            return self.replay_command(command).await;
+        }
+
+        let _schema = self.schema_lock.lock().await;
+        match self.replay_command(command).await {
+            Err(e) if deletes && e.code == CommandErrorCode::NotFound => {
+                tracing::info!("Skipping delete, already applied by another shard group: {}", e);
+                Ok(CommandOutput::default())
+            }
+            result => result,
+        }
+    }
+
+    async fn process_space_command(&self, value: &Value) -> CommandResult {
+        tracing::info!("Processing space command");
+        let space_name = value.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if self.space_exists(space_name) {
+            return Err(CommandError::conflict(format!("Space with the given name already exists: {}", space_name)));
+        }
+
+        self.atinyvectors_bo.space.create_space(&value.to_string()).map_err(CommandError::internal)?;
+
+        if !self.space_exists(space_name) {
+            return Err(CommandError::internal(format!("Failed to create space: {}", space_name)));
+        }
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_update_space_command(&self, space_name: &str, value: &Value) -> CommandResult {
+        tracing::info!("Processing update space command");
+        self.require_space(space_name)?;
+
+        self.atinyvectors_bo.space.update_space(space_name, &value.to_string()).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_delete_space_command(&self, space_name: &str, value: &Value) -> CommandResult {
+        tracing::info!("Processing delete space command");
+        self.require_space(space_name)?;
+
+        self.atinyvectors_bo.space.delete_space(space_name, &value.to_string()).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_version_command(&self, space_name: &str, value: &Value) -> CommandResult {
+        tracing::info!("Processing version command");
+        self.require_space(space_name)?;
+
+        self.atinyvectors_bo.version.create_version(space_name, &value.to_string()).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_delete_version_command(&self, space_name: &str, version_id: i32) -> CommandResult {
+        tracing::info!("Processing delete version command");
+        self.require_version(space_name, version_id)?;
+
+        self.atinyvectors_bo.version.delete_by_version_id(space_name, version_id).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_vector_command(&self, space_name: &str, version_id: i32, index_name: Option<&str>, value: &Value) -> CommandResult {
+        tracing::debug!("Processing vector command: space_name={} version_id={} index_name={:?}", space_name, version_id, index_name);
+        self.require_version(space_name, version_id)?;
+
+        let vectors = value.get("vectors").and_then(|v| v.as_array())
+            .ok_or_else(|| CommandError::invalid_argument("Missing 'vectors' array"))?;
+
+        let index = match index_name {
+            Some(_) => Some(vector_index::resolve(&self.atinyvectors_bo, space_name, version_id, index_name).map_err(|e| match e {
+                IndexError::NotFound(_) => CommandError::not_found(format!("{} in {}", e, space_name)),
+                IndexError::NotAddressable(_) => CommandError::invalid_argument(e.to_string()),
+            })?),
+            None => vector_index::lookup(&self.atinyvectors_bo, space_name, version_id, None),
+        };
+
+        if let Some(dimension) = index.as_ref().and_then(vector_index::dimension) {
+            for vector in vectors {
+                if let Some(data) = vector.get("data").and_then(|v| v.as_array()) {
+                    if data.len() != dimension {
+                        return Err(CommandError::invalid_argument(format!(
+                            "Vector {} has dimension {}, expected {}",
+                            vector.get("id").unwrap_or(&Value::Null), data.len(), dimension)));
+                    }
+                }
+            }
+        }
+
+        // only the default index is accepted above, which the engine writes to without being told
+        self.atinyvectors_bo.vector.upsert_vectors(space_name, version_id, &value.to_string())
+            .map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(vectors.len() as u64))
+    }
+
+    async fn process_create_snapshot_command(&self, value: &Value) -> CommandResult {
+        tracing::debug!("Processing process_create_snapshot_command command: {}", value);
+        self.atinyvectors_bo.snapshot.create_snapshot(&value.to_string()).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_snapshot_delete_command(&self, file_name: &str) -> CommandResult {
+        tracing::debug!("Processing process_snapshot_delete_command command: {}", file_name);
+        self.require_snapshot_file(file_name).await?;
+
+        self.atinyvectors_bo.snapshot.delete_snapshot(file_name).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_snapshot_restore_command(&self, file_name: &str) -> CommandResult {
+        tracing::debug!("Processing snapshot_restore command: {}", file_name);
+        self.require_snapshot_file(file_name).await?;
+
+        self.atinyvectors_bo.snapshot.restore_snapshot(file_name).map_err(CommandError::internal)?;
+        self.atinyvectors_bo.rbac_token.tokens_changed();
+        Ok(CommandOutput::affected(1))
+    }
+
+    /// Restores a snapshot uploaded to the leader. Followers download it first; every node checks
+    /// it against the size and hash in the command and refuses to restore a file that differs.
+    async fn process_snapshot_sync_command(
+        &self,
+        file_name: &str,
+        leader_id: u64,
+        leader_addr: &str,
+        sha256: Option<&str>,
+        size: Option<u64>,
+    ) -> CommandResult {
+        tracing::info!("Processing snapshot_sync command: file_name={} / leader_addr={}", file_name, leader_addr);
+
+        // entries written before `Command::validate` checked the name are checked here
+        let stamp = snapshot_sync::snapshot_stamp(file_name)
+            .ok_or_else(|| CommandError::invalid_argument(format!("Invalid snapshot file name: {}", file_name)))?;
+        let snapshot_dir = std::path::PathBuf::from(Config::data_path()).join("snapshot");
+        let target = snapshot_dir.join(file_name);
+        let mut status = SnapshotSyncStatus::new(file_name, size);
+
+        // a replayed command finds the file already in place
+        let present = target.exists() && snapshot_sync::verify(&target, sha256, size).is_ok();
+        if leader_id != Config::instance_id() && !present {
+            std::fs::create_dir_all(&snapshot_dir)
+                .map_err(|e| CommandError::internal(format!("Failed to create snapshot directory: {}", e)))?;
+
+            let download_url = format!("{}/api/snapshot/{}/download", tls::base_url(leader_addr), stamp);
+            tracing::debug!("Download Endpoint: {}", download_url);
+
+            let downloaded = snapshot_sync::download(&download_url, &target, sha256, size, &mut status, |status| {
+                self.report_snapshot_sync(status)
+            })
+            .await;
+            if let Err(e) = downloaded {
+                return Err(self.fail_snapshot_sync(&mut status, format!("Failed to download snapshot {}: {}", file_name, e)));
+            }
+            tracing::debug!("File downloaded successfully: {:?}", target);
+        }
+
+        status.set(SyncState::Verifying);
+        self.report_snapshot_sync(&status);
+        if let Err(e) = snapshot_sync::verify(&target, sha256, size) {
+            return Err(self.fail_snapshot_sync(&mut status, e));
+        }
+        status.bytes = std::fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+
+        status.set(SyncState::Restoring);
+        self.report_snapshot_sync(&status);
+        if let Err(e) = self.atinyvectors_bo.snapshot.restore_snapshot(file_name) {
+            return Err(self.fail_snapshot_sync(&mut status, e));
+        }
+        self.atinyvectors_bo.rbac_token.tokens_changed();
+
+        status.error = None;
+        status.set(SyncState::Done);
+        self.report_snapshot_sync(&status);
+        Ok(CommandOutput::affected(1))
+    }
+
+    fn report_snapshot_sync(&self, status: &SnapshotSyncStatus) {
+        *self.snapshot_sync.lock().unwrap() = Some(status.clone());
+    }
+
+    fn fail_snapshot_sync(&self, status: &mut SnapshotSyncStatus, error: String) -> CommandError {
+        status.fail(error.clone());
+        self.report_snapshot_sync(status);
+        CommandError::internal(error)
+    }
+
+    async fn process_create_rbac_token_command(&self, token: &str, json_str: &str) -> CommandResult {
+        tracing::debug!("Processing create_rbac_token command");
+
+        self.atinyvectors_bo.rbac_token.new_token(json_str, token).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_delete_rbac_token_command(&self, token: &str) -> CommandResult {
+        tracing::debug!("Processing delete_rbac_token command");
+        self.require_token(token)?;
+
+        self.atinyvectors_bo.rbac_token.delete_token(token).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_update_rbac_token_command(&self, token: &str, json_str: &str) -> CommandResult {
+        tracing::debug!("Processing update_rbac_token command");
+        self.require_token(token)?;
+
+        self.atinyvectors_bo.rbac_token.update_token(token, json_str).map_err(CommandError::internal)?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_storage_put_key_command(&self, space_name: &str, key: &str, value: &str) -> CommandResult {
+        tracing::debug!("Processing storage_put_key command");
+        let target_directory = format!("{}/space/{}", Config::data_path(), space_name);
+
+        // Create target directory if it does not exist
+        if !std::path::Path::new(&target_directory).exists() {
+            let _ = fs::create_dir_all(&target_directory).await;
+        }
+
+        let path = target_directory + "storage.rocksdb";
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+
+        let db = DB::open(&db_opts, path)
+            .map_err(|e| CommandError::internal(format!("Failed to open key-value storage: {}", e)))?;
+        db.put(key, value)
+            .map_err(|e| CommandError::internal(format!("Failed to put key: {}", e)))?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    async fn process_storage_remove_key_command(&self, space_name: &str, key: &str) -> CommandResult {
+        tracing::debug!("Processing storage_remove_key command");
+        let target_directory = format!("{}/space/{}", Config::data_path(), space_name);
+
+        // Create target directory if it does not exist
+        if !std::path::Path::new(&target_directory).exists() {
+            let _ = fs::create_dir_all(&target_directory).await;
+        }
+
+        let path = target_directory + "storage.rocksdb";
+        if !std::path::Path::new(&path).exists() {
+            tracing::debug!("No database found at path: {}. Returning early.", path);
+            return Err(CommandError::not_found(format!("Key not found: {}", key))); // No database, no key to remove
+        }
+
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+
+        let db = DB::open(&db_opts, path)
+            .map_err(|e| CommandError::internal(format!("Failed to open key-value storage: {}", e)))?;
+        let exists = db.get(key)
+            .map_err(|e| CommandError::internal(format!("Failed to read key: {}", e)))?
+            .is_some();
+        if !exists {
+            return Err(CommandError::not_found(format!("Key not found: {}", key)));
+        }
+
+        db.delete(key)
+            .map_err(|e| CommandError::internal(format!("Failed to remove key: {}", e)))?;
+        Ok(CommandOutput::affected(1))
+    }
+
+    /// Dumps spaces, versions, vectors and tokens through the engine's own snapshot service so
+    /// they can be shipped inside a Raft snapshot.
+    ///
+    /// `write` is handed the name and path of the archive the engine produced, which is removed
+    /// afterwards.
+    pub fn export_engine_state<F>(&self, write: F) -> Result<(), String>
+    where
+        F: FnOnce(&str, &std::path::Path) -> std::io::Result<()>,
+    {
+        Self::with_user_snapshots_held(|snapshot_dir| {
+            self.atinyvectors_bo.snapshot.create_snapshot("{}")?;
+
+            // the directory was emptied, the only file in it is the one just created
+            let file_name = Self::list_snapshot_files(snapshot_dir)
+                .into_iter()
+                .next()
+                .ok_or_else(|| "Engine did not produce a snapshot".to_string())?;
+
+            let file_path = snapshot_dir.join(&file_name);
+            let written = write(&file_name, &file_path)
+                .map_err(|e| format!("Failed to read engine snapshot {}: {}", file_name, e));
+            let _ = std::fs::remove_file(&file_path);
+            written
+        })
+    }
+
+    /// Replaces the engine state with an archive produced by [`Self::export_engine_state`].
+    pub fn import_engine_state<R: std::io::Read>(&self, file_name: &str, data: &mut R) -> Result<(), String> {
+        Self::with_user_snapshots_held(|snapshot_dir| {
+            let file_path = snapshot_dir.join(file_name);
+            std::fs::File::create(&file_path)
+                .and_then(|mut file| std::io::copy(data, &mut file))
+                .map_err(|e| format!("Failed to write engine snapshot {}: {}", file_name, e))?;
+
+            let restored = self.atinyvectors_bo.snapshot.restore_snapshot(file_name);
+            let _ = std::fs::remove_file(&file_path);
+            restored
+        })?;
+
+        self.atinyvectors_bo.id_cache.clean();
+        self.atinyvectors_bo.id_cache.clear_space_name_cache();
+        self.atinyvectors_bo.rbac_token.tokens_changed();
+        Ok(())
+    }
+
+    /// Runs `f` on the engine's snapshot directory with the user snapshots moved out of it.
+    ///
+    /// The engine only reads and writes archives in that directory and names new ones by the
+    /// minute, so a Raft export would otherwise replace a user snapshot taken in the same minute,
+    /// and an import one of the same name. They are moved back afterwards, or by the next call if
+    /// the node stopped in between. Listing the user snapshots meanwhile shows none.
+    fn with_user_snapshots_held<T>(f: impl FnOnce(&std::path::Path) -> Result<T, String>) -> Result<T, String> {
+        let data_path = std::path::PathBuf::from(Config::data_path());
+        let snapshot_dir = data_path.join("snapshot");
+        let held_dir = data_path.join("snapshot_held");
+        for dir in [&snapshot_dir, &held_dir] {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", dir, e))?;
+        }
+
+        Self::move_snapshot_files(&held_dir, &snapshot_dir)?;
+        Self::move_snapshot_files(&snapshot_dir, &held_dir)?;
+        let result = f(&snapshot_dir);
+        Self::move_snapshot_files(&held_dir, &snapshot_dir)?;
+        result
+    }
+
+    /// Moves every file of `from` to `to`, leaving in place the ones `to` already has.
+    fn move_snapshot_files(from: &std::path::Path, to: &std::path::Path) -> Result<(), String> {
+        for file_name in Self::list_snapshot_files(from) {
+            let target = to.join(&file_name);
+            if target.exists() {
+                tracing::warn!("Not moving snapshot {} to {:?}, a file of that name exists", file_name, to);
+                continue;
+            }
+            std::fs::rename(from.join(&file_name), &target)
+                .map_err(|e| format!("Failed to move snapshot {}: {}", file_name, e))?;
+        }
+        Ok(())
+    }
+
+    fn list_snapshot_files(snapshot_dir: &std::path::Path) -> Vec<String> {
+        std::fs::read_dir(snapshot_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn version_exists(&self, space_name: &str, version_name: &str) -> bool {
+        self.atinyvectors_bo.version.get_by_version_name(space_name, version_name)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+            .map(|version| version.as_object().map(|o| !o.is_empty()).unwrap_or(false))
+            .unwrap_or(false)
+    }
+
+    fn token_exists(&self, token: &str) -> bool {
+        fn contains(value: &Value, token: &str) -> bool {
+            match value {
+                Value::String(s) => s == token,
+                Value::Array(items) => items.iter().any(|v| contains(v, token)),
+                Value::Object(fields) => fields.values().any(|v| contains(v, token)),
+                _ => false,
+            }
+        }
+
+        self.atinyvectors_bo.rbac_token.list_tokens()
+            .ok()
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+            .map(|tokens| contains(&tokens, token))
+            .unwrap_or(false)
+    }
+
+    fn require_token(&self, token: &str) -> Result<(), CommandError> {
+        if !self.token_exists(token) {
+            return Err(CommandError::not_found("Token not found"));
+        }
+        Ok(())
+    }
+
+    /// Id of the default version of a space, 0 or less when the space does not exist.
+    pub fn default_version_id(&self, space_name: &str) -> i32 {
+        self.atinyvectors_bo.id_cache.get_default_version_id(space_name)
+    }
+
+    fn space_exists(&self, space_name: &str) -> bool {
+        self.atinyvectors_bo.id_cache.get_default_version_id(space_name) > 0
+    }
+
+    fn require_space(&self, space_name: &str) -> Result<(), CommandError> {
+        if !self.space_exists(space_name) {
+            return Err(CommandError::not_found(format!("Space not found: {}", space_name)));
+        }
+        Ok(())
+    }
+
+    /// `version_id` 0 resolves to the default version, which exists whenever the space does.
+    fn require_version(&self, space_name: &str, version_id: i32) -> Result<(), CommandError> {
+        self.require_space(space_name)?;
+        if version_id != 0 && self.atinyvectors_bo.id_cache.get_version_id(space_name, version_id) <= 0 {
+            return Err(CommandError::not_found(format!("Version not found: {}/{}", space_name, version_id)));
+        }
+        Ok(())
+    }
+
+    async fn require_snapshot_file(&self, file_name: &str) -> Result<(), CommandError> {
+        Command::require_snapshot_name(file_name).map_err(CommandError::invalid_argument)?;
+        let snapshot_path = PathBuf::from(Config::data_path()).join("snapshot").join(file_name);
+        if !snapshot_path.exists().await {
+            return Err(CommandError::not_found(format!("Snapshot not found: {}", file_name)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+//! JSON merge-patch (RFC 7386), used to change the metadata of a stored vector.
+
+use serde_json::Map;
+use serde_json::Value;
+
+/// Applies `patch` to `target`: members of an object patch are merged recursively, `null`
+/// removes a member, and anything else replaces the target.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(fields) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                fields.remove(key);
+            } else {
+                merge_patch(fields.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
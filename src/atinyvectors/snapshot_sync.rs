@@ -0,0 +1,219 @@
+//! Transfer of an uploaded snapshot from the leader to the followers for `snapshot_sync`.
+//!
+//! The command carries the SHA-256 and size of the file. A follower downloads it into a
+//! `.part` file, resuming with a `Range` request after an interrupted attempt, and only moves it
+//! next to the other snapshots once size and hash match. Every node keeps the progress of its
+//! last sync in a [`SnapshotSyncStatus`].
+
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::raft_cluster::cluster_auth;
+use crate::raft_cluster::tls;
+
+/// Attempts to download a snapshot before the sync is given up.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+const RETRY_BASE: Duration = Duration::from_millis(500);
+
+const RETRY_MAX: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Downloading,
+    Verifying,
+    Restoring,
+    Done,
+    Failed,
+}
+
+/// Progress of the last `snapshot_sync` applied on a node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotSyncStatus {
+    pub file_name: String,
+    pub state: SyncState,
+    /// Bytes on disk so far.
+    pub bytes: u64,
+    pub size: Option<u64>,
+    pub attempts: u32,
+    pub error: Option<String>,
+    pub updated_at: String,
+}
+
+impl SnapshotSyncStatus {
+    pub fn new(file_name: &str, size: Option<u64>) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            state: SyncState::Downloading,
+            bytes: 0,
+            size,
+            attempts: 0,
+            error: None,
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn set(&mut self, state: SyncState) {
+        self.state = state;
+        self.updated_at = chrono::Utc::now().to_rfc3339();
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.error = Some(error);
+        self.set(SyncState::Failed);
+    }
+}
+
+/// SHA-256 as lower case hex and size of the file at `path`.
+pub fn file_digest(path: &Path) -> io::Result<(String, u64)> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+/// Checks the file at `path` against the size and hash carried by the command. Either may be
+/// missing in commands written by older versions.
+pub fn verify(path: &Path, sha256: Option<&str>, size: Option<u64>) -> Result<(), String> {
+    let (actual_sha256, actual_size) = file_digest(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    if let Some(size) = size {
+        if size != actual_size {
+            return Err(format!("Size mismatch for {:?}: expected {} bytes, got {}", path, size, actual_size));
+        }
+    }
+    if let Some(sha256) = sha256 {
+        if !sha256.eq_ignore_ascii_case(&actual_sha256) {
+            return Err(format!("Checksum mismatch for {:?}: expected {}, got {}", path, sha256, actual_sha256));
+        }
+    }
+    Ok(())
+}
+
+/// Stamp of a snapshot file name, `202401011230` for `snapshot-202401011230.zip`.
+pub fn snapshot_stamp(file_name: &str) -> Option<String> {
+    let re = regex::Regex::new(r"^snapshot-(\d{12})\.zip$").ok()?;
+    re.captures(file_name).and_then(|cap| cap.get(1).map(|stamp| stamp.as_str().to_string()))
+}
+
+/// Downloads `url` to `target`, retrying with backoff and resuming from what earlier attempts
+/// left in `<target>.part`. `target` is only written once the file matches `sha256` and `size`.
+///
+/// `report` is called with the status after every change.
+pub async fn download<F>(
+    url: &str,
+    target: &Path,
+    sha256: Option<&str>,
+    size: Option<u64>,
+    status: &mut SnapshotSyncStatus,
+    mut report: F,
+) -> Result<(), String>
+where
+    F: FnMut(&SnapshotSyncStatus),
+{
+    let part = part_path(target);
+    let client = tls::http_client();
+
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_ATTEMPTS {
+        status.attempts = attempt;
+        status.set(SyncState::Downloading);
+        report(status);
+
+        match fetch(&client, url, &part, size, status, &mut report).await {
+            Ok(()) => {
+                status.set(SyncState::Verifying);
+                report(status);
+                match verify(&part, sha256, size) {
+                    Ok(()) => {
+                        return fs::rename(&part, target)
+                            .map_err(|e| format!("Failed to move {:?} to {:?}: {}", part, target, e));
+                    }
+                    Err(e) => {
+                        // a corrupt file can not be resumed, start over
+                        let _ = fs::remove_file(&part);
+                        last_error = e;
+                    }
+                }
+            }
+            Err(e) => last_error = e,
+        }
+
+        tracing::warn!("Snapshot download attempt {} of {} failed: {}", attempt, MAX_ATTEMPTS, last_error);
+        status.error = Some(last_error.clone());
+        report(status);
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(retry_delay(attempt)).await;
+        }
+    }
+
+    Err(format!("Giving up after {} attempts: {}", MAX_ATTEMPTS, last_error))
+}
+
+async fn fetch<F>(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    size: Option<u64>,
+    status: &mut SnapshotSyncStatus,
+    report: &mut F,
+) -> Result<(), String>
+where
+    F: FnMut(&SnapshotSyncStatus),
+{
+    let have = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    if size.is_some_and(|size| have >= size) {
+        status.bytes = have;
+        return Ok(());
+    }
+
+    let mut builder = cluster_auth::sign(client.get(url));
+    if have > 0 {
+        builder = builder.header("Range", format!("bytes={}-", have));
+    }
+    let mut response = builder.send().await.map_err(|e| e.to_string())?;
+
+    let mut file = match response.status() {
+        reqwest::StatusCode::PARTIAL_CONTENT => fs::OpenOptions::new().append(true).open(part),
+        reqwest::StatusCode::OK => fs::File::create(part),
+        other => return Err(format!("Leader answered {} for {}", other, url)),
+    }
+    .map_err(|e| format!("Failed to open {:?}: {}", part, e))?;
+
+    status.bytes = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT { have } else { 0 };
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).map_err(|e| format!("Failed to write {:?}: {}", part, e))?;
+        status.bytes += chunk.len() as u64;
+        report(status);
+    }
+    file.sync_all().map_err(|e| format!("Failed to write {:?}: {}", part, e))
+}
+
+fn part_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(".part");
+    target.with_file_name(name)
+}
+
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE.saturating_mul(1 << (attempt - 1).min(8)).min(RETRY_MAX)
+}
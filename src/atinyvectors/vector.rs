@@ -1,76 +1,121 @@
-use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
-
-// FFI declaration for VectorServiceManager
-#[derive(Clone, Debug)]
-#[repr(C)]
-pub struct VectorServiceManager {
-    _private: [u8; 0],
-}
-
-extern "C" {
-    pub fn atv_vector_service_manager_new() -> *mut VectorServiceManager;
-    pub fn atv_vector_service_manager_free(manager: *mut VectorServiceManager);
-    pub fn atv_vector_service_upsert(
-        manager: *mut VectorServiceManager,
-        space_name: *const c_char,
-        version_id: i32,
-        json_str: *const c_char,
-    );
-    pub fn atv_vector_service_get_vectors_by_version_id(
-        manager: *mut VectorServiceManager, 
-        space_name: *const c_char,
-        version_id: i32, 
-        start: i32, 
-        limit: i32,
-        filter: *const c_char,) -> *mut c_char;
-}
-
-// Safe Rust wrapper for VectorServiceManager
-#[derive(Clone, Debug)]
-pub struct VectorServiceManagerWrapper {
-    inner: *mut VectorServiceManager,
-}
-
-impl VectorServiceManagerWrapper {
-    pub fn new() -> Self {
-        unsafe { VectorServiceManagerWrapper { inner: atv_vector_service_manager_new() } }
-    }
-
-    pub fn upsert_vectors(&self, space_name: &str, version_id: i32, json_str: &str) -> Result<(), String> {
-        let space_name_c = CString::new(space_name).unwrap();
-        let json_str_c = CString::new(json_str).unwrap();
-        unsafe {
-            atv_vector_service_upsert(self.inner, space_name_c.as_ptr(), version_id, json_str_c.as_ptr());
-        };
-
-        Ok(())
-    }
-
-    pub fn get_vectors_by_version_id(&self, space_name: &str, version_id: i32, start: i32, limit: i32, filter: &str) -> Result<String, String> {
-        let space_name_c = CString::new(space_name).unwrap();
-        let filter_c = CString::new(filter).unwrap();
-
-        unsafe {
-            let result = atv_vector_service_get_vectors_by_version_id(self.inner, space_name_c.as_ptr(), version_id, start, limit, filter_c.as_ptr());
-            if result.is_null() {
-                Err("Failed to extract vectors".to_string())
-            } else {
-                let result_str = CStr::from_ptr(result).to_string_lossy().into_owned();
-                super::atv_free_json_string(result);
-                Ok(result_str)
-            }
-        }
-    }
-}
-
-impl Drop for VectorServiceManagerWrapper {
-    fn drop(&mut self) {
-        unsafe {
-            atv_vector_service_manager_free(self.inner);
-        }
-    }
-}
-
-unsafe impl Send for VectorServiceManagerWrapper {}
-unsafe impl Sync for VectorServiceManagerWrapper {}
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+use serde_json::Value;
+
+/// Vectors fetched per call when looking vectors up by id.
+const SCAN_PAGE_SIZE: i32 = 1000;
+
+// FFI declaration for VectorServiceManager
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct VectorServiceManager {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    pub fn atv_vector_service_manager_new() -> *mut VectorServiceManager;
+    pub fn atv_vector_service_manager_free(manager: *mut VectorServiceManager);
+    pub fn atv_vector_service_upsert(
+        manager: *mut VectorServiceManager,
+        space_name: *const c_char,
+        version_id: i32,
+        json_str: *const c_char,
+    );
+    pub fn atv_vector_service_get_vectors_by_version_id(
+        manager: *mut VectorServiceManager, 
+        space_name: *const c_char,
+        version_id: i32, 
+        start: i32, 
+        limit: i32,
+        filter: *const c_char,) -> *mut c_char;
+}
+
+// Safe Rust wrapper for VectorServiceManager
+#[derive(Clone, Debug)]
+pub struct VectorServiceManagerWrapper {
+    inner: *mut VectorServiceManager,
+}
+
+impl VectorServiceManagerWrapper {
+    pub fn new() -> Self {
+        unsafe { VectorServiceManagerWrapper { inner: atv_vector_service_manager_new() } }
+    }
+
+    pub fn upsert_vectors(&self, space_name: &str, version_id: i32, json_str: &str) -> Result<(), String> {
+        let space_name_c = CString::new(space_name).unwrap();
+        let json_str_c = CString::new(json_str).unwrap();
+        unsafe {
+            atv_vector_service_upsert(self.inner, space_name_c.as_ptr(), version_id, json_str_c.as_ptr());
+        };
+
+        Ok(())
+    }
+
+    pub fn get_vectors_by_version_id(&self, space_name: &str, version_id: i32, start: i32, limit: i32, filter: &str) -> Result<String, String> {
+        let space_name_c = CString::new(space_name).unwrap();
+        let filter_c = CString::new(filter).unwrap();
+
+        unsafe {
+            let result = atv_vector_service_get_vectors_by_version_id(self.inner, space_name_c.as_ptr(), version_id, start, limit, filter_c.as_ptr());
+            if result.is_null() {
+                Err("Failed to extract vectors".to_string())
+            } else {
+                let result_str = CStr::from_ptr(result).to_string_lossy().into_owned();
+                super::atv_free_json_string(result);
+                Ok(result_str)
+            }
+        }
+    }
+
+    /// The vector `vector_id` with its data and metadata, `None` if the version has no such vector.
+    pub fn get_vector_by_id(&self, space_name: &str, version_id: i32, vector_id: u64) -> Option<String> {
+        self.get_vectors_by_ids(space_name, version_id, &[vector_id])
+            .remove(&vector_id)
+            .map(|vector| vector.to_string())
+    }
+
+    /// The vectors among `ids` that the version holds, by id. The engine cannot look vectors up
+    /// by id, so the version is listed page by page until all of them are found.
+    pub fn get_vectors_by_ids(&self, space_name: &str, version_id: i32, ids: &[u64]) -> BTreeMap<u64, Value> {
+        let wanted: BTreeSet<u64> = ids.iter().copied().collect();
+        let mut found = BTreeMap::new();
+        let mut start = 0;
+        while found.len() < wanted.len() {
+            let page = match self.get_vectors_by_version_id(space_name, version_id, start, SCAN_PAGE_SIZE, "") {
+                Ok(page) => page,
+                Err(_) => break,
+            };
+            let page: Value = match serde_json::from_str(&page) {
+                Ok(page) => page,
+                Err(_) => break,
+            };
+            let vectors = match page.get("vectors").and_then(Value::as_array) {
+                Some(vectors) if !vectors.is_empty() => vectors,
+                _ => break,
+            };
+
+            for vector in vectors {
+                if let Some(id) = vector.get("id").and_then(Value::as_u64).filter(|id| wanted.contains(id)) {
+                    found.insert(id, vector.clone());
+                }
+            }
+            start += vectors.len() as i32;
+        }
+        found
+    }
+
+}
+
+impl Drop for VectorServiceManagerWrapper {
+    fn drop(&mut self) {
+        unsafe {
+            atv_vector_service_manager_free(self.inner);
+        }
+    }
+}
+
+unsafe impl Send for VectorServiceManagerWrapper {}
+unsafe impl Sync for VectorServiceManagerWrapper {}
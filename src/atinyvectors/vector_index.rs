@@ -0,0 +1,111 @@
+//! Lookup of the vector indexes declared for a version, as reported in its `vectorIndices`.
+//!
+//! The engine resolves the index of a space and version by itself, see
+//! `IdCacheManagerWrapper::get_vector_index_id`, and its upsert, search and listing calls take no
+//! index. Only the default index of a version can therefore be written, searched or listed; a
+//! route naming another index is refused with [`IndexError::NotAddressable`] rather than served
+//! from the default one.
+
+use serde_json::Value;
+
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+
+/// Name routes use for the default index of a version.
+pub const DEFAULT_INDEX: &str = "default";
+
+/// Index name taken by the batch search routes, `/search/batch`.
+pub const RESERVED_INDEX: &str = "batch";
+
+/// Why an index named in a request cannot be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexError {
+    NotFound(String),
+    /// The index exists but is not the default one, which is the only one the engine reaches.
+    NotAddressable(String),
+}
+
+impl std::fmt::Display for IndexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexError::NotFound(name) => write!(f, "Vector index not found: {}", name),
+            IndexError::NotAddressable(name) => write!(
+                f,
+                "Vector index {} is not the default index, the engine only serves the default index of a version",
+                name
+            ),
+        }
+    }
+}
+
+/// The index named `index_name` in the version JSON, or its default index for `None`.
+///
+/// `"default"` matches an index of that name first, and the index flagged `is_default` otherwise.
+pub fn find_index<'a>(version: &'a Value, index_name: Option<&str>) -> Option<&'a Value> {
+    let indices = version.get("vectorIndices")
+        .or_else(|| version.get("version").and_then(|v| v.get("vectorIndices")))?
+        .as_array()?;
+    let default = || indices.iter()
+        .find(|index| index.get("is_default").and_then(Value::as_bool).unwrap_or(false))
+        .or_else(|| indices.first());
+
+    match index_name {
+        None => default(),
+        Some(name) => indices.iter()
+            .find(|index| index.get("name").and_then(Value::as_str) == Some(name))
+            .or_else(|| if name == DEFAULT_INDEX { default() } else { None }),
+    }
+}
+
+/// The index `index_name` of `version`, or its default index for `None`, if the engine can
+/// address it.
+pub fn check_index<'a>(version: &'a Value, index_name: Option<&str>) -> Result<&'a Value, IndexError> {
+    let default = find_index(version, None);
+    let name = match index_name {
+        None => return default.ok_or_else(|| IndexError::NotFound(DEFAULT_INDEX.to_string())),
+        Some(name) => name,
+    };
+    let index = find_index(version, Some(name)).ok_or_else(|| IndexError::NotFound(name.to_string()))?;
+    if default != Some(index) {
+        return Err(IndexError::NotAddressable(name.to_string()));
+    }
+    Ok(index)
+}
+
+/// Names of the indexes declared in the `indexes` of a space or version request, given either
+/// as an object keyed by name or as an array of objects with a `name`.
+pub fn declared_names(value: &Value) -> Vec<&str> {
+    match value.get("indexes") {
+        Some(Value::Object(indexes)) => indexes.keys().map(String::as_str).collect(),
+        Some(Value::Array(indexes)) => indexes.iter().filter_map(|index| index.get("name").and_then(Value::as_str)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Dimension of `index`, if the engine reports one.
+pub fn dimension(index: &Value) -> Option<usize> {
+    match index.get("dimension").and_then(Value::as_u64) {
+        Some(dimension) if dimension > 0 => Some(dimension as usize),
+        _ => None,
+    }
+}
+
+fn load_version(bo: &ATinyVectorsBO, space_name: &str, version_id: i32) -> Option<Value> {
+    let version_json = if version_id == 0 {
+        bo.version.get_default_version(space_name).ok()?
+    } else {
+        bo.version.get_by_version_id(space_name, version_id).ok()?
+    };
+    serde_json::from_str(&version_json).ok()
+}
+
+/// The index `index_name` of version `version_id` of a space, `0` for the default version.
+pub fn lookup(bo: &ATinyVectorsBO, space_name: &str, version_id: i32, index_name: Option<&str>) -> Option<Value> {
+    find_index(&load_version(bo, space_name, version_id)?, index_name).cloned()
+}
+
+/// [`check_index`] for version `version_id` of a space, `0` for the default version.
+pub fn resolve(bo: &ATinyVectorsBO, space_name: &str, version_id: i32, index_name: Option<&str>) -> Result<Value, IndexError> {
+    let not_found = || IndexError::NotFound(index_name.unwrap_or(DEFAULT_INDEX).to_string());
+    let version = load_version(bo, space_name, version_id).ok_or_else(not_found)?;
+    check_index(&version, index_name).cloned()
+}
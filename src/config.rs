@@ -1,350 +1,682 @@
-use std::env;
-use clap::{Arg, ArgAction, Command};
-use once_cell::sync::Lazy;
-use std::sync::Mutex;
-
-/// Config struct that dynamically fetches values from environment variables.
-#[derive(Debug)]
-pub struct Config;
-
-// Create a singleton for Config
-static CONFIG: Lazy<Mutex<Config>> = Lazy::new(|| Mutex::new(Config::new()));
-
-/// Implement Config struct
-impl Config {
-    /// Creates a new Config instance
-    pub fn new() -> Self {
-        Self
-    }
-
-    /// Initialize the configuration and update environment variables if needed
-    pub fn initialize() {
-        let matches = Command::new("asimplevectors")
-            .version(env!("CARGO_PKG_VERSION")) // Uses the version defined in Cargo.toml
-            .author("billionvectors.com")
-            .about("Handles config with environment variables and command-line arguments")
-            .arg(
-                Arg::new("cache_capacity")
-                    .long("cache_capacity")
-                    .action(ArgAction::Set)
-                    .help("Set cache capacity for HNSW index"),
-            )
-            .arg(
-                Arg::new("db_name")
-                    .long("db_name")
-                    .action(ArgAction::Set)
-                    .help("Set the database name"),
-            )
-            .arg(
-                Arg::new("log_file")
-                    .long("log_file")
-                    .action(ArgAction::Set)
-                    .help("Set the log file path"),
-            )
-            .arg(
-                Arg::new("log_level")
-                    .long("log_level")
-                    .action(ArgAction::Set)
-                    .help("Set the log level (e.g., info, debug)"),
-            )
-            .arg(
-                Arg::new("service_log_file")
-                    .long("service_log_file")
-                    .action(ArgAction::Set)
-                    .help("Set the service log file path"),
-            )
-            .arg(
-                Arg::new("service_log_level")
-                    .long("service_log_level")
-                    .action(ArgAction::Set)
-                    .help("Set the service log level (e.g., info, debug)"),
-            )
-            .arg(
-                Arg::new("m")
-                    .long("m")
-                    .action(ArgAction::Set)
-                    .help("Set the default M value"),
-            )
-            .arg(
-                Arg::new("ef_construction")
-                    .long("ef_construction")
-                    .action(ArgAction::Set)
-                    .help("Set the EF construction value"),
-            )
-            .arg(
-                Arg::new("max_datasize")
-                    .long("max_datasize")
-                    .action(ArgAction::Set)
-                    .help("Set the maximum data size"),
-            )
-            .arg(
-                Arg::new("data_path")
-                    .long("data_path")
-                    .action(ArgAction::Set)
-                    .help("Set the data path"),
-            )
-            .arg(
-                Arg::new("token_expire_days")
-                    .long("token_expire_days")
-                    .action(ArgAction::Set)
-                    .help("Set token expiration days"),
-            )
-            .arg(
-                Arg::new("jwt_token_key")
-                    .long("jwt_token_key")
-                    .action(ArgAction::Set)
-                    .help("Set the JWT token key"),
-            )
-            .arg(
-                Arg::new("enable_security")
-                    .long("enable_security")
-                    .action(ArgAction::Set)
-                    .help("Enable or disable security (1: enable or 0: disable)"),
-            )
-            .arg(
-                Arg::new("enable_swagger_ui")
-                    .long("enable_swagger_ui")
-                    .action(ArgAction::SetTrue)
-                    .help("Enable or disable Swagger UI (true: enable, false: disable)"),
-            )
-            .arg(
-                Arg::new("id")
-                    .long("id")
-                    .action(ArgAction::Set)
-                    .help("Set the instance ID"),
-            )
-            .arg(
-                Arg::new("standalone")
-                    .long("standalone")
-                    .action(ArgAction::SetTrue)
-                    .help("Set the standalone node (true: standalone, false: cluster)"),
-            )
-            .arg(
-                Arg::new("http_addr")
-                    .long("http-addr")
-                    .action(ArgAction::Set)
-                    .help("Set the HTTP address"),
-            )
-            .arg(
-                Arg::new("rpc_addr")
-                    .long("rpc-addr")
-                    .action(ArgAction::Set)
-                    .help("Set the RPC address"),
-            )
-            .arg(
-                Arg::new("raft_heartbeat_interval")
-                    .long("raft_heartbeat_interval")
-                    .action(ArgAction::Set)
-                    .help("Set the Raft heartbeat interval (ms)"),
-            )
-            .arg(
-                Arg::new("raft_election_timeout")
-                    .long("raft_election_timeout")
-                    .action(ArgAction::Set)
-                    .help("Set the Raft election timeout (ms)"),
-            )
-            .get_matches();
-
-        // Check and update environment variables from command-line arguments
-        if let Some(value) = matches.get_one::<String>("cache_capacity") {
-            env::set_var("ATV_HNSW_INDEX_CACHE_CAPACITY", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("db_name") {
-            env::set_var("ATV_DB_NAME", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("log_file") {
-            env::set_var("ATV_LOG_FILE", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("log_level") {
-            env::set_var("ATV_LOG_LEVEL", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("service_log_file") {
-            env::set_var("ATV_SERVICE_LOG_FILE", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("service_log_level") {
-            env::set_var("ATV_SERVICE_LOG_LEVEL", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("m") {
-            env::set_var("ATV_DEFAULT_M", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("ef_construction") {
-            env::set_var("ATV_DEFAULT_EF_CONSTRUCTION", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("max_datasize") {
-            env::set_var("ATV_HNSW_MAX_DATASIZE", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("data_path") {
-            env::set_var("ATV_DATA_PATH", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("token_expire_days") {
-            env::set_var("ATV_DEFAULT_TOKEN_EXPIRE_DAYS", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("jwt_token_key") {
-            env::set_var("ATV_JWT_TOKEN_KEY", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("enable_security") {
-            env::set_var("ATV_ENABLE_SECURITY", value);
-        }
-
-        if matches.get_flag("enable_swagger_ui") {
-            env::set_var("ATV_ENABLE_SWAGGER_UI", "true");
-        }
-
-        if let Some(value) = matches.get_one::<String>("id") {
-            env::set_var("ATV_INSTANCE_ID", value);
-        }
-
-        if matches.get_flag("standalone") {
-            env::set_var("ATV_STANDALONE", "true");
-        }
-
-        if let Some(value) = matches.get_one::<String>("http_addr") {
-            env::set_var("ATV_HTTP_ADDR", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("rpc_addr") {
-            env::set_var("ATV_RPC_ADDR", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("raft_heartbeat_interval") {
-            env::set_var("ATV_RAFT_HEARTBEAT_INTERVAL", value);
-        }
-
-        if let Some(value) = matches.get_one::<String>("raft_election_timeout") {
-            env::set_var("ATV_RAFT_ELECTION_TIMEOUT", value);
-        }
-    }
-
-    // Dynamic getters that always read from the environment
-    pub fn cache_capacity() -> i64 {
-        env::var("ATV_HNSW_INDEX_CACHE_CAPACITY")
-            .unwrap_or_else(|_| "100".to_string())
-            .parse::<i64>()
-            .unwrap_or(100)
-    }
-
-    pub fn db_name() -> String {
-        env::var("ATV_DB_NAME").unwrap_or_else(|_| ":memory:".to_string())
-    }
-
-    pub fn log_file() -> String {
-        env::var("ATV_LOG_FILE").unwrap_or_else(|_| "logs/atinyvectors.log".to_string())
-    }
-
-    pub fn log_level() -> String {
-        env::var("ATV_LOG_LEVEL").unwrap_or_else(|_| "info".to_string())
-    }
-
-    pub fn service_log_file() -> String {
-        env::var("ATV_SERVICE_LOG_FILE").unwrap_or_else(|_| "logs/asimplevectors.log".to_string())
-    }
-
-    pub fn service_log_level() -> String {
-        env::var("ATV_SERVICE_LOG_LEVEL").unwrap_or_else(|_| "info".to_string())
-    }
-
-    pub fn default_m() -> i64 {
-        env::var("ATV_DEFAULT_M")
-            .unwrap_or_else(|_| "16".to_string())
-            .parse::<i64>()
-            .unwrap_or(16)
-    }
-
-    pub fn ef_construction() -> i64 {
-        env::var("ATV_DEFAULT_EF_CONSTRUCTION")
-            .unwrap_or_else(|_| "100".to_string())
-            .parse::<i64>()
-            .unwrap_or(100)
-    }
-
-    pub fn max_datasize() -> i64 {
-        env::var("ATV_HNSW_MAX_DATASIZE")
-            .unwrap_or_else(|_| "1000000".to_string())
-            .parse::<i64>()
-            .unwrap_or(1_000_000)
-    }
-
-    pub fn data_path() -> String {
-        env::var("ATV_DATA_PATH").unwrap_or_else(|_| "data/".to_string())
-    }
-
-    pub fn token_expire_days() -> i64 {
-        env::var("ATV_DEFAULT_TOKEN_EXPIRE_DAYS")
-            .unwrap_or_else(|_| "30".to_string())
-            .parse::<i64>()
-            .unwrap_or(30)
-    }
-
-    pub fn jwt_token_key() -> String {
-        env::var("ATV_JWT_TOKEN_KEY").unwrap_or_else(|_| {
-            "atinyvectors_jwt_token_key_is_really_good_and_i_hope_so_much_whatever_you_want".to_string()
-        })
-    }
-
-    pub fn enable_security() -> i64 {
-        env::var("ATV_ENABLE_SECURITY")
-            .unwrap_or_else(|_| "0".to_string())
-            .parse::<i64>()
-            .unwrap_or(0)
-    }
-
-    pub fn enable_swagger_ui() -> bool {
-        env::var("ATV_ENABLE_SWAGGER_UI")
-            .unwrap_or_else(|_| "false".to_string())
-            .parse::<bool>()
-            .unwrap_or(false)
-    }
-
-    pub fn standalone() -> bool {
-        env::var("ATV_STANDALONE")
-            .unwrap_or_else(|_| "false".to_string())
-            .parse::<bool>()
-            .unwrap_or(false)
-    }
-
-    pub fn instance_id() -> u64 {
-        env::var("ATV_INSTANCE_ID")
-        .unwrap_or_else(|_| "1".to_string())
-        .parse::<u64>()
-        .unwrap_or(1)
-    }
-
-    pub fn http_addr() -> String {
-        env::var("ATV_HTTP_ADDR").unwrap_or_else(|_| "0.0.0.0:21001".to_string())
-    }
-
-    pub fn rpc_addr() -> String {
-        env::var("ATV_RPC_ADDR").unwrap_or_else(|_| "0.0.0.0:22001".to_string())
-    }
-
-    pub fn raft_heartbeat_interval() -> u64 {
-        env::var("ATV_RAFT_HEARTBEAT_INTERVAL")
-            .unwrap_or_else(|_| "250".to_string())
-            .parse::<u64>()
-            .unwrap_or(250)
-    }
-
-    pub fn raft_election_timeout() -> u64 {
-        env::var("ATV_RAFT_ELECTION_TIMEOUT")
-            .unwrap_or_else(|_| "299".to_string())
-            .parse::<u64>()
-            .unwrap_or(299)
-    }
-
-    /// Method to get the singleton Config instance
-    pub fn get_config() -> &'static Mutex<Config> {
-        &CONFIG
-    }
-}
+use std::env;
+use clap::{Arg, ArgAction, Command};
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// Config struct that dynamically fetches values from environment variables.
+#[derive(Debug)]
+pub struct Config;
+
+// Create a singleton for Config
+static CONFIG: Lazy<Mutex<Config>> = Lazy::new(|| Mutex::new(Config::new()));
+
+/// Implement Config struct
+impl Config {
+    /// Creates a new Config instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Initialize the configuration and update environment variables if needed
+    pub fn initialize() {
+        let matches = Command::new("asimplevectors")
+            .version(env!("CARGO_PKG_VERSION")) // Uses the version defined in Cargo.toml
+            .author("billionvectors.com")
+            .about("Handles config with environment variables and command-line arguments")
+            .arg(
+                Arg::new("cache_capacity")
+                    .long("cache_capacity")
+                    .action(ArgAction::Set)
+                    .help("Set cache capacity for HNSW index"),
+            )
+            .arg(
+                Arg::new("db_name")
+                    .long("db_name")
+                    .action(ArgAction::Set)
+                    .help("Set the database name"),
+            )
+            .arg(
+                Arg::new("log_file")
+                    .long("log_file")
+                    .action(ArgAction::Set)
+                    .help("Set the log file path"),
+            )
+            .arg(
+                Arg::new("log_level")
+                    .long("log_level")
+                    .action(ArgAction::Set)
+                    .help("Set the log level (e.g., info, debug)"),
+            )
+            .arg(
+                Arg::new("service_log_file")
+                    .long("service_log_file")
+                    .action(ArgAction::Set)
+                    .help("Set the service log file path"),
+            )
+            .arg(
+                Arg::new("service_log_level")
+                    .long("service_log_level")
+                    .action(ArgAction::Set)
+                    .help("Set the service log level (e.g., info, debug)"),
+            )
+            .arg(
+                Arg::new("m")
+                    .long("m")
+                    .action(ArgAction::Set)
+                    .help("Set the default M value"),
+            )
+            .arg(
+                Arg::new("ef_construction")
+                    .long("ef_construction")
+                    .action(ArgAction::Set)
+                    .help("Set the EF construction value"),
+            )
+            .arg(
+                Arg::new("max_datasize")
+                    .long("max_datasize")
+                    .action(ArgAction::Set)
+                    .help("Set the maximum data size"),
+            )
+            .arg(
+                Arg::new("data_path")
+                    .long("data_path")
+                    .action(ArgAction::Set)
+                    .help("Set the data path"),
+            )
+            .arg(
+                Arg::new("token_expire_days")
+                    .long("token_expire_days")
+                    .action(ArgAction::Set)
+                    .help("Set token expiration days"),
+            )
+            .arg(
+                Arg::new("jwt_token_key")
+                    .long("jwt_token_key")
+                    .action(ArgAction::Set)
+                    .help("Set the JWT token key"),
+            )
+            .arg(
+                Arg::new("enable_security")
+                    .long("enable_security")
+                    .action(ArgAction::Set)
+                    .help("Enable or disable security (1: enable or 0: disable)"),
+            )
+            .arg(
+                Arg::new("enable_swagger_ui")
+                    .long("enable_swagger_ui")
+                    .action(ArgAction::SetTrue)
+                    .help("Enable or disable Swagger UI (true: enable, false: disable)"),
+            )
+            .arg(
+                Arg::new("id")
+                    .long("id")
+                    .action(ArgAction::Set)
+                    .help("Set the instance ID"),
+            )
+            .arg(
+                Arg::new("standalone")
+                    .long("standalone")
+                    .action(ArgAction::SetTrue)
+                    .help("Set the standalone node (true: standalone, false: cluster)"),
+            )
+            .arg(
+                Arg::new("http_addr")
+                    .long("http-addr")
+                    .action(ArgAction::Set)
+                    .help("Set the HTTP address"),
+            )
+            .arg(
+                Arg::new("rpc_addr")
+                    .long("rpc-addr")
+                    .action(ArgAction::Set)
+                    .help("Set the RPC address"),
+            )
+            .arg(
+                Arg::new("raft_heartbeat_interval")
+                    .long("raft_heartbeat_interval")
+                    .action(ArgAction::Set)
+                    .help("Set the Raft heartbeat interval (ms)"),
+            )
+            .arg(
+                Arg::new("raft_election_timeout")
+                    .long("raft_election_timeout")
+                    .action(ArgAction::Set)
+                    .help("Set the Raft election timeout (ms)"),
+            )
+            .arg(
+                Arg::new("raft_snapshot_chunk_size")
+                    .long("raft_snapshot_chunk_size")
+                    .action(ArgAction::Set)
+                    .help("Set the size of the chunks a Raft snapshot is sent in (bytes)"),
+            )
+            .arg(
+                Arg::new("raft_snapshot_logs_since_last")
+                    .long("raft_snapshot_logs_since_last")
+                    .action(ArgAction::Set)
+                    .help("Build a Raft snapshot every N applied logs, 0 disables automatic snapshots"),
+            )
+            .arg(
+                Arg::new("raft_max_in_snapshot_log_to_keep")
+                    .long("raft_max_in_snapshot_log_to_keep")
+                    .action(ArgAction::Set)
+                    .help("Set the number of logs kept after they are included in a Raft snapshot"),
+            )
+            .arg(
+                Arg::new("raft_purge_batch_size")
+                    .long("raft_purge_batch_size")
+                    .action(ArgAction::Set)
+                    .help("Set the minimum number of logs purged at once"),
+            )
+            .arg(
+                Arg::new("raft_log_format")
+                    .long("raft_log_format")
+                    .action(ArgAction::Set)
+                    .help("Set the encoding of new Raft log entries and of replicated entries (binary, json)"),
+            )
+            .arg(
+                Arg::new("raft_log_sync")
+                    .long("raft_log_sync")
+                    .action(ArgAction::Set)
+                    .help("Set when appended Raft logs are fsynced (batch, periodic, none)"),
+            )
+            .arg(
+                Arg::new("raft_log_sync_interval")
+                    .long("raft_log_sync_interval")
+                    .action(ArgAction::Set)
+                    .help("Set the fsync interval of Raft logs in periodic sync mode (ms)"),
+            )
+            .arg(
+                Arg::new("leader_forwarding")
+                    .long("leader_forwarding")
+                    .action(ArgAction::Set)
+                    .help("How a follower handles writes: proxy (forward to the leader), redirect (307 to the leader) or off"),
+            )
+            .arg(
+                Arg::new("seeds")
+                    .long("seeds")
+                    .action(ArgAction::Set)
+                    .help("Set the HTTP addresses of the seed nodes used to bootstrap or join a cluster (comma separated)"),
+            )
+            .arg(
+                Arg::new("expected_cluster_size")
+                    .long("expected_cluster_size")
+                    .action(ArgAction::Set)
+                    .help("Set how many seed nodes must be reachable before a new cluster is bootstrapped (default: number of seeds)"),
+            )
+            .arg(
+                Arg::new("advertise_http_addr")
+                    .long("advertise_http_addr")
+                    .action(ArgAction::Set)
+                    .help("Set the HTTP address other nodes use to reach this node (default: http-addr)"),
+            )
+            .arg(
+                Arg::new("advertise_rpc_addr")
+                    .long("advertise_rpc_addr")
+                    .action(ArgAction::Set)
+                    .help("Set the RPC address other nodes use to reach this node (default: rpc-addr)"),
+            )
+            .arg(
+                Arg::new("cluster_secret")
+                    .long("cluster_secret")
+                    .action(ArgAction::Set)
+                    .help("Set the secret shared by the nodes of a cluster, required on Raft RPCs and accepted on /cluster endpoints"),
+            )
+            .arg(
+                Arg::new("tls_cert")
+                    .long("tls_cert")
+                    .action(ArgAction::Set)
+                    .help("Set the PEM certificate chain of this node; with tls_key set, the API is served over HTTPS and Raft RPCs over wss"),
+            )
+            .arg(
+                Arg::new("tls_key")
+                    .long("tls_key")
+                    .action(ArgAction::Set)
+                    .help("Set the PEM private key of the tls_cert certificate"),
+            )
+            .arg(
+                Arg::new("tls_ca")
+                    .long("tls_ca")
+                    .action(ArgAction::Set)
+                    .help("Set the PEM CA certificates that sign the certificates of the nodes; when set, nodes also authenticate each other with their certificates (mTLS)"),
+            )
+            .arg(
+                Arg::new("cluster_id")
+                    .long("cluster_id")
+                    .action(ArgAction::Set)
+                    .help("Set the id of the cluster this node belongs to, recorded in the data directory and checked on startup"),
+            )
+            .arg(
+                Arg::new("node_role")
+                    .long("node_role")
+                    .action(ArgAction::Set)
+                    .help("Set the role of this node: voter, or replica (a read-only learner that is never promoted)"),
+            )
+            .arg(
+                Arg::new("shard_groups")
+                    .long("shard_groups")
+                    .action(ArgAction::Set)
+                    .help("API addresses of the nodes of every shard group, groups separated by ';' and nodes by ','. A node serves the groups listing its advertised API address"),
+            )
+            .arg(
+                Arg::new("admin_token")
+                    .long("admin_token")
+                    .action(ArgAction::Set)
+                    .help("Set a bootstrap token holding every permission, used to create the first tokens when security is enabled"),
+            )
+            .arg(
+                Arg::new("search_batch_workers")
+                    .long("search_batch_workers")
+                    .action(ArgAction::Set)
+                    .help("Set how many queries of batch searches run at the same time over all requests, defaults to the number of CPUs"),
+            )
+            .get_matches();
+
+        // Check and update environment variables from command-line arguments
+        if let Some(value) = matches.get_one::<String>("cache_capacity") {
+            env::set_var("ATV_HNSW_INDEX_CACHE_CAPACITY", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("db_name") {
+            env::set_var("ATV_DB_NAME", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("log_file") {
+            env::set_var("ATV_LOG_FILE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("log_level") {
+            env::set_var("ATV_LOG_LEVEL", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("service_log_file") {
+            env::set_var("ATV_SERVICE_LOG_FILE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("service_log_level") {
+            env::set_var("ATV_SERVICE_LOG_LEVEL", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("m") {
+            env::set_var("ATV_DEFAULT_M", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("ef_construction") {
+            env::set_var("ATV_DEFAULT_EF_CONSTRUCTION", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("max_datasize") {
+            env::set_var("ATV_HNSW_MAX_DATASIZE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("data_path") {
+            env::set_var("ATV_DATA_PATH", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("token_expire_days") {
+            env::set_var("ATV_DEFAULT_TOKEN_EXPIRE_DAYS", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("jwt_token_key") {
+            env::set_var("ATV_JWT_TOKEN_KEY", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("enable_security") {
+            env::set_var("ATV_ENABLE_SECURITY", value);
+        }
+
+        if matches.get_flag("enable_swagger_ui") {
+            env::set_var("ATV_ENABLE_SWAGGER_UI", "true");
+        }
+
+        if let Some(value) = matches.get_one::<String>("id") {
+            env::set_var("ATV_INSTANCE_ID", value);
+        }
+
+        if matches.get_flag("standalone") {
+            env::set_var("ATV_STANDALONE", "true");
+        }
+
+        if let Some(value) = matches.get_one::<String>("http_addr") {
+            env::set_var("ATV_HTTP_ADDR", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("rpc_addr") {
+            env::set_var("ATV_RPC_ADDR", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_heartbeat_interval") {
+            env::set_var("ATV_RAFT_HEARTBEAT_INTERVAL", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_election_timeout") {
+            env::set_var("ATV_RAFT_ELECTION_TIMEOUT", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_snapshot_chunk_size") {
+            env::set_var("ATV_RAFT_SNAPSHOT_CHUNK_SIZE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_snapshot_logs_since_last") {
+            env::set_var("ATV_RAFT_SNAPSHOT_LOGS_SINCE_LAST", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_max_in_snapshot_log_to_keep") {
+            env::set_var("ATV_RAFT_MAX_IN_SNAPSHOT_LOG_TO_KEEP", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_purge_batch_size") {
+            env::set_var("ATV_RAFT_PURGE_BATCH_SIZE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_log_format") {
+            env::set_var("ATV_RAFT_LOG_FORMAT", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_log_sync") {
+            env::set_var("ATV_RAFT_LOG_SYNC", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("raft_log_sync_interval") {
+            env::set_var("ATV_RAFT_LOG_SYNC_INTERVAL", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("leader_forwarding") {
+            env::set_var("ATV_LEADER_FORWARDING", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("seeds") {
+            env::set_var("ATV_SEEDS", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("expected_cluster_size") {
+            env::set_var("ATV_EXPECTED_CLUSTER_SIZE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("advertise_http_addr") {
+            env::set_var("ATV_ADVERTISE_HTTP_ADDR", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("advertise_rpc_addr") {
+            env::set_var("ATV_ADVERTISE_RPC_ADDR", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("cluster_secret") {
+            env::set_var("ATV_CLUSTER_SECRET", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("tls_cert") {
+            env::set_var("ATV_TLS_CERT", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("tls_key") {
+            env::set_var("ATV_TLS_KEY", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("tls_ca") {
+            env::set_var("ATV_TLS_CA", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("cluster_id") {
+            env::set_var("ATV_CLUSTER_ID", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("node_role") {
+            env::set_var("ATV_NODE_ROLE", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("shard_groups") {
+            env::set_var("ATV_SHARD_GROUPS", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("admin_token") {
+            env::set_var("ATV_ADMIN_TOKEN", value);
+        }
+
+        if let Some(value) = matches.get_one::<String>("search_batch_workers") {
+            env::set_var("ATV_SEARCH_BATCH_WORKERS", value);
+        }
+    }
+
+    // Dynamic getters that always read from the environment
+    pub fn cache_capacity() -> i64 {
+        env::var("ATV_HNSW_INDEX_CACHE_CAPACITY")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<i64>()
+            .unwrap_or(100)
+    }
+
+    pub fn db_name() -> String {
+        env::var("ATV_DB_NAME").unwrap_or_else(|_| ":memory:".to_string())
+    }
+
+    pub fn log_file() -> String {
+        env::var("ATV_LOG_FILE").unwrap_or_else(|_| "logs/atinyvectors.log".to_string())
+    }
+
+    pub fn log_level() -> String {
+        env::var("ATV_LOG_LEVEL").unwrap_or_else(|_| "info".to_string())
+    }
+
+    pub fn service_log_file() -> String {
+        env::var("ATV_SERVICE_LOG_FILE").unwrap_or_else(|_| "logs/asimplevectors.log".to_string())
+    }
+
+    pub fn service_log_level() -> String {
+        env::var("ATV_SERVICE_LOG_LEVEL").unwrap_or_else(|_| "info".to_string())
+    }
+
+    pub fn default_m() -> i64 {
+        env::var("ATV_DEFAULT_M")
+            .unwrap_or_else(|_| "16".to_string())
+            .parse::<i64>()
+            .unwrap_or(16)
+    }
+
+    pub fn ef_construction() -> i64 {
+        env::var("ATV_DEFAULT_EF_CONSTRUCTION")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<i64>()
+            .unwrap_or(100)
+    }
+
+    pub fn max_datasize() -> i64 {
+        env::var("ATV_HNSW_MAX_DATASIZE")
+            .unwrap_or_else(|_| "1000000".to_string())
+            .parse::<i64>()
+            .unwrap_or(1_000_000)
+    }
+
+    pub fn data_path() -> String {
+        env::var("ATV_DATA_PATH").unwrap_or_else(|_| "data/".to_string())
+    }
+
+    pub fn token_expire_days() -> i64 {
+        env::var("ATV_DEFAULT_TOKEN_EXPIRE_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse::<i64>()
+            .unwrap_or(30)
+    }
+
+    pub fn jwt_token_key() -> String {
+        env::var("ATV_JWT_TOKEN_KEY").unwrap_or_else(|_| {
+            "atinyvectors_jwt_token_key_is_really_good_and_i_hope_so_much_whatever_you_want".to_string()
+        })
+    }
+
+    pub fn enable_security() -> i64 {
+        env::var("ATV_ENABLE_SECURITY")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<i64>()
+            .unwrap_or(0)
+    }
+
+    pub fn enable_swagger_ui() -> bool {
+        env::var("ATV_ENABLE_SWAGGER_UI")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .unwrap_or(false)
+    }
+
+    pub fn standalone() -> bool {
+        env::var("ATV_STANDALONE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .unwrap_or(false)
+    }
+
+    pub fn instance_id() -> u64 {
+        env::var("ATV_INSTANCE_ID")
+        .unwrap_or_else(|_| "1".to_string())
+        .parse::<u64>()
+        .unwrap_or(1)
+    }
+
+    pub fn http_addr() -> String {
+        env::var("ATV_HTTP_ADDR").unwrap_or_else(|_| "0.0.0.0:21001".to_string())
+    }
+
+    pub fn rpc_addr() -> String {
+        env::var("ATV_RPC_ADDR").unwrap_or_else(|_| "0.0.0.0:22001".to_string())
+    }
+
+    pub fn raft_heartbeat_interval() -> u64 {
+        env::var("ATV_RAFT_HEARTBEAT_INTERVAL")
+            .unwrap_or_else(|_| "250".to_string())
+            .parse::<u64>()
+            .unwrap_or(250)
+    }
+
+    pub fn raft_election_timeout() -> u64 {
+        env::var("ATV_RAFT_ELECTION_TIMEOUT")
+            .unwrap_or_else(|_| "299".to_string())
+            .parse::<u64>()
+            .unwrap_or(299)
+    }
+
+    pub fn raft_snapshot_chunk_size() -> u64 {
+        env::var("ATV_RAFT_SNAPSHOT_CHUNK_SIZE")
+            .unwrap_or_else(|_| "3145728".to_string())
+            .parse::<u64>()
+            .unwrap_or(3 * 1024 * 1024)
+    }
+
+    pub fn raft_snapshot_logs_since_last() -> u64 {
+        env::var("ATV_RAFT_SNAPSHOT_LOGS_SINCE_LAST")
+            .unwrap_or_else(|_| "5000".to_string())
+            .parse::<u64>()
+            .unwrap_or(5000)
+    }
+
+    pub fn raft_max_in_snapshot_log_to_keep() -> u64 {
+        env::var("ATV_RAFT_MAX_IN_SNAPSHOT_LOG_TO_KEEP")
+            .unwrap_or_else(|_| "1000".to_string())
+            .parse::<u64>()
+            .unwrap_or(1000)
+    }
+
+    pub fn raft_purge_batch_size() -> u64 {
+        env::var("ATV_RAFT_PURGE_BATCH_SIZE")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse::<u64>()
+            .unwrap_or(1)
+    }
+
+    pub fn raft_log_format() -> String {
+        env::var("ATV_RAFT_LOG_FORMAT").unwrap_or_else(|_| "binary".to_string())
+    }
+
+    pub fn raft_log_sync() -> String {
+        env::var("ATV_RAFT_LOG_SYNC").unwrap_or_else(|_| "batch".to_string())
+    }
+
+    pub fn raft_log_sync_interval() -> u64 {
+        env::var("ATV_RAFT_LOG_SYNC_INTERVAL")
+            .unwrap_or_else(|_| "100".to_string())
+            .parse::<u64>()
+            .unwrap_or(100)
+    }
+
+    pub fn leader_forwarding() -> String {
+        env::var("ATV_LEADER_FORWARDING").unwrap_or_else(|_| "proxy".to_string())
+    }
+
+    pub fn seeds() -> Vec<String> {
+        env::var("ATV_SEEDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    pub fn expected_cluster_size() -> usize {
+        env::var("ATV_EXPECTED_CLUSTER_SIZE")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse::<usize>()
+            .unwrap_or(0)
+    }
+
+    pub fn advertise_http_addr() -> String {
+        env::var("ATV_ADVERTISE_HTTP_ADDR").unwrap_or_else(|_| Self::http_addr())
+    }
+
+    pub fn advertise_rpc_addr() -> String {
+        env::var("ATV_ADVERTISE_RPC_ADDR").unwrap_or_else(|_| Self::rpc_addr())
+    }
+
+    pub fn cluster_secret() -> String {
+        env::var("ATV_CLUSTER_SECRET").unwrap_or_default()
+    }
+
+    pub fn tls_cert() -> String {
+        env::var("ATV_TLS_CERT").unwrap_or_default()
+    }
+
+    pub fn tls_key() -> String {
+        env::var("ATV_TLS_KEY").unwrap_or_default()
+    }
+
+    pub fn tls_ca() -> String {
+        env::var("ATV_TLS_CA").unwrap_or_default()
+    }
+
+    pub fn cluster_id() -> String {
+        env::var("ATV_CLUSTER_ID").unwrap_or_default()
+    }
+
+    pub fn node_role() -> String {
+        env::var("ATV_NODE_ROLE").unwrap_or_else(|_| "voter".to_string())
+    }
+
+    pub fn shard_groups() -> Vec<Vec<String>> {
+        env::var("ATV_SHARD_GROUPS")
+            .unwrap_or_default()
+            .split(';')
+            .map(|group| {
+                group
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<String>>()
+            })
+            .filter(|group| !group.is_empty())
+            .collect()
+    }
+
+    pub fn admin_token() -> String {
+        env::var("ATV_ADMIN_TOKEN").unwrap_or_default()
+    }
+
+    pub fn search_batch_workers() -> usize {
+        env::var("ATV_SEARCH_BATCH_WORKERS")
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|workers| *workers > 0)
+            .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4))
+    }
+
+    /// Method to get the singleton Config instance
+    pub fn get_config() -> &'static Mutex<Config> {
+        &CONFIG
+    }
+}
@@ -53,13 +53,13 @@ async fn main() -> std::io::Result<()> {
 
     let id = Config::instance_id();
     let rpc_addr = Config::rpc_addr();
-    let db_path = format!("{}.db", rpc_addr);
+    let data_path = Config::data_path();
     let http_addr = Config::http_addr();
 
     // Start the Raft node with the retrieved configuration values
     raft_cluster::start_raft_node(
         id,
-        db_path,
+        data_path,
         http_addr,
         rpc_addr,
     )
@@ -1,24 +1,52 @@
-use std::collections::BTreeMap;
-use std::sync::Arc;
-
-use openraft::Config;
-use tokio::sync::RwLock;
-
-use crate::raft_cluster::RaftCluster;
-use crate::raft_cluster::NodeId;
-
-use crate::atinyvectors::atinyvectors_raft_command::ATinyVectorsRaftCommand;
-use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
-
-// Representation of an application state. This struct can be shared around to share
-// instances of raft, store and more.
-pub struct App {
-    pub id: NodeId,
-    pub api_addr: String,
-    pub rpc_addr: String,
-    pub raft: RaftCluster,
-    pub key_values: Arc<RwLock<BTreeMap<String, String>>>,
-    pub config: Arc<Config>,
-    pub atinyvectors_bo: Arc<ATinyVectorsBO>,
-    pub atinyvectors_command: Arc<ATinyVectorsRaftCommand>,
-}
\ No newline at end of file
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use openraft::Config;
+use tokio::sync::RwLock;
+
+use crate::raft_cluster::cluster_auth;
+use crate::raft_cluster::compaction::CompactionStatus;
+use crate::raft_cluster::network::peer_stats::PeerStatsRegistry;
+use crate::raft_cluster::RaftCluster;
+use crate::raft_cluster::NodeId;
+
+use crate::atinyvectors::atinyvectors_raft_command::ATinyVectorsRaftCommand;
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+use crate::service::sharding::SHARD_HEADER;
+
+// Representation of an application state. This struct can be shared around to share
+// instances of raft, store and more.
+pub struct App {
+    pub id: NodeId,
+    pub api_addr: String,
+    pub rpc_addr: String,
+    /// Shard group this instance belongs to, `None` without sharding. A node keeps one `App`
+    /// per group it serves.
+    pub shard: Option<usize>,
+    /// Whether other groups apply their writes to the atinyvectors engine of the nodes of this
+    /// group as well, see `ShardMap::shares_engine`.
+    pub shares_engine: bool,
+    /// Whether this group takes no snapshots, see `StateMachineStore::keeps_whole_log`.
+    pub keeps_whole_log: bool,
+    pub raft: RaftCluster,
+    pub key_values: Arc<RwLock<BTreeMap<String, String>>>,
+    pub config: Arc<Config>,
+    pub atinyvectors_bo: Arc<ATinyVectorsBO>,
+    pub atinyvectors_command: Arc<ATinyVectorsRaftCommand>,
+    pub compaction: Arc<RwLock<CompactionStatus>>,
+    // tide handlers run on async-std, work that needs tokio is spawned here.
+    pub runtime: tokio::runtime::Handle,
+    pub peer_stats: Arc<PeerStatsRegistry>,
+}
+
+impl App {
+    /// Prepares a request to the same group on another node: signed with the cluster secret
+    /// and addressed to the group.
+    pub fn sign(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let builder = cluster_auth::sign(builder);
+        match self.shard {
+            Some(shard) => builder.header(SHARD_HEADER, shard.to_string()),
+            None => builder,
+        }
+    }
+}
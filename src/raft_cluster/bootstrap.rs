@@ -0,0 +1,182 @@
+//! Seed based cluster formation.
+//!
+//! A node started with `seeds` looks for a cluster among them. If one exists it registers
+//! itself as a learner through the leader, which returns once the node has caught up, and then
+//! asks to be promoted to voter. If none exists yet, the voter with the lowest id among the
+//! reachable seeds initializes the cluster once `expected_cluster_size` voters are up, and the
+//! others join it the same way. Replica nodes join as learners and are never promoted.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use openraft::error::Infallible;
+use openraft::RaftMetrics;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::membership::MembershipError;
+use crate::raft_cluster::membership::MembershipReport;
+use crate::raft_cluster::tls;
+use crate::raft_cluster::typ;
+use crate::raft_cluster::Node;
+use crate::raft_cluster::NodeRole;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::TypeConfig;
+
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Keeps trying until this node is a member of a cluster formed from `seeds`.
+pub async fn run(app: Arc<App>, seeds: Vec<String>, expected_cluster_size: usize) {
+    let expected = match expected_cluster_size {
+        0 => seeds.len(),
+        n => n,
+    };
+    let client = tls::http_client();
+    let me = Node {
+        api_addr: crate::Config::advertise_http_addr(),
+        rpc_addr: crate::Config::advertise_rpc_addr(),
+        role: NodeRole::from_config(),
+    };
+
+    tracing::info!("Bootstrapping from seeds {:?}, expected cluster size {}", seeds, expected);
+    loop {
+        match step(&app, &client, &seeds, expected, &me).await {
+            Ok(true) => {
+                tracing::info!("Node {} joined the cluster as {:?}", app.id, me.role);
+                return;
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Cluster bootstrap: {}", e),
+        }
+        tokio::time::sleep(RETRY_INTERVAL).await;
+    }
+}
+
+/// One bootstrap round, `Ok(true)` once this node is a voter, or a learner for a replica.
+async fn step(
+    app: &App,
+    client: &reqwest::Client,
+    seeds: &[String],
+    expected: usize,
+    me: &Node,
+) -> Result<bool, String> {
+    let metrics = app.raft.metrics().borrow().clone();
+    let membership = metrics.membership_config.membership();
+    let known = membership.get_node(&app.id).is_some();
+    if membership.voter_ids().any(|id| id == app.id) || (known && me.role == NodeRole::Replica) {
+        return Ok(true);
+    }
+
+    let peers: Vec<(RaftMetrics<TypeConfig>, NodeRole)> = probe_seeds(app, client, seeds).await
+        .into_iter()
+        .filter(|(m, _)| m.id != app.id)
+        .collect();
+
+    if known || peers.iter().any(|(m, _)| is_initialized(m)) {
+        let leader_addr = leader_api_addr(&metrics)
+            .or_else(|| peers.iter().find_map(|(m, _)| leader_api_addr(m)))
+            .ok_or_else(|| "the cluster has no leader yet".to_string())?;
+
+        if !known {
+            tracing::info!("Joining the cluster through the leader at {}", leader_addr);
+            let req = (app.id, &me.api_addr, &me.rpc_addr, me.role);
+            let res: Result<typ::ClientWriteResponse, typ::RaftError<typ::ClientWriteError>> =
+                post(app, client, &leader_addr, "cluster/add-learner", &req, None).await?;
+            res.map_err(|e| format!("add-learner failed: {}", e))?;
+        }
+        if me.role == NodeRole::Replica {
+            return Ok(true);
+        }
+
+        let res: Result<MembershipReport, MembershipError> =
+            post(app, client, &leader_addr, "cluster/promote", &app.id, Some(PROBE_TIMEOUT)).await?;
+        let report = res.map_err(|e| format!("promote failed: {}", e))?;
+        return Ok(report.voters.contains(&app.id));
+    }
+
+    // No cluster yet: the lowest id among the reachable voters bootstraps it.
+    if me.role == NodeRole::Replica {
+        tracing::debug!("Waiting for the voters to form the cluster");
+        return Ok(false);
+    }
+    let reachable: BTreeSet<NodeId> = peers
+        .iter()
+        .filter(|(_, role)| *role == NodeRole::Voter)
+        .map(|(m, _)| m.id)
+        .chain([app.id])
+        .collect();
+    if reachable.len() < expected {
+        tracing::debug!("Waiting for seeds: {} of {} reachable", reachable.len(), expected);
+        return Ok(false);
+    }
+    if reachable.first() != Some(&app.id) {
+        return Ok(false);
+    }
+
+    tracing::info!("Initializing the cluster with node {}, reachable nodes: {:?}", app.id, reachable);
+    app.raft
+        .initialize(BTreeMap::from([(app.id, me.clone())]))
+        .await
+        .map_err(|e| format!("initialize failed: {}", e))?;
+    Ok(true)
+}
+
+fn is_initialized(metrics: &RaftMetrics<TypeConfig>) -> bool {
+    metrics.membership_config.membership().voter_ids().next().is_some()
+}
+
+fn leader_api_addr(metrics: &RaftMetrics<TypeConfig>) -> Option<String> {
+    let leader = metrics.current_leader?;
+    metrics.membership_config.membership().get_node(&leader).map(|node| node.api_addr.clone())
+}
+
+/// Metrics and role of every reachable seed, in the group of `app`.
+async fn probe_seeds(app: &App, client: &reqwest::Client, seeds: &[String]) -> Vec<(RaftMetrics<TypeConfig>, NodeRole)> {
+    let mut found = Vec::new();
+    for seed in seeds {
+        let res = app.sign(client.get(format!("{}/cluster/metrics", tls::base_url(seed))))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await;
+        let metrics = match res {
+            Ok(response) => response.json::<Result<serde_json::Value, Infallible>>().await,
+            Err(e) => {
+                tracing::debug!("Seed {} is not reachable: {}", seed, e);
+                continue;
+            }
+        };
+        if let Ok(Ok(metrics)) = metrics {
+            // `role` is added to the metrics by `/cluster/metrics`; older nodes are voters.
+            let role = metrics.get("role").and_then(|r| serde_json::from_value(r.clone()).ok()).unwrap_or_default();
+            if let Ok(metrics) = serde_json::from_value::<RaftMetrics<TypeConfig>>(metrics) {
+                found.push((metrics, role));
+            }
+        }
+    }
+    found
+}
+
+async fn post<Req, Resp>(
+    app: &App,
+    client: &reqwest::Client,
+    addr: &str,
+    uri: &str,
+    req: &Req,
+    timeout: Option<Duration>,
+) -> Result<Resp, String>
+where
+    Req: Serialize,
+    Resp: DeserializeOwned,
+{
+    let mut builder = app.sign(client.post(format!("{}/{}", tls::base_url(addr), uri))).json(req);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    let response = builder.send().await.map_err(|e| e.to_string())?;
+    response.json::<Resp>().await.map_err(|e| e.to_string())
+}
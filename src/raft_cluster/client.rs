@@ -1,236 +1,330 @@
-use std::collections::BTreeSet;
-use std::sync::Arc;
-use std::sync::Mutex;
-
-use openraft::error::NetworkError;
-use openraft::error::RPCError;
-use openraft::error::RemoteError;
-use openraft::error::Unreachable;
-use openraft::RaftMetrics;
-use openraft::TryAsRef;
-use reqwest::Client;
-use serde::de::DeserializeOwned;
-use serde::Deserialize;
-use serde::Serialize;
-use serde_json::Value;
-
-use crate::raft_cluster::typ;
-use crate::raft_cluster::NodeId;
-use crate::raft_cluster::Request;
-use crate::raft_cluster::TypeConfig;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Empty {}
-
-pub struct atinyvectorsClient {
-    /// The leader node to send request to.
-    ///
-    /// All traffic should be sent to the leader in a cluster.
-    pub leader: Arc<Mutex<(NodeId, String)>>,
-
-    pub inner: Client,
-}
-
-impl atinyvectorsClient {
-    /// Create a client with a leader node id and a node manager to get node address by node id.
-    pub fn new(leader_id: NodeId, leader_addr: String) -> Self {
-        Self {
-            leader: Arc::new(Mutex::new((leader_id, leader_addr))),
-            inner: Client::new(),
-        }
-    }
-
-    // --- Application API
-    /// Send a request to the space endpoint.
-    pub async fn space(&self, req: &Value) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader("api/space", Some(req)).await
-    }
-
-    /// Send a request to the vector endpoint.
-    pub async fn vector(&self, req: &Value) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader("api/space/vector", Some(req)).await
-    }
-
-    /// Send a request to the vector endpoint with a specific space_id and version_id.
-    pub async fn vector_with_version(&self, space_id: &str, version_id: &str, req: &Value) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader(&format!("api/space/{}/version/{}/vector", space_id, version_id), Some(req)).await
-    }
-
-    /// Send a request to the search endpoint.
-    pub async fn search(&self, space_id: &str, req: &Value) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader(&format!("api/space/{}/search", space_id), Some(req)).await
-    }
-
-    /// Send a request to the search endpoint with a specific space_id and version_id.
-    pub async fn search_with_version(&self, space_id: &str, version_id: &str, req: &Value) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader(&format!("api/space/{}/version/{}/search", space_id, version_id), Some(req)).await
-    }
-
-    /// Get a specific space by space_id.
-    pub async fn get_space(&self, space_id: &str) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader(&format!("api/space/{}", space_id), None::<&()>).await
-    }
-
-    /// List all spaces.
-    pub async fn list_spaces(&self) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader("api/space/list", None::<&()>).await
-    }
-
-    /// List all versions of spaces.
-    pub async fn list_space_versions(&self) -> Result<Value, typ::RPCError> {
-        self.do_send_rpc_to_leader("api/space/version/list", None::<&()>).await
-    }
-
-    /// Submit a write request to the raft cluster.
-    ///
-    /// The request will be processed by raft protocol: it will be replicated to a quorum and then
-    /// will be applied to state machine.
-    ///
-    /// The result of applying the request will be returned.
-    pub async fn write(&self, req: &Request) -> Result<typ::ClientWriteResponse, typ::RPCError<typ::ClientWriteError>> {
-        self.send_rpc_to_leader("api/debug/write", Some(req)).await
-    }
-
-    /// Read value by key, in an inconsistent mode.
-    ///
-    /// This method may return stale value because it does not force to read on a legal leader.
-    pub async fn read(&self, req: &String) -> Result<String, typ::RPCError> {
-        self.do_send_rpc_to_leader("api/debug/read", Some(req)).await
-    }
-
-    /// Consistent Read value by key, in an inconsistent mode.
-    ///
-    /// This method MUST return consistent value or CheckIsLeaderError.
-    pub async fn consistent_read(&self, req: &String) -> Result<String, typ::RPCError<typ::CheckIsLeaderError>> {
-        self.do_send_rpc_to_leader("api/debug/consistent_read", Some(req)).await
-    }
-
-    // --- Cluster management API
-
-    /// Initialize a cluster of only the node that receives this request.
-    ///
-    /// This is the first step to initialize a cluster.
-    /// With a initialized cluster, new node can be added with [`write`].
-    /// Then setup replication with [`add_learner`].
-    /// Then make the new node a member with [`change_membership`].
-    pub async fn init(&self) -> Result<(), typ::RPCError<typ::InitializeError>> {
-        self.do_send_rpc_to_leader("cluster/init", Some(&Empty {})).await
-    }
-
-    /// Add a node as learner.
-    ///
-    /// The node to add has to exist, i.e., being added with `write(ExampleRequest::AddNode{})`
-    pub async fn add_learner(
-        &self,
-        req: (NodeId, String, String),
-    ) -> Result<typ::ClientWriteResponse, typ::RPCError<typ::ClientWriteError>> {
-        self.send_rpc_to_leader("cluster/add-learner", Some(&req)).await
-    }
-
-    /// Change membership to the specified set of nodes.
-    ///
-    /// All nodes in `req` have to be already added as learner with [`add_learner`],
-    /// or an error [`LearnerNotFound`] will be returned.
-    pub async fn change_membership(
-        &self,
-        req: &BTreeSet<NodeId>,
-    ) -> Result<typ::ClientWriteResponse, typ::RPCError<typ::ClientWriteError>> {
-        self.send_rpc_to_leader("cluster/change-membership", Some(req)).await
-    }
-
-    /// Get the metrics about the cluster.
-    ///
-    /// Metrics contains various information about the cluster, such as current leader,
-    /// membership config, replication status etc.
-    /// See [`RaftMetrics`].
-    pub async fn metrics(&self) -> Result<RaftMetrics<TypeConfig>, typ::RPCError> {
-        self.do_send_rpc_to_leader("cluster/metrics", None::<&()>).await
-    }
-
-    // --- Internal methods
-
-    /// Send RPC to specified node.
-    ///
-    /// It sends out a POST request if `req` is Some. Otherwise a GET request.
-    /// The remote endpoint must respond a reply in form of `Result<T, E>`.
-    /// An `Err` happened on remote will be wrapped in an [`RPCError::RemoteError`].
-    async fn do_send_rpc_to_leader<Req, Resp, Err>(
-        &self,
-        uri: &str,
-        req: Option<&Req>,
-    ) -> Result<Resp, RPCError<TypeConfig, Err>>
-    where
-        Req: Serialize + 'static,
-        Resp: Serialize + DeserializeOwned,
-        Err: std::error::Error + Serialize + DeserializeOwned,
-    {
-        let (leader_id, url) = {
-            let t = self.leader.lock().unwrap();
-            let target_addr = &t.1;
-            (t.0, format!("http://{}/{}", target_addr, uri))
-        };
-
-        let resp = if let Some(r) = req {
-            self.inner.post(url.clone()).json(r)
-        } else {
-            self.inner.get(url.clone())
-        }
-        .send()
-        .await
-        .map_err(|e| {
-            if e.is_connect() {
-                return RPCError::Unreachable(Unreachable::new(&e));
-            }
-            RPCError::Network(NetworkError::new(&e))
-        })?;
-
-        let res: Result<Resp, Err> = resp.json().await.map_err(|e| RPCError::Network(NetworkError::new(&e)))?;
-        res.map_err(|e| RPCError::RemoteError(RemoteError::new(leader_id, e)))
-    }
-
-    /// Try the best to send a request to the leader.
-    ///
-    /// If the target node is not a leader, a `ForwardToLeader` error will be
-    /// returned and this client will retry at most 3 times to contact the updated leader.
-    async fn send_rpc_to_leader<Req, Resp, Err>(&self, uri: &str, req: Option<&Req>) -> Result<Resp, typ::RPCError<Err>>
-    where
-        Req: Serialize + 'static,
-        Resp: Serialize + DeserializeOwned,
-        Err: std::error::Error + Serialize + DeserializeOwned + TryAsRef<typ::ForwardToLeader> + Clone,
-    {
-        let mut n_retry = 3;
-
-        loop {
-            let res: Result<Resp, typ::RPCError<Err>> = self.do_send_rpc_to_leader(uri, req).await;
-
-            let rpc_err = match res {
-                Ok(x) => return Ok(x),
-                Err(rpc_err) => rpc_err,
-            };
-
-            if let RPCError::RemoteError(remote_err) = &rpc_err {
-                let raft_err: &typ::RaftError<_> = &remote_err.source;
-
-                if let Some(typ::ForwardToLeader {
-                    leader_id: Some(leader_id),
-                    leader_node: Some(leader_node),
-                    ..
-                }) = raft_err.forward_to_leader()
-                {
-                    {
-                        let mut t = self.leader.lock().unwrap();
-                        let api_addr = leader_node.api_addr.clone();
-                        *t = (*leader_id, api_addr);
-                    }
-
-                    n_retry -= 1;
-                    if n_retry > 0 {
-                        continue;
-                    }
-                }
-            }
-
-            return Err(rpc_err);
-        }
-    }
-}
+use std::collections::BTreeSet;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::RemoteError;
+use openraft::error::Unreachable;
+use openraft::RaftMetrics;
+use openraft::TryAsRef;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::raft_cluster::membership::MembershipError;
+use crate::raft_cluster::membership::MembershipReport;
+use crate::raft_cluster::tls;
+use crate::raft_cluster::typ;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::NodeRole;
+use crate::raft_cluster::Request;
+use crate::raft_cluster::TypeConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Empty {}
+
+pub struct atinyvectorsClient {
+    /// The leader node to send request to.
+    ///
+    /// All traffic should be sent to the leader in a cluster.
+    pub leader: Arc<Mutex<(NodeId, String)>>,
+
+    /// Replica nodes that take the search and read traffic, filled by [`refresh_replicas`].
+    ///
+    /// [`refresh_replicas`]: atinyvectorsClient::refresh_replicas
+    pub replicas: Arc<Mutex<Vec<(NodeId, String)>>>,
+
+    next_replica: AtomicUsize,
+
+    pub inner: Client,
+}
+
+impl atinyvectorsClient {
+    /// Create a client with a leader node id and a node manager to get node address by node id.
+    pub fn new(leader_id: NodeId, leader_addr: String) -> Self {
+        Self {
+            leader: Arc::new(Mutex::new((leader_id, leader_addr))),
+            replicas: Arc::new(Mutex::new(Vec::new())),
+            next_replica: AtomicUsize::new(0),
+            inner: tls::http_client(),
+        }
+    }
+
+    // --- Application API
+    /// Send a request to the space endpoint.
+    pub async fn space(&self, req: &Value) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_leader("api/space", Some(req)).await
+    }
+
+    /// Send a request to the vector endpoint.
+    pub async fn vector(&self, req: &Value) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_leader("api/space/vector", Some(req)).await
+    }
+
+    /// Send a request to the vector endpoint with a specific space_id and version_id.
+    pub async fn vector_with_version(&self, space_id: &str, version_id: &str, req: &Value) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_leader(&format!("api/space/{}/version/{}/vector", space_id, version_id), Some(req)).await
+    }
+
+    /// Send a request to the search endpoint.
+    pub async fn search(&self, space_id: &str, req: &Value) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_reader(&format!("api/space/{}/search", space_id), Some(req)).await
+    }
+
+    /// Send a request to the search endpoint with a specific space_id and version_id.
+    pub async fn search_with_version(&self, space_id: &str, version_id: &str, req: &Value) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_reader(&format!("api/space/{}/version/{}/search", space_id, version_id), Some(req)).await
+    }
+
+    /// Get a specific space by space_id.
+    pub async fn get_space(&self, space_id: &str) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_reader(&format!("api/space/{}", space_id), None::<&()>).await
+    }
+
+    /// List all spaces.
+    pub async fn list_spaces(&self) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_reader("api/space/list", None::<&()>).await
+    }
+
+    /// List all versions of spaces.
+    pub async fn list_space_versions(&self) -> Result<Value, typ::RPCError> {
+        self.do_send_rpc_to_reader("api/space/version/list", None::<&()>).await
+    }
+
+    /// Submit a write request to the raft cluster.
+    ///
+    /// The request will be processed by raft protocol: it will be replicated to a quorum and then
+    /// will be applied to state machine.
+    ///
+    /// The result of applying the request will be returned.
+    pub async fn write(&self, req: &Request) -> Result<typ::ClientWriteResponse, typ::RPCError<typ::ClientWriteError>> {
+        self.send_rpc_to_leader("api/debug/write", Some(req)).await
+    }
+
+    /// Read value by key, in an inconsistent mode.
+    ///
+    /// This method may return stale value because it does not force to read on a legal leader.
+    pub async fn read(&self, req: &String) -> Result<String, typ::RPCError> {
+        self.do_send_rpc_to_leader("api/debug/read", Some(req)).await
+    }
+
+    /// Consistent Read value by key, in an inconsistent mode.
+    ///
+    /// This method MUST return consistent value or CheckIsLeaderError.
+    pub async fn consistent_read(&self, req: &String) -> Result<String, typ::RPCError<typ::CheckIsLeaderError>> {
+        self.do_send_rpc_to_leader("api/debug/consistent_read", Some(req)).await
+    }
+
+    // --- Cluster management API
+
+    /// Initialize a cluster of only the node that receives this request.
+    ///
+    /// This is the first step to initialize a cluster.
+    /// With a initialized cluster, new node can be added with [`write`].
+    /// Then setup replication with [`add_learner`].
+    /// Then make the new node a member with [`change_membership`].
+    pub async fn init(&self) -> Result<(), typ::RPCError<typ::InitializeError>> {
+        self.do_send_rpc_to_leader("cluster/init", Some(&Empty {})).await
+    }
+
+    /// Add a node as learner.
+    ///
+    /// The node to add has to exist, i.e., being added with `write(ExampleRequest::AddNode{})`
+    pub async fn add_learner(
+        &self,
+        req: (NodeId, String, String),
+    ) -> Result<typ::ClientWriteResponse, typ::RPCError<typ::ClientWriteError>> {
+        self.send_rpc_to_leader("cluster/add-learner", Some(&req)).await
+    }
+
+    /// Change membership to the specified set of nodes.
+    ///
+    /// All nodes in `req` have to be already added as learner with [`add_learner`],
+    /// or an error [`LearnerNotFound`] will be returned.
+    pub async fn change_membership(
+        &self,
+        req: &BTreeSet<NodeId>,
+    ) -> Result<typ::ClientWriteResponse, typ::RPCError<typ::ClientWriteError>> {
+        self.send_rpc_to_leader("cluster/change-membership", Some(req)).await
+    }
+
+    /// Make the learner `node_id` a voter.
+    pub async fn promote(&self, node_id: NodeId) -> Result<MembershipReport, RPCError<TypeConfig, MembershipError>> {
+        self.do_send_rpc_to_leader("cluster/promote", Some(&node_id)).await
+    }
+
+    /// Remove a voter or a learner from the cluster.
+    ///
+    /// Fails with `QuorumLost` if the remaining voters could not form a quorum.
+    pub async fn remove_node(&self, node_id: NodeId) -> Result<MembershipReport, RPCError<TypeConfig, MembershipError>> {
+        self.do_send_rpc_to_leader("cluster/remove-node", Some(&node_id)).await
+    }
+
+    /// Turn a voter into a learner.
+    pub async fn demote(&self, node_id: NodeId) -> Result<MembershipReport, RPCError<TypeConfig, MembershipError>> {
+        self.do_send_rpc_to_leader("cluster/demote", Some(&node_id)).await
+    }
+
+    /// Make the voter `node_id` the leader.
+    pub async fn transfer_leader(&self, node_id: NodeId) -> Result<MembershipReport, RPCError<TypeConfig, MembershipError>> {
+        self.do_send_rpc_to_leader("cluster/transfer-leader", Some(&node_id)).await
+    }
+
+    /// Get the metrics about the cluster.
+    ///
+    /// Metrics contains various information about the cluster, such as current leader,
+    /// membership config, replication status etc.
+    /// See [`RaftMetrics`].
+    pub async fn metrics(&self) -> Result<RaftMetrics<TypeConfig>, typ::RPCError> {
+        self.do_send_rpc_to_leader("cluster/metrics", None::<&()>).await
+    }
+
+    /// Reload the replica nodes from the cluster membership, so that reads are spread over them.
+    pub async fn refresh_replicas(&self) -> Result<Vec<(NodeId, String)>, typ::RPCError> {
+        let metrics = self.metrics().await?;
+        let replicas: Vec<(NodeId, String)> = metrics
+            .membership_config
+            .membership()
+            .nodes()
+            .filter(|(_, node)| node.role == NodeRole::Replica)
+            .map(|(id, node)| (*id, node.api_addr.clone()))
+            .collect();
+        *self.replicas.lock().unwrap() = replicas.clone();
+        Ok(replicas)
+    }
+
+    // --- Internal methods
+
+    /// Send a read to the next replica in turn, or to the leader when there is no replica or the
+    /// replica can not be reached.
+    async fn do_send_rpc_to_reader<Req, Resp, Err>(
+        &self,
+        uri: &str,
+        req: Option<&Req>,
+    ) -> Result<Resp, RPCError<TypeConfig, Err>>
+    where
+        Req: Serialize + 'static,
+        Resp: Serialize + DeserializeOwned,
+        Err: std::error::Error + Serialize + DeserializeOwned,
+    {
+        let replica = {
+            let replicas = self.replicas.lock().unwrap();
+            match replicas.len() {
+                0 => None,
+                n => Some(replicas[self.next_replica.fetch_add(1, Ordering::Relaxed) % n].clone()),
+            }
+        };
+
+        if let Some(target) = replica {
+            match self.do_send_rpc(target.clone(), uri, req).await {
+                Err(RPCError::Unreachable(_)) | Err(RPCError::Network(_)) => {
+                    tracing::debug!("Replica {} failed, reading from the leader", target.0);
+                }
+                res => return res,
+            }
+        }
+        self.do_send_rpc_to_leader(uri, req).await
+    }
+
+    /// Send RPC to the leader.
+    ///
+    /// It sends out a POST request if `req` is Some. Otherwise a GET request.
+    /// The remote endpoint must respond a reply in form of `Result<T, E>`.
+    /// An `Err` happened on remote will be wrapped in an [`RPCError::RemoteError`].
+    async fn do_send_rpc_to_leader<Req, Resp, Err>(
+        &self,
+        uri: &str,
+        req: Option<&Req>,
+    ) -> Result<Resp, RPCError<TypeConfig, Err>>
+    where
+        Req: Serialize + 'static,
+        Resp: Serialize + DeserializeOwned,
+        Err: std::error::Error + Serialize + DeserializeOwned,
+    {
+        let leader = self.leader.lock().unwrap().clone();
+        self.do_send_rpc(leader, uri, req).await
+    }
+
+    /// Send RPC to the node `target`, a node id and its API address.
+    async fn do_send_rpc<Req, Resp, Err>(
+        &self,
+        target: (NodeId, String),
+        uri: &str,
+        req: Option<&Req>,
+    ) -> Result<Resp, RPCError<TypeConfig, Err>>
+    where
+        Req: Serialize + 'static,
+        Resp: Serialize + DeserializeOwned,
+        Err: std::error::Error + Serialize + DeserializeOwned,
+    {
+        let (target_id, url) = (target.0, format!("{}/{}", tls::base_url(&target.1), uri));
+
+        let resp = if let Some(r) = req {
+            self.inner.post(url.clone()).json(r)
+        } else {
+            self.inner.get(url.clone())
+        }
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_connect() {
+                return RPCError::Unreachable(Unreachable::new(&e));
+            }
+            RPCError::Network(NetworkError::new(&e))
+        })?;
+
+        let res: Result<Resp, Err> = resp.json().await.map_err(|e| RPCError::Network(NetworkError::new(&e)))?;
+        res.map_err(|e| RPCError::RemoteError(RemoteError::new(target_id, e)))
+    }
+
+    /// Try the best to send a request to the leader.
+    ///
+    /// If the target node is not a leader, a `ForwardToLeader` error will be
+    /// returned and this client will retry at most 3 times to contact the updated leader.
+    async fn send_rpc_to_leader<Req, Resp, Err>(&self, uri: &str, req: Option<&Req>) -> Result<Resp, typ::RPCError<Err>>
+    where
+        Req: Serialize + 'static,
+        Resp: Serialize + DeserializeOwned,
+        Err: std::error::Error + Serialize + DeserializeOwned + TryAsRef<typ::ForwardToLeader> + Clone,
+    {
+        let mut n_retry = 3;
+
+        loop {
+            let res: Result<Resp, typ::RPCError<Err>> = self.do_send_rpc_to_leader(uri, req).await;
+
+            let rpc_err = match res {
+                Ok(x) => return Ok(x),
+                Err(rpc_err) => rpc_err,
+            };
+
+            if let RPCError::RemoteError(remote_err) = &rpc_err {
+                let raft_err: &typ::RaftError<_> = &remote_err.source;
+
+                if let Some(typ::ForwardToLeader {
+                    leader_id: Some(leader_id),
+                    leader_node: Some(leader_node),
+                    ..
+                }) = raft_err.forward_to_leader()
+                {
+                    {
+                        let mut t = self.leader.lock().unwrap();
+                        let api_addr = leader_node.api_addr.clone();
+                        *t = (*leader_id, api_addr);
+                    }
+
+                    n_retry -= 1;
+                    if n_retry > 0 {
+                        continue;
+                    }
+                }
+            }
+
+            return Err(rpc_err);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Authentication between the nodes of a cluster.
+//!
+//! With `cluster_secret` set, every Raft RPC carries the secret and is rejected without it, and
+//! nodes present it in [`SECRET_HEADER`] when they call each other's `/cluster/*` endpoints.
+//! The secret travels in clear text unless TLS is configured, see [`tls`](super::tls), so
+//! without it the RPC and HTTP ports should only be reachable from a trusted network.
+//!
+//! Nodes of releases before [`Authenticated`] cannot read wrapped RPCs, whether a secret is set
+//! or not, so such clusters are upgraded all at once. Fields added to the wrapper later, like
+//! `shard`, default when missing and do not need that. Append requests with binary encoded
+//! entries, see `AppendPayload`, are only read by nodes of the same release or later.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+pub const SECRET_HEADER: &str = "X-ATV-Cluster-Secret";
+
+/// A Raft RPC request together with the secret of the sending node and the shard group it is
+/// meant for, `0` without sharding.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Authenticated<T> {
+    pub secret: String,
+    /// Missing in RPCs of nodes that predate sharding.
+    #[serde(default)]
+    pub shard: usize,
+    pub req: T,
+}
+
+impl<T> Authenticated<T> {
+    pub fn new(shard: usize, req: T) -> Self {
+        Self {
+            secret: crate::Config::cluster_secret(),
+            shard,
+            req,
+        }
+    }
+}
+
+/// Whether an RPC carrying `given` is accepted. Without a configured secret every RPC is.
+pub fn rpc_allowed(given: &str) -> bool {
+    let secret = crate::Config::cluster_secret();
+    secret.is_empty() || constant_time_eq(secret.as_bytes(), given.as_bytes())
+}
+
+/// Whether `given` is the cluster secret. Always false when no secret is configured.
+pub fn secret_matches(given: &str) -> bool {
+    let secret = crate::Config::cluster_secret();
+    !secret.is_empty() && constant_time_eq(secret.as_bytes(), given.as_bytes())
+}
+
+/// Adds the cluster secret to a request made to another node.
+pub fn sign(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    let secret = crate::Config::cluster_secret();
+    if secret.is_empty() {
+        builder
+    } else {
+        builder.header(SECRET_HEADER, secret)
+    }
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use openraft::LogId;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use crate::raft_cluster::wait_for_metrics;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::RaftCluster;
+
+/// How long an on-demand compaction may wait for the snapshot or the purge to show up in metrics.
+const COMPACTION_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionState {
+    #[default]
+    Idle,
+    BuildingSnapshot,
+    Purging,
+    Done,
+    Failed,
+}
+
+/// Progress of the last compaction requested through `/cluster/snapshot`, reported in
+/// `/cluster/metrics`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CompactionStatus {
+    pub state: CompactionState,
+    /// Applied log index the snapshot has to cover.
+    pub target_index: Option<u64>,
+    /// Logs up to this index are purged once the snapshot is built, `None` keeps them all.
+    pub purge_upto: Option<u64>,
+    pub snapshot: Option<LogId<NodeId>>,
+    pub purged: Option<LogId<NodeId>>,
+    pub error: Option<String>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+impl CompactionStatus {
+    pub fn started(target_index: Option<u64>) -> Self {
+        Self {
+            state: CompactionState::BuildingSnapshot,
+            target_index,
+            started_at: Some(chrono::Utc::now().to_rfc3339()),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, CompactionState::BuildingSnapshot | CompactionState::Purging)
+    }
+}
+
+/// Builds a snapshot covering everything applied up to `target_index`, then purges the logs it
+/// made obsolete except the last `keep` ones, updating `status` along the way.
+///
+/// `status` must already have been reset with [`CompactionStatus::started`].
+pub async fn compact(
+    raft: RaftCluster,
+    runtime: tokio::runtime::Handle,
+    status: Arc<RwLock<CompactionStatus>>,
+    target_index: Option<u64>,
+    purge: bool,
+    keep: u64,
+) {
+    if let Err(e) = run(&raft, &runtime, &status, target_index, purge, keep).await {
+        tracing::error!("Compaction failed: {}", e);
+        let mut s = status.write().await;
+        s.state = CompactionState::Failed;
+        s.error = Some(e);
+    } else {
+        status.write().await.state = CompactionState::Done;
+    }
+    status.write().await.finished_at = Some(chrono::Utc::now().to_rfc3339());
+}
+
+async fn run(
+    raft: &RaftCluster,
+    runtime: &tokio::runtime::Handle,
+    status: &RwLock<CompactionStatus>,
+    target_index: Option<u64>,
+    purge: bool,
+    keep: u64,
+) -> Result<(), String> {
+    let target_index = target_index.ok_or_else(|| "Nothing has been applied yet".to_string())?;
+
+    tracing::info!("Compaction: building snapshot up to index {}", target_index);
+    raft.trigger().snapshot().await.map_err(|e| e.to_string())?;
+    let snapshot = wait_for_metrics(raft, runtime, COMPACTION_TIMEOUT, move |m| m.snapshot.filter(|s| s.index >= target_index)).await
+        .ok_or_else(|| format!("Snapshot up to index {} was not built in time", target_index))?;
+    status.write().await.snapshot = Some(snapshot);
+
+    let purge_upto = snapshot.index.saturating_sub(keep);
+    if !purge || purge_upto == 0 {
+        return Ok(());
+    }
+
+    tracing::info!("Compaction: purging logs up to index {}", purge_upto);
+    {
+        let mut s = status.write().await;
+        s.state = CompactionState::Purging;
+        s.purge_upto = Some(purge_upto);
+    }
+    raft.trigger().purge_log(purge_upto).await.map_err(|e| e.to_string())?;
+    // the leader defers purging until no replication stream needs the logs anymore
+    let purged = wait_for_metrics(raft, runtime, COMPACTION_TIMEOUT, move |m| m.purged.filter(|p| p.index >= purge_upto)).await
+        .ok_or_else(|| format!("Logs up to index {} were not purged in time", purge_upto))?;
+    status.write().await.purged = Some(purged);
+
+    Ok(())
+}
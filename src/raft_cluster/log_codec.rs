@@ -0,0 +1,223 @@
+//! Encoding of Raft log entries in the `logs` column family.
+//!
+//! Older versions stored every entry as JSON. Vector upserts then carry each float as text, so
+//! the binary format moves the vectors out of the JSON document:
+//!
+//! ```text
+//! "ATVL" | format version (u8) | JSON length (u32) | entry as JSON, vector data set to null
+//!        | vector count (u32) | per vector: index in `vectors` (u32), dimension (u32), f32 values
+//! ```
+//!
+//! Integers are big endian, vector values little endian. Entries are decoded by their first
+//! bytes, so both formats can be present in the same log.
+//!
+//! Vector data is stored as f32, the precision of the engine. New commands are rounded to f32
+//! before they are proposed, see `Request::command`, so their entries decode to what was
+//! committed. Entries proposed by older versions may hold values f32 cannot represent exactly;
+//! they come back rounded, which is what the engine stored for them anyway. Data holding
+//! anything other than numbers stays in the JSON document.
+
+use std::io::Cursor;
+use std::io::Read;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use byteorder::BigEndian;
+use byteorder::LittleEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use openraft::raft::AppendEntriesRequest;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::LogId;
+use openraft::Vote;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+use crate::config::Config;
+use crate::raft_cluster::store::Request;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::TypeConfig;
+
+pub const MAGIC: &[u8; 4] = b"ATVL";
+
+/// Version of the binary entry format written by [`encode_entry`].
+pub const LOG_FORMAT_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Json,
+    Binary,
+}
+
+impl LogFormat {
+    pub fn from_name(name: &str) -> Option<LogFormat> {
+        match name {
+            "json" => Some(LogFormat::Json),
+            "binary" => Some(LogFormat::Binary),
+            _ => None,
+        }
+    }
+
+    /// Format set with `raft_log_format`, binary when unknown. It applies to the log and to the
+    /// append RPCs alike.
+    pub fn from_config() -> LogFormat {
+        LogFormat::from_name(&Config::raft_log_format()).unwrap_or_else(|| {
+            tracing::warn!("Unknown raft log format {}, using binary", Config::raft_log_format());
+            LogFormat::Binary
+        })
+    }
+}
+
+pub fn encode_entry(entry: &Entry<TypeConfig>, format: LogFormat) -> Result<Vec<u8>, String> {
+    if format == LogFormat::Json {
+        return serde_json::to_vec(entry).map_err(|e| e.to_string());
+    }
+
+    let mut entry = entry.clone();
+    let mut vectors: Vec<(u32, Vec<f32>)> = Vec::new();
+    if let Some(items) = upsert_vectors_mut(&mut entry) {
+        for (i, item) in items.iter_mut().enumerate() {
+            if let Some(data) = item.get_mut("data") {
+                if let Some(values) = as_f32_vec(data) {
+                    vectors.push((i as u32, values));
+                    *data = Value::Null;
+                }
+            }
+        }
+    }
+
+    let json = serde_json::to_vec(&entry).map_err(|e| e.to_string())?;
+    let dims: usize = vectors.iter().map(|(_, v)| v.len()).sum();
+    let mut buf = Vec::with_capacity(MAGIC.len() + 1 + 4 + json.len() + 4 + vectors.len() * 8 + dims * 4);
+
+    buf.extend_from_slice(MAGIC);
+    buf.push(LOG_FORMAT_VERSION);
+    buf.write_u32::<BigEndian>(json.len() as u32).map_err(|e| e.to_string())?;
+    buf.extend_from_slice(&json);
+    buf.write_u32::<BigEndian>(vectors.len() as u32).map_err(|e| e.to_string())?;
+    for (index, values) in vectors {
+        buf.write_u32::<BigEndian>(index).map_err(|e| e.to_string())?;
+        buf.write_u32::<BigEndian>(values.len() as u32).map_err(|e| e.to_string())?;
+        for v in values {
+            buf.write_f32::<LittleEndian>(v).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(buf)
+}
+
+pub fn decode_entry(buf: &[u8]) -> Result<Entry<TypeConfig>, String> {
+    if !buf.starts_with(MAGIC) {
+        return serde_json::from_slice(buf).map_err(|e| e.to_string());
+    }
+
+    let mut r = Cursor::new(&buf[MAGIC.len()..]);
+    let version = r.read_u8().map_err(|e| e.to_string())?;
+    if version > LOG_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported log entry format version {} (max supported: {})",
+            version, LOG_FORMAT_VERSION
+        ));
+    }
+
+    let json_len = r.read_u32::<BigEndian>().map_err(|e| e.to_string())? as usize;
+    let mut json = vec![0u8; json_len];
+    r.read_exact(&mut json).map_err(|e| e.to_string())?;
+    let mut entry: Entry<TypeConfig> = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+
+    let count = r.read_u32::<BigEndian>().map_err(|e| e.to_string())?;
+    let mut items = upsert_vectors_mut(&mut entry);
+    for _ in 0..count {
+        let index = r.read_u32::<BigEndian>().map_err(|e| e.to_string())? as usize;
+        let dim = r.read_u32::<BigEndian>().map_err(|e| e.to_string())? as usize;
+        let mut values = Vec::with_capacity(dim);
+        for _ in 0..dim {
+            values.push(Value::from(r.read_f32::<LittleEndian>().map_err(|e| e.to_string())?));
+        }
+
+        let item = items
+            .as_mut()
+            .and_then(|items| items.get_mut(index))
+            .and_then(|item| item.as_object_mut())
+            .ok_or_else(|| format!("Log entry has vector data for missing vectors[{}]", index))?;
+        item.insert("data".to_string(), Value::Array(values));
+    }
+
+    Ok(entry)
+}
+
+/// Append request as sent between nodes. The RPCs are JSON encoded, so with the binary format
+/// the entries travel as base64 of [`encode_entry`] rather than with every float as text.
+/// Either form is accepted; a node of an older version only reads [`AppendPayload::Plain`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum AppendPayload {
+    Encoded {
+        vote: Vote<NodeId>,
+        prev_log_id: Option<LogId<NodeId>>,
+        leader_commit: Option<LogId<NodeId>>,
+        encoded_entries: Vec<String>,
+    },
+    Plain(AppendEntriesRequest<TypeConfig>),
+}
+
+impl AppendPayload {
+    pub fn new(req: AppendEntriesRequest<TypeConfig>, format: LogFormat) -> Result<Self, String> {
+        if format == LogFormat::Json {
+            return Ok(AppendPayload::Plain(req));
+        }
+
+        let encoded_entries = req
+            .entries
+            .iter()
+            .map(|entry| encode_entry(entry, LogFormat::Binary).map(|buf| STANDARD.encode(buf)))
+            .collect::<Result<_, _>>()?;
+        Ok(AppendPayload::Encoded {
+            vote: req.vote,
+            prev_log_id: req.prev_log_id,
+            leader_commit: req.leader_commit,
+            encoded_entries,
+        })
+    }
+
+    pub fn into_request(self) -> Result<AppendEntriesRequest<TypeConfig>, String> {
+        match self {
+            AppendPayload::Plain(req) => Ok(req),
+            AppendPayload::Encoded {
+                vote,
+                prev_log_id,
+                leader_commit,
+                encoded_entries,
+            } => {
+                let entries = encoded_entries
+                    .iter()
+                    .map(|text| decode_entry(&STANDARD.decode(text).map_err(|e| e.to_string())?))
+                    .collect::<Result<_, _>>()?;
+                Ok(AppendEntriesRequest {
+                    vote,
+                    prev_log_id,
+                    entries,
+                    leader_commit,
+                })
+            }
+        }
+    }
+}
+
+fn upsert_vectors_mut(entry: &mut Entry<TypeConfig>) -> Option<&mut Vec<Value>> {
+    match &mut entry.payload {
+        EntryPayload::Normal(Request::Command {
+            command: Command::UpsertVectors { value, .. },
+            ..
+        }) => value.get_mut("vectors").and_then(|v| v.as_array_mut()),
+        _ => None,
+    }
+}
+
+/// Arrays of numbers are moved out as f32, integers included; anything else stays in the JSON
+/// document. Values beyond the f32 range are refused when the command is validated.
+fn as_f32_vec(data: &Value) -> Option<Vec<f32>> {
+    data.as_array()?.iter().map(|v| v.as_f64().map(|f| f as f32)).collect()
+}
@@ -0,0 +1,338 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::Duration;
+
+use openraft::ChangeMembers;
+use openraft::LogId;
+use openraft::Membership;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::tls;
+use crate::raft_cluster::typ;
+use crate::raft_cluster::wait_for_metrics;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::NodeRole;
+use crate::raft_cluster::TypeConfig;
+
+/// How long a peer may take to answer the health probe made before a membership change.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long a leadership transfer may take before it is reported as failed.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Membership after a change, as returned by the `/cluster/*` membership endpoints.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MembershipReport {
+    /// Log id of the membership config that the report describes.
+    pub log_id: Option<LogId<NodeId>>,
+    pub leader: Option<NodeId>,
+    pub voters: BTreeSet<NodeId>,
+    pub learners: BTreeSet<NodeId>,
+}
+
+impl MembershipReport {
+    fn new(log_id: Option<LogId<NodeId>>, leader: Option<NodeId>, membership: &Membership<TypeConfig>) -> Self {
+        Self {
+            log_id,
+            leader,
+            voters: membership.voter_ids().collect(),
+            learners: membership.learner_ids().collect(),
+        }
+    }
+
+    pub fn current(app: &App) -> Self {
+        let metrics = app.raft.metrics().borrow().clone();
+        Self::new(
+            *metrics.membership_config.log_id(),
+            metrics.current_leader,
+            metrics.membership_config.membership(),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum MembershipError {
+    /// Membership changes are made by the leader.
+    NotLeader {
+        leader_id: Option<NodeId>,
+        leader_api_addr: Option<String>,
+    },
+    UnknownNode { node_id: NodeId },
+    NotVoter { node_id: NodeId },
+    /// Replica nodes never vote.
+    Replica { node_id: NodeId },
+    /// The change would leave the cluster without voters.
+    LastVoter { node_id: NodeId },
+    /// Too few of the remaining voters answered the health probe to form a quorum afterwards.
+    QuorumLost {
+        voters: BTreeSet<NodeId>,
+        reachable: BTreeSet<NodeId>,
+        required: usize,
+    },
+    Timeout { message: String },
+    Raft { message: String },
+}
+
+impl MembershipError {
+    pub fn status(&self) -> u16 {
+        match self {
+            MembershipError::NotLeader { .. } => 421,
+            MembershipError::UnknownNode { .. } => 404,
+            MembershipError::NotVoter { .. } => 400,
+            MembershipError::Replica { .. } | MembershipError::LastVoter { .. } | MembershipError::QuorumLost { .. } => {
+                409
+            }
+            MembershipError::Timeout { .. } => 504,
+            MembershipError::Raft { .. } => 500,
+        }
+    }
+}
+
+impl fmt::Display for MembershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MembershipError::NotLeader { leader_id, .. } => write!(f, "Not the leader, current leader: {:?}", leader_id),
+            MembershipError::UnknownNode { node_id } => write!(f, "Node {} is not part of the cluster", node_id),
+            MembershipError::NotVoter { node_id } => write!(f, "Node {} is not a voter", node_id),
+            MembershipError::Replica { node_id } => write!(f, "Node {} is a replica and can not vote", node_id),
+            MembershipError::LastVoter { node_id } => write!(f, "Node {} is the last voter", node_id),
+            MembershipError::QuorumLost { voters, reachable, required } => write!(
+                f,
+                "Only {} of the remaining voters {:?} are reachable, {} needed for a quorum",
+                reachable.len(),
+                voters,
+                required
+            ),
+            MembershipError::Timeout { message } | MembershipError::Raft { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for MembershipError {}
+
+/// Removes `node_id` from the cluster, whether it is a voter or a learner.
+pub async fn remove_node(app: &App, node_id: NodeId, force: bool) -> Result<MembershipReport, MembershipError> {
+    let membership = leader_membership(app)?;
+    if membership.get_node(&node_id).is_none() {
+        return Err(MembershipError::UnknownNode { node_id });
+    }
+
+    let ids = BTreeSet::from([node_id]);
+    let changes = if membership.voter_ids().any(|id| id == node_id) {
+        check_quorum(app, &membership, node_id, force).await?;
+        // without `retain` the removed voters are dropped from the nodes as well
+        ChangeMembers::RemoveVoters(ids)
+    } else {
+        ChangeMembers::RemoveNodes(ids)
+    };
+
+    tracing::info!("Removing node {} from the cluster", node_id);
+    change(app, changes, false).await
+}
+
+/// Turns the learner `node_id` into a voter. Nodes that joined through the seeds call this once
+/// they have caught up with the log.
+pub async fn promote(app: &App, node_id: NodeId) -> Result<MembershipReport, MembershipError> {
+    let membership = leader_membership(app)?;
+    match membership.get_node(&node_id) {
+        None => return Err(MembershipError::UnknownNode { node_id }),
+        Some(node) if node.role == NodeRole::Replica => return Err(MembershipError::Replica { node_id }),
+        Some(_) => {}
+    }
+    if membership.voter_ids().any(|id| id == node_id) {
+        return Ok(MembershipReport::current(app));
+    }
+
+    tracing::info!("Promoting node {} to voter", node_id);
+    change(app, ChangeMembers::AddVoterIds(BTreeSet::from([node_id])), true).await
+}
+
+/// Turns the voter `node_id` into a learner that keeps replicating the log.
+pub async fn demote(app: &App, node_id: NodeId, force: bool) -> Result<MembershipReport, MembershipError> {
+    let membership = leader_membership(app)?;
+    if membership.get_node(&node_id).is_none() {
+        return Err(MembershipError::UnknownNode { node_id });
+    }
+    if !membership.voter_ids().any(|id| id == node_id) {
+        return Err(MembershipError::NotVoter { node_id });
+    }
+
+    check_quorum(app, &membership, node_id, force).await?;
+
+    tracing::info!("Demoting node {} to learner", node_id);
+    change(app, ChangeMembers::RemoveVoters(BTreeSet::from([node_id])), true).await
+}
+
+/// Hands leadership to the voter `node_id` and waits until it has taken over.
+pub async fn transfer_leader(app: &App, node_id: NodeId) -> Result<MembershipReport, MembershipError> {
+    let membership = leader_membership(app)?;
+    if membership.get_node(&node_id).is_none() {
+        return Err(MembershipError::UnknownNode { node_id });
+    }
+    if !membership.voter_ids().any(|id| id == node_id) {
+        return Err(MembershipError::NotVoter { node_id });
+    }
+    if node_id == app.id {
+        return Ok(MembershipReport::current(app));
+    }
+
+    tracing::info!("Transferring leadership to node {}", node_id);
+    app.raft.trigger().transfer_leader(node_id).await.map_err(|e| MembershipError::Raft { message: e.to_string() })?;
+
+    let took_over = wait_for_metrics(&app.raft, &app.runtime, TRANSFER_TIMEOUT, move |m| {
+        (m.current_leader == Some(node_id)).then_some(())
+    })
+    .await;
+    match took_over {
+        Some(()) => Ok(MembershipReport::current(app)),
+        None => Err(MembershipError::Timeout {
+            message: format!("Node {} did not take over leadership in time", node_id),
+        }),
+    }
+}
+
+/// Takes this node out of the cluster. A leader first hands leadership to the most up to date
+/// voter, then the leader removes this node.
+pub async fn leave(app: &App, force: bool) -> Result<MembershipReport, MembershipError> {
+    let metrics = app.raft.metrics().borrow().clone();
+    let membership = metrics.membership_config.membership().clone();
+    if membership.get_node(&app.id).is_none() {
+        return Err(MembershipError::UnknownNode { node_id: app.id });
+    }
+
+    if metrics.current_leader == Some(app.id) {
+        if !membership.voter_ids().any(|id| id != app.id) {
+            return Err(MembershipError::LastVoter { node_id: app.id });
+        }
+
+        let matched = |id: &NodeId| {
+            metrics.replication.as_ref().and_then(|r| r.get(id).cloned().flatten()).map(|log_id| log_id.index)
+        };
+        let successor = membership.voter_ids().filter(|id| *id != app.id).max_by_key(|id| matched(id)).unwrap();
+
+        check_quorum(app, &membership, app.id, force).await?;
+        transfer_leader(app, successor).await?;
+    }
+
+    let (leader_id, leader_api_addr) = leader(app);
+    let leader_api_addr = leader_api_addr.ok_or(MembershipError::NotLeader { leader_id, leader_api_addr: None })?;
+
+    let url = format!(
+        "{}/cluster/remove-node{}",
+        tls::base_url(&leader_api_addr),
+        if force { "?force=true" } else { "" }
+    );
+    let request = app.sign(tls::http_client().post(url)).json(&app.id);
+    let result = app
+        .runtime
+        .spawn(async move {
+            let response = request.send().await?;
+            response.json::<Result<MembershipReport, MembershipError>>().await
+        })
+        .await;
+
+    match result {
+        Ok(Ok(res)) => res,
+        Ok(Err(e)) => Err(MembershipError::Raft { message: e.to_string() }),
+        Err(e) => Err(MembershipError::Raft { message: e.to_string() }),
+    }
+}
+
+fn leader(app: &App) -> (Option<NodeId>, Option<String>) {
+    let metrics = app.raft.metrics().borrow().clone();
+    let leader_id = metrics.current_leader;
+    let api_addr = leader_id
+        .and_then(|id| metrics.membership_config.membership().get_node(&id).map(|node| node.api_addr.clone()));
+    (leader_id, api_addr)
+}
+
+/// The current membership, if this node is the leader that can change it.
+fn leader_membership(app: &App) -> Result<Membership<TypeConfig>, MembershipError> {
+    let metrics = app.raft.metrics().borrow().clone();
+    if metrics.current_leader != Some(app.id) {
+        let (leader_id, leader_api_addr) = leader(app);
+        return Err(MembershipError::NotLeader { leader_id, leader_api_addr });
+    }
+    Ok(metrics.membership_config.membership().clone())
+}
+
+/// Makes sure a majority of the voters left after taking `leaving` out are reachable, so the
+/// cluster can still commit once the change is applied.
+async fn check_quorum(
+    app: &App,
+    membership: &Membership<TypeConfig>,
+    leaving: NodeId,
+    force: bool,
+) -> Result<(), MembershipError> {
+    let voters: BTreeSet<NodeId> = membership.voter_ids().filter(|id| *id != leaving).collect();
+    if voters.is_empty() {
+        return Err(MembershipError::LastVoter { node_id: leaving });
+    }
+    if force {
+        return Ok(());
+    }
+
+    let mut reachable = BTreeSet::new();
+    for id in &voters {
+        if *id == app.id {
+            reachable.insert(*id);
+            continue;
+        }
+        if let Some(node) = membership.get_node(id) {
+            if probe(app, &node.api_addr).await {
+                reachable.insert(*id);
+            }
+        }
+    }
+
+    let required = voters.len() / 2 + 1;
+    if reachable.len() < required {
+        return Err(MembershipError::QuorumLost { voters, reachable, required });
+    }
+    Ok(())
+}
+
+/// Asks the peer for its metrics; a successful answer within [`PROBE_TIMEOUT`] counts as reachable.
+async fn probe(app: &App, api_addr: &str) -> bool {
+    let request = app.sign(tls::http_client().get(format!("{}/cluster/metrics", tls::base_url(api_addr))));
+    let result = app
+        .runtime
+        .spawn(async move {
+            request
+                .timeout(PROBE_TIMEOUT)
+                .send()
+                .await
+                .map(|r| r.status().is_success())
+        })
+        .await;
+    matches!(result, Ok(Ok(true)))
+}
+
+async fn change(
+    app: &App,
+    changes: ChangeMembers<TypeConfig>,
+    retain: bool,
+) -> Result<MembershipReport, MembershipError> {
+    let res: Result<typ::ClientWriteResponse, typ::RaftError<typ::ClientWriteError>> =
+        app.raft.change_membership(changes, retain).await;
+
+    match res {
+        Ok(res) => {
+            let leader = app.raft.metrics().borrow().current_leader;
+            Ok(match res.membership {
+                Some(membership) => MembershipReport::new(Some(res.log_id), leader, &membership),
+                None => MembershipReport::current(app),
+            })
+        }
+        Err(e) => match e.forward_to_leader() {
+            Some(forward) => Err(MembershipError::NotLeader {
+                leader_id: forward.leader_id,
+                leader_api_addr: forward.leader_node.as_ref().map(|node| node.api_addr.clone()),
+            }),
+            None => Err(MembershipError::Raft { message: e.to_string() }),
+        },
+    }
+}
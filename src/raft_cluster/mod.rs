@@ -1,170 +1,345 @@
-#![allow(clippy::uninlined_format_args)]
-#![deny(unused_qualifications)]
-
-use std::fmt::Display;
-use std::io::Cursor;
-use std::path::Path;
-use std::sync::Arc;
-use std::collections::BTreeMap;
-
-use openraft::Config;
-use tokio::net::TcpListener;
-use tokio::task;
-
-use app::App;
-use network::management;
-use network::Network;
-use store::new_storage;
-use store::Request;
-use store::Response;
-use async_std::process::exit;
-
-pub mod app;
-pub mod client;
-pub mod network;
-pub mod store;
-
-pub type NodeId = u64;
-
-use crate::atinyvectors::atinyvectors_raft_command::ATinyVectorsRaftCommand;
-use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
-use crate::service::routes;
-
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
-pub struct Node {
-    pub rpc_addr: String,
-    pub api_addr: String,
-}
-
-impl Display for Node {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Node {{ rpc_addr: {}, api_addr: {} }}", self.rpc_addr, self.api_addr)
-    }
-}
-
-pub type SnapshotData = Cursor<Vec<u8>>;
-
-openraft::declare_raft_types!(
-    pub TypeConfig:
-        D = Request,
-        R = Response,
-        Node = Node,
-);
-
-pub mod typ {
-    use openraft::error::Infallible;
-
-    use crate::raft_cluster::TypeConfig;
-
-    pub type Entry = openraft::Entry<TypeConfig>;
-
-    pub type RaftError<E = Infallible> = openraft::error::RaftError<TypeConfig, E>;
-    pub type RPCError<E = Infallible> = openraft::error::RPCError<TypeConfig, RaftError<E>>;
-
-    pub type ClientWriteError = openraft::error::ClientWriteError<TypeConfig>;
-    pub type CheckIsLeaderError = openraft::error::CheckIsLeaderError<TypeConfig>;
-    pub type ForwardToLeader = openraft::error::ForwardToLeader<TypeConfig>;
-    pub type InitializeError = openraft::error::InitializeError<TypeConfig>;
-
-    pub type ClientWriteResponse = openraft::raft::ClientWriteResponse<TypeConfig>;
-}
-
-pub type RaftCluster = openraft::Raft<TypeConfig>;
-
-pub type Server = tide::Server<Arc<App>>;
-
-pub async fn start_raft_node<P>(
-    node_id: NodeId,
-    dir: P,
-    http_addr: String,
-    rpc_addr: String,
-) -> std::io::Result<()>
-where
-    P: AsRef<Path>,
-{
-    if crate::Config::raft_heartbeat_interval() > crate::Config::raft_election_timeout() {
-        tracing::error!("Heatbeat interval shoud be lower than Election Timeout: election_timeout={}, heartbeat_interval={}",
-            crate::Config::raft_election_timeout(), crate::Config::raft_heartbeat_interval());
-        exit(-1);
-    }
-
-    if crate::Config::raft_heartbeat_interval() >= 300 {
-        tracing::error!("Heatbeat interval is shoud be under 300: heartbeat_interval={}",
-            crate::Config::raft_heartbeat_interval());
-        exit(-1);
-    }
-
-    tracing::info!("App Server listening on: {}", http_addr);
-    
-    if crate::Config::enable_swagger_ui() {
-        tracing::info!("Swagger running on: {}/swagger-ui/", http_addr);
-    }
-
-    // Create a configuration for the raft instance.
-    let config = Config {
-        heartbeat_interval: crate::Config::raft_heartbeat_interval(),
-        election_timeout_min: crate::Config::raft_election_timeout(),
-        ..Default::default()
-    };
-
-    let config = Arc::new(config.validate().unwrap());
-
-    // init atinyvectors module
-    let atinyvectors_bo = Arc::new(ATinyVectorsBO::new());
-    let atinyvectors_command = Arc::new(ATinyVectorsRaftCommand::new(atinyvectors_bo.clone()));
-
-    let (log_store, state_machine_store) = new_storage(&dir, atinyvectors_command.clone()).await;
-
-    let kvs = state_machine_store.data.kvs.clone();
-
-    // Create the network layer that will connect and communicate the raft instances and
-    // will be used in conjunction with the store created above.
-    let network = Network {};
-
-    // Create a local raft instance.
-    let raft = openraft::Raft::new(node_id, config.clone(), network, log_store, state_machine_store).await.unwrap();
-    if crate::Config::standalone() {
-        // standalone
-        tracing::info!("This is standalone mode");
-        let mut nodes = BTreeMap::new();
-        let node = Node {
-            api_addr: http_addr.clone(),
-            rpc_addr: rpc_addr.clone(),
-        };
-
-        nodes.insert(node_id, node);
-        raft.initialize(nodes).await;
-    } else {
-        tracing::info!("This is cluster mode. you should call /cluster/init before api call");
-    }
-
-    let app = Arc::new(App {
-        id: node_id,
-        api_addr: http_addr.clone(),
-        rpc_addr: rpc_addr.clone(),
-        raft,
-        key_values: kvs,
-        config,
-        atinyvectors_bo,
-        atinyvectors_command
-    });
-
-    let echo_service = Arc::new(network::raft::Raft::new(app.clone()));
-
-    let server = toy_rpc::Server::builder().register(echo_service).build();
-
-    let listener = TcpListener::bind(rpc_addr).await.unwrap();
-    let handle = task::spawn(async move {
-        server.accept_websocket(listener).await.unwrap();
-    });
-
-    let mut app: Server = tide::Server::with_state(app);
-
-    management::rest(&mut app);
-    routes::register_routes(&mut app);
-
-    app.listen(http_addr.clone()).await?;
-
-    _ = handle.await;
-    Ok(())
+#![allow(clippy::uninlined_format_args)]
+#![deny(unused_qualifications)]
+
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use openraft::Config;
+use openraft::RaftMetrics;
+use openraft::SnapshotPolicy;
+use tokio::net::TcpListener;
+use tokio::task;
+
+use app::App;
+use network::management;
+use network::peer_stats::PeerStatsRegistry;
+use network::Network;
+use store::new_storage;
+use store::Request;
+use store::Response;
+use async_std::process::exit;
+
+pub mod app;
+pub mod bootstrap;
+pub mod client;
+pub mod cluster_auth;
+pub mod compaction;
+pub mod log_codec;
+pub mod membership;
+pub mod network;
+pub mod node_identity;
+pub mod shard_journal;
+pub mod snapshot_archive;
+pub mod store;
+pub mod tls;
+
+pub type NodeId = u64;
+
+use crate::atinyvectors::atinyvectors_raft_command::ATinyVectorsRaftCommand;
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+use crate::service::auth::Authenticate;
+use crate::service::routes;
+use crate::service::sharding;
+use crate::service::sharding::ShardDispatch;
+
+/// What a node is for, set with `Config::node_role()` and kept in its membership entry.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRole {
+    /// Joins as a learner and is promoted to voter.
+    #[default]
+    Voter,
+    /// Stays a learner for good: replicates the log and serves reads, never votes, and redirects
+    /// writes to the leader.
+    Replica,
+}
+
+impl NodeRole {
+    pub fn from_config() -> NodeRole {
+        match crate::Config::node_role().as_str() {
+            "replica" => NodeRole::Replica,
+            "voter" => NodeRole::Voter,
+            other => {
+                tracing::warn!("Unknown node_role '{}', using 'voter'", other);
+                NodeRole::Voter
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct Node {
+    pub rpc_addr: String,
+    pub api_addr: String,
+    /// Missing in membership entries written before roles existed.
+    #[serde(default)]
+    pub role: NodeRole,
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Node {{ rpc_addr: {}, api_addr: {}, role: {:?} }}", self.rpc_addr, self.api_addr, self.role)
+    }
+}
+
+/// Snapshots are kept in files and streamed in chunks, so they never have to fit in memory.
+pub type SnapshotData = tokio::fs::File;
+
+openraft::declare_raft_types!(
+    pub TypeConfig:
+        D = Request,
+        R = Response,
+        Node = Node,
+        SnapshotData = SnapshotData,
+);
+
+pub mod typ {
+    use openraft::error::Infallible;
+
+    use crate::raft_cluster::TypeConfig;
+
+    pub type Entry = openraft::Entry<TypeConfig>;
+
+    pub type RaftError<E = Infallible> = openraft::error::RaftError<TypeConfig, E>;
+    pub type RPCError<E = Infallible> = openraft::error::RPCError<TypeConfig, RaftError<E>>;
+
+    pub type ClientWriteError = openraft::error::ClientWriteError<TypeConfig>;
+    pub type CheckIsLeaderError = openraft::error::CheckIsLeaderError<TypeConfig>;
+    pub type ForwardToLeader = openraft::error::ForwardToLeader<TypeConfig>;
+    pub type InitializeError = openraft::error::InitializeError<TypeConfig>;
+
+    pub type ClientWriteResponse = openraft::raft::ClientWriteResponse<TypeConfig>;
+}
+
+pub type RaftCluster = openraft::Raft<TypeConfig>;
+
+/// Waits at most `timeout` until `f` finds what it looks for in the metrics of `raft`, `None`
+/// on timeout. `Raft::wait` needs a tokio context for its timer, which tide handlers, running on
+/// async-std, do not have, so the wait runs on `runtime`, see `App::runtime`.
+pub async fn wait_for_metrics<T, F>(
+    raft: &RaftCluster,
+    runtime: &tokio::runtime::Handle,
+    timeout: Duration,
+    f: F,
+) -> Option<T>
+where
+    T: Send + 'static,
+    F: Fn(&RaftMetrics<TypeConfig>) -> Option<T> + Send + Sync + 'static,
+{
+    let raft = raft.clone();
+    let wait = async move {
+        let metrics = raft.wait(Some(timeout)).metrics(|m| f(m).is_some(), "wait_for_metrics").await.ok()?;
+        f(&metrics)
+    };
+    runtime.spawn(wait).await.ok().flatten()
+}
+
+pub type Server = tide::Server<Arc<App>>;
+
+pub async fn start_raft_node<P>(
+    node_id: NodeId,
+    dir: P,
+    http_addr: String,
+    rpc_addr: String,
+) -> std::io::Result<()>
+where
+    P: AsRef<Path>,
+{
+    if crate::Config::raft_heartbeat_interval() > crate::Config::raft_election_timeout() {
+        tracing::error!("Heatbeat interval shoud be lower than Election Timeout: election_timeout={}, heartbeat_interval={}",
+            crate::Config::raft_election_timeout(), crate::Config::raft_heartbeat_interval());
+        exit(-1);
+    }
+
+    if crate::Config::raft_heartbeat_interval() >= 300 {
+        tracing::error!("Heatbeat interval is shoud be under 300: heartbeat_interval={}",
+            crate::Config::raft_heartbeat_interval());
+        exit(-1);
+    }
+
+    // all node state lives in the data directory, which must belong to this node
+    let data_path = dir.as_ref();
+    let cluster_id = crate::Config::cluster_id();
+    let identity = node_identity::verify_or_create(data_path, node_id, Some(cluster_id.as_str()).filter(|id| !id.is_empty()))
+        .and_then(|identity| {
+            node_identity::migrate_legacy_raft_dir(Path::new(&format!("{}.db", rpc_addr)), data_path)?;
+            Ok(identity)
+        });
+    match identity {
+        Ok(identity) => tracing::info!("Node {} using data directory {:?}, cluster: {:?}", node_id, data_path, identity.cluster_id),
+        Err(e) => {
+            tracing::error!("{}", e);
+            exit(-1);
+        }
+    }
+
+    if let Some(shards) = sharding::shard_map() {
+        if shards.local.is_empty() {
+            tracing::error!("The advertised API address {} is not listed in shard_groups", crate::Config::advertise_http_addr());
+            exit(-1);
+        }
+        if crate::Config::enable_security() != 0 && crate::Config::cluster_secret().is_empty() {
+            tracing::error!("Sharding with security enabled needs a cluster_secret, the requests sent to other shard groups are signed with it");
+            exit(-1);
+        }
+        tracing::info!("Serving shards {:?} of {}", shards.local, shards.groups.len());
+    }
+
+    if tls::enabled() {
+        if let Err(e) = tls::check() {
+            tracing::error!("Invalid TLS configuration: {:#}", e);
+            exit(-1);
+        }
+        tracing::info!("Serving the API over HTTPS and Raft RPCs over wss, client certificates required: {}", !crate::Config::tls_ca().is_empty());
+    }
+
+    tracing::info!("App Server listening on: {}", http_addr);
+    
+    if crate::Config::enable_swagger_ui() {
+        tracing::info!("Swagger running on: {}/swagger-ui/", http_addr);
+    }
+
+    // Create a configuration for the raft instances.
+    let config = Config {
+        heartbeat_interval: crate::Config::raft_heartbeat_interval(),
+        election_timeout_min: crate::Config::raft_election_timeout(),
+        snapshot_max_chunk_size: crate::Config::raft_snapshot_chunk_size(),
+        snapshot_policy: match crate::Config::raft_snapshot_logs_since_last() {
+            0 => SnapshotPolicy::Never,
+            n => SnapshotPolicy::LogsSinceLast(n),
+        },
+        max_in_snapshot_log_to_keep: crate::Config::raft_max_in_snapshot_log_to_keep(),
+        purge_batch_size: crate::Config::raft_purge_batch_size().max(1),
+        ..Default::default()
+    };
+
+    // init atinyvectors module, one engine for every group of this node
+    let atinyvectors_bo = Arc::new(ATinyVectorsBO::new());
+    let atinyvectors_command = Arc::new(ATinyVectorsRaftCommand::new(atinyvectors_bo.clone()));
+
+    // Every group this node serves gets its own Raft instance, log and state machine.
+    let shards: Vec<Option<usize>> = match sharding::shard_map() {
+        Some(shards) => shards.local.iter().map(|shard| Some(*shard)).collect(),
+        None => vec![None],
+    };
+
+    let mut groups = BTreeMap::new();
+    for shard in shards {
+        let shares_engine = match (sharding::shard_map(), shard) {
+            (Some(shards), Some(shard)) => shards.shares_engine(shard),
+            _ => false,
+        };
+        let raft_dir = match shard {
+            Some(shard) => node_identity::shard_raft_dir(data_path, shard),
+            None => node_identity::raft_dir(data_path),
+        };
+
+        let (log_store, state_machine_store) = new_storage(raft_dir, atinyvectors_command.clone(), shares_engine).await;
+
+        // Groups sharing the engine snapshot their journal, see `shard_journal`, unless their log
+        // was purged before they kept one.
+        let keeps_whole_log = state_machine_store.keeps_whole_log();
+        let config = if keeps_whole_log {
+            tracing::info!("Shard {} has no complete journal, it keeps its whole log", shard.unwrap_or(0));
+            Config { snapshot_policy: SnapshotPolicy::Never, ..config.clone() }
+        } else {
+            config.clone()
+        };
+        let config = Arc::new(config.validate().unwrap());
+
+        let kvs = state_machine_store.data.kvs.clone();
+
+        // Create the network layer that will connect and communicate the raft instances and
+        // will be used in conjunction with the store created above.
+        let peer_stats = Arc::new(PeerStatsRegistry::default());
+        let network = Network::new(peer_stats.clone(), shard.unwrap_or(0));
+
+        // Create a local raft instance.
+        let raft = openraft::Raft::new(node_id, config.clone(), network, log_store, state_machine_store).await.unwrap();
+        if crate::Config::standalone() {
+            // standalone
+            tracing::info!("This is standalone mode");
+            let mut nodes = BTreeMap::new();
+            let node = Node {
+                api_addr: http_addr.clone(),
+                rpc_addr: rpc_addr.clone(),
+                role: NodeRole::Voter,
+            };
+
+            nodes.insert(node_id, node);
+            raft.initialize(nodes).await;
+        } else if crate::Config::seeds().is_empty() && shard.is_none() {
+            tracing::info!("This is cluster mode. you should call /cluster/init before api call");
+        }
+
+        let app = Arc::new(App {
+            id: node_id,
+            api_addr: http_addr.clone(),
+            rpc_addr: rpc_addr.clone(),
+            shard,
+            shares_engine,
+            keeps_whole_log,
+            raft,
+            key_values: kvs,
+            config,
+            atinyvectors_bo: atinyvectors_bo.clone(),
+            atinyvectors_command: atinyvectors_command.clone(),
+            compaction: Arc::new(Default::default()),
+            runtime: tokio::runtime::Handle::current(),
+            peer_stats,
+        });
+
+        // The nodes of a shard group are listed in the configuration, they are its seeds.
+        let seeds = match (sharding::shard_map(), shard) {
+            (Some(shards), Some(shard)) => shards.groups[shard].clone(),
+            _ => crate::Config::seeds(),
+        };
+        if !crate::Config::standalone() && !seeds.is_empty() {
+            tracing::info!("This is cluster mode, the cluster is formed from the seeds");
+            task::spawn(bootstrap::run(app.clone(), seeds, crate::Config::expected_cluster_size()));
+        }
+
+        groups.insert(shard.unwrap_or(0), app);
+    }
+
+    let echo_service = Arc::new(network::raft::Raft::new(groups.clone()));
+
+    let server = toy_rpc::Server::builder().register(echo_service).build();
+
+    let listener = TcpListener::bind(rpc_addr).await.unwrap();
+    let handle = task::spawn(async move {
+        if tls::enabled() {
+            server.accept_websocket_with_tls_config(listener, tls::server_config().unwrap()).await.unwrap();
+        } else {
+            server.accept_websocket(listener).await.unwrap();
+        }
+    });
+
+    // One HTTP server per group, the first one listens and hands requests for the others on.
+    let servers: BTreeMap<usize, Server> = groups
+        .into_iter()
+        .map(|(shard, app)| {
+            let mut app: Server = tide::Server::with_state(app);
+            app.with(ShardDispatch);
+            app.with(Authenticate);
+
+            management::rest(&mut app);
+            routes::register_routes(&mut app);
+            (shard, app)
+        })
+        .collect();
+    let app = servers.values().next().cloned().unwrap();
+    sharding::serve_groups(servers);
+
+    if tls::enabled() {
+        tls::listen(app, http_addr.clone()).await?;
+    } else {
+        app.listen(http_addr.clone()).await?;
+    }
+
+    _ = handle.await;
+    Ok(())
 }
\ No newline at end of file
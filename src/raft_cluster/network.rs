@@ -1,5 +1,6 @@
-pub mod management;
-pub mod raft;
-mod raft_network_impl;
-
+pub mod management;
+pub mod peer_stats;
+pub mod raft;
+mod raft_network_impl;
+
 pub use raft_network_impl::Network;
\ No newline at end of file
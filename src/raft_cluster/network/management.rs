@@ -1,66 +1,235 @@
-use std::collections::BTreeMap;
-use std::collections::BTreeSet;
-use std::sync::Arc;
-
-use openraft::error::Infallible;
-use openraft::RaftMetrics;
-use tide::Body;
-use tide::Request;
-use tide::Response;
-use tide::StatusCode;
-
-use crate::raft_cluster::app::App;
-use crate::raft_cluster::Node;
-use crate::raft_cluster::NodeId;
-use crate::raft_cluster::Server;
-use crate::raft_cluster::TypeConfig;
-
-// --- Cluster management
-
-pub fn rest(app: &mut Server) {
-    let mut cluster = app.at("/cluster");
-    cluster.at("/add-learner").post(add_learner);
-    cluster.at("/change-membership").post(change_membership);
-    cluster.at("/init").post(init);
-    cluster.at("/metrics").get(metrics);
-}
-
-/// Add a node as **Learner**.
-///
-/// A Learner receives log replication from the leader but does not vote.
-/// This should be done before adding a node as a member into the cluster
-/// (by calling `change-membership`)
-async fn add_learner(mut req: Request<Arc<App>>) -> tide::Result {
-    let (node_id, api_addr, rpc_addr): (NodeId, String, String) = req.body_json().await?;
-    let node = Node { rpc_addr, api_addr };
-    let res = req.state().raft.add_learner(node_id, node, true).await;
-    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
-}
-
-/// Changes specified learners to members, or remove members.
-async fn change_membership(mut req: Request<Arc<App>>) -> tide::Result {
-    let body: BTreeSet<NodeId> = req.body_json().await?;
-    let res = req.state().raft.change_membership(body, false).await;
-    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
-}
-
-/// Initialize a single-node cluster.
-async fn init(req: Request<Arc<App>>) -> tide::Result {
-    let mut nodes = BTreeMap::new();
-    let node = Node {
-        api_addr: req.state().api_addr.clone(),
-        rpc_addr: req.state().rpc_addr.clone(),
-    };
-
-    nodes.insert(req.state().id, node);
-    let res = req.state().raft.initialize(nodes).await;
-    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
-}
-
-/// Get the latest metrics of the cluster
-async fn metrics(req: Request<Arc<App>>) -> tide::Result {
-    let metrics = req.state().raft.metrics().borrow().clone();
-
-    let res: Result<RaftMetrics<TypeConfig>, Infallible> = Ok(metrics);
-    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use openraft::error::Infallible;
+use serde::Deserialize;
+use serde_json::json;
+use tide::Body;
+use tide::Request;
+use tide::Response;
+use tide::StatusCode;
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::compaction;
+use crate::raft_cluster::compaction::CompactionStatus;
+use crate::raft_cluster::membership;
+use crate::raft_cluster::membership::MembershipError;
+use crate::raft_cluster::membership::MembershipReport;
+use crate::raft_cluster::Node;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::NodeRole;
+use crate::raft_cluster::Server;
+use crate::service::auth::authorize;
+use crate::service::auth::Resource;
+use crate::service::auth::READ;
+use crate::service::auth::WRITE;
+use crate::service::forward::forward_writes;
+use crate::service::handlers::raft_response::NotLeader;
+
+// --- Cluster management
+
+pub fn rest(app: &mut Server) {
+    let mut cluster = app.at("/cluster");
+    cluster.at("/add-learner").post(authorize(Resource::System, WRITE, add_learner));
+    cluster.at("/change-membership").post(authorize(Resource::System, WRITE, change_membership));
+    cluster.at("/init").post(authorize(Resource::System, WRITE, init));
+    cluster.at("/metrics").get(authorize(Resource::System, READ, metrics));
+    cluster.at("/snapshot").post(authorize(Resource::System, WRITE, snapshot)).get(authorize(Resource::System, READ, snapshot_status));
+    cluster.at("/promote").post(authorize(Resource::System, WRITE, forward_writes(promote)));
+    cluster.at("/remove-node").post(authorize(Resource::System, WRITE, forward_writes(remove_node)));
+    cluster.at("/demote").post(authorize(Resource::System, WRITE, forward_writes(demote)));
+    cluster.at("/transfer-leader").post(authorize(Resource::System, WRITE, forward_writes(transfer_leader)));
+    cluster.at("/leave").post(authorize(Resource::System, WRITE, leave));
+}
+
+#[derive(Deserialize)]
+struct SnapshotParams {
+    purge: Option<bool>,
+}
+
+/// Body of `add-learner`, `[id, api_addr, rpc_addr]` with an optional role.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AddLearner {
+    WithRole(NodeId, String, String, NodeRole),
+    Voter(NodeId, String, String),
+}
+
+#[derive(Deserialize)]
+struct MembershipParams {
+    /// Skip the reachability check of the remaining voters.
+    force: Option<bool>,
+}
+
+/// Add a node as **Learner**.
+///
+/// A Learner receives log replication from the leader but does not vote.
+/// This should be done before adding a node as a member into the cluster
+/// (by calling `change-membership`). A `"replica"` passed as fourth element marks a node that
+/// stays a learner and serves reads only.
+async fn add_learner(mut req: Request<Arc<App>>) -> tide::Result {
+    let (node_id, api_addr, rpc_addr, role) = match req.body_json::<AddLearner>().await? {
+        AddLearner::WithRole(node_id, api_addr, rpc_addr, role) => (node_id, api_addr, rpc_addr, role),
+        AddLearner::Voter(node_id, api_addr, rpc_addr) => (node_id, api_addr, rpc_addr, NodeRole::Voter),
+    };
+    let node = Node { rpc_addr, api_addr, role };
+    let res = req.state().raft.add_learner(node_id, node, true).await;
+    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
+}
+
+/// Changes specified learners to members, or remove members. Replica nodes can not vote.
+async fn change_membership(mut req: Request<Arc<App>>) -> tide::Result {
+    let body: BTreeSet<NodeId> = req.body_json().await?;
+    let replicas: Vec<NodeId> = {
+        let metrics = req.state().raft.metrics().borrow().clone();
+        let membership = metrics.membership_config.membership();
+        body.iter().copied().filter(|id| membership.get_node(id).is_some_and(|n| n.role == NodeRole::Replica)).collect()
+    };
+    if !replicas.is_empty() {
+        return Ok(Response::builder(StatusCode::Conflict)
+            .header("Content-Type", "application/json")
+            .body(Body::from_json(&json!({"error": format!("Replica nodes can not be voters: {:?}", replicas)}))?)
+            .build());
+    }
+    let res = req.state().raft.change_membership(body, false).await;
+    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
+}
+
+/// Make a learner a voter.
+async fn promote(mut req: Request<Arc<App>>) -> tide::Result {
+    let node_id: NodeId = req.body_json().await?;
+    membership_response(membership::promote(req.state(), node_id).await)
+}
+
+/// Remove a voter or a learner from the cluster.
+///
+/// Refused when the voters left could not form a quorum, unless `?force=true`.
+async fn remove_node(mut req: Request<Arc<App>>) -> tide::Result {
+    let node_id: NodeId = req.body_json().await?;
+    let force = req.query::<MembershipParams>()?.force.unwrap_or(false);
+    membership_response(membership::remove_node(req.state(), node_id, force).await)
+}
+
+/// Turn a voter back into a learner, e.g. before taking its machine down for longer.
+async fn demote(mut req: Request<Arc<App>>) -> tide::Result {
+    let node_id: NodeId = req.body_json().await?;
+    let force = req.query::<MembershipParams>()?.force.unwrap_or(false);
+    membership_response(membership::demote(req.state(), node_id, force).await)
+}
+
+/// Hand leadership to another voter before maintenance on the current leader.
+async fn transfer_leader(mut req: Request<Arc<App>>) -> tide::Result {
+    let node_id: NodeId = req.body_json().await?;
+    membership_response(membership::transfer_leader(req.state(), node_id).await)
+}
+
+/// Remove the node receiving this request from the cluster, meant to be called by the node
+/// itself while shutting down.
+async fn leave(req: Request<Arc<App>>) -> tide::Result {
+    let force = req.query::<MembershipParams>()?.force.unwrap_or(false);
+    membership_response(membership::leave(req.state(), force).await)
+}
+
+fn membership_response(res: Result<MembershipReport, MembershipError>) -> tide::Result {
+    let status = match &res {
+        Ok(_) => 200,
+        Err(e) => e.status(),
+    };
+    let mut response = Response::builder(status).body(Body::from_json(&res)?).build();
+    if let Err(MembershipError::NotLeader { leader_id, leader_api_addr }) = res {
+        response.insert_ext(NotLeader { leader_id, leader_api_addr });
+    }
+    Ok(response)
+}
+
+/// Initialize a single-node cluster. A replica node can not start a cluster.
+async fn init(req: Request<Arc<App>>) -> tide::Result {
+    if NodeRole::from_config() == NodeRole::Replica {
+        return Ok(Response::builder(StatusCode::Conflict)
+            .header("Content-Type", "application/json")
+            .body(Body::from_json(&json!({"error": "A replica node can not initialize the cluster"}))?)
+            .build());
+    }
+
+    let mut nodes = BTreeMap::new();
+    let node = Node {
+        api_addr: req.state().api_addr.clone(),
+        rpc_addr: req.state().rpc_addr.clone(),
+        role: NodeRole::Voter,
+    };
+
+    nodes.insert(req.state().id, node);
+    let res = req.state().raft.initialize(nodes).await;
+    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
+}
+
+/// Get the latest metrics of the cluster, with the progress of the last `/cluster/snapshot` call
+/// under `compaction`, the health of the RPC connections to the other nodes under `peers` and
+/// the `role` of this node. On the leader, `replication_lag` holds the number of log entries
+/// each follower is behind.
+async fn metrics(req: Request<Arc<App>>) -> tide::Result {
+    let metrics = req.state().raft.metrics().borrow().clone();
+    let compaction = req.state().compaction.read().await.clone();
+    let peers = req.state().peer_stats.snapshot();
+
+    let replication_lag: Option<BTreeMap<NodeId, u64>> = metrics.replication.as_ref().map(|replication| {
+        let last = metrics.last_log_index.unwrap_or(0);
+        replication
+            .iter()
+            .map(|(id, matched)| (*id, last.saturating_sub(matched.map(|log_id| log_id.index).unwrap_or(0))))
+            .collect()
+    });
+
+    let mut metrics = serde_json::to_value(&metrics)?;
+    if let Some(fields) = metrics.as_object_mut() {
+        fields.insert("compaction".to_string(), serde_json::to_value(&compaction)?);
+        fields.insert("peers".to_string(), serde_json::to_value(&peers)?);
+        fields.insert("role".to_string(), serde_json::to_value(NodeRole::from_config())?);
+        fields.insert("replication_lag".to_string(), serde_json::to_value(&replication_lag)?);
+    }
+
+    let res: Result<serde_json::Value, Infallible> = Ok(metrics);
+    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&res)?).build())
+}
+
+/// Build a snapshot of everything applied so far and purge the logs it covers, keeping the
+/// configured `max_in_snapshot_log_to_keep`. Pass `?purge=false` to only build the snapshot.
+///
+/// Runs in the background; poll `GET /cluster/snapshot` or `/cluster/metrics` for progress.
+async fn snapshot(req: Request<Arc<App>>) -> tide::Result {
+    let purge = req.query::<SnapshotParams>()?.purge.unwrap_or(true);
+    let app = req.state();
+    if app.keeps_whole_log {
+        return Ok(Response::builder(StatusCode::Conflict)
+            .body(Body::from_json(&json!({"error": "This shard group keeps its whole log, it was purged before the group kept a journal"}))?)
+            .build());
+    }
+
+    let mut status = app.compaction.write().await;
+    if status.is_running() {
+        return Ok(Response::builder(StatusCode::Conflict).body(Body::from_json(&*status)?).build());
+    }
+
+    let target_index = app.raft.metrics().borrow().last_applied.map(|log_id| log_id.index);
+    *status = CompactionStatus::started(target_index);
+    let started = status.clone();
+    drop(status);
+
+    async_std::task::spawn(compaction::compact(
+        app.raft.clone(),
+        app.runtime.clone(),
+        app.compaction.clone(),
+        target_index,
+        purge,
+        app.config.max_in_snapshot_log_to_keep,
+    ));
+
+    Ok(Response::builder(StatusCode::Accepted).body(Body::from_json(&started)?).build())
+}
+
+/// Progress of the last `/cluster/snapshot` call.
+async fn snapshot_status(req: Request<Arc<App>>) -> tide::Result {
+    let status = req.state().compaction.read().await.clone();
+    Ok(Response::builder(StatusCode::Ok).body(Body::from_json(&status)?).build())
 }
\ No newline at end of file
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::raft_cluster::NodeId;
+
+/// Weight of the newest sample in the moving average of the round trip time.
+const RTT_SMOOTHING: f64 = 0.2;
+
+/// Health of the RPC connection to one peer, reported under `peers` in `/cluster/metrics`.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PeerStats {
+    pub rpc_addr: String,
+    pub connected: bool,
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    /// Successful dials, the first one included.
+    pub connects: u64,
+    pub last_success: Option<String>,
+    pub last_failure: Option<String>,
+    pub last_error: Option<String>,
+    /// Round trip time of the last successful RPC.
+    pub rtt_ms: Option<f64>,
+    /// Moving average of the round trip time.
+    pub avg_rtt_ms: Option<f64>,
+}
+
+/// Connection stats of all peers, shared by the connections of this node.
+#[derive(Debug, Default)]
+pub struct PeerStatsRegistry {
+    peers: Mutex<BTreeMap<NodeId, PeerStats>>,
+}
+
+impl PeerStatsRegistry {
+    pub fn snapshot(&self) -> BTreeMap<NodeId, PeerStats> {
+        self.peers.lock().unwrap().clone()
+    }
+
+    pub fn connected(&self, target: NodeId, rpc_addr: &str) {
+        self.update(target, |s| {
+            s.rpc_addr = rpc_addr.to_string();
+            s.connected = true;
+            s.connects += 1;
+        });
+    }
+
+    pub fn disconnected(&self, target: NodeId) {
+        self.update(target, |s| s.connected = false);
+    }
+
+    pub fn success(&self, target: NodeId, rtt: Duration) {
+        let rtt_ms = rtt.as_secs_f64() * 1000.0;
+        self.update(target, |s| {
+            s.successes += 1;
+            s.consecutive_failures = 0;
+            s.last_success = Some(chrono::Utc::now().to_rfc3339());
+            s.rtt_ms = Some(rtt_ms);
+            s.avg_rtt_ms = Some(match s.avg_rtt_ms {
+                Some(avg) => avg + RTT_SMOOTHING * (rtt_ms - avg),
+                None => rtt_ms,
+            });
+        });
+    }
+
+    pub fn failure(&self, target: NodeId, error: String) {
+        self.update(target, |s| {
+            s.failures += 1;
+            s.consecutive_failures += 1;
+            s.last_failure = Some(chrono::Utc::now().to_rfc3339());
+            s.last_error = Some(error);
+        });
+    }
+
+    fn update<F: FnOnce(&mut PeerStats)>(&self, target: NodeId, f: F) {
+        f(self.peers.lock().unwrap().entry(target).or_default());
+    }
+}
@@ -1,45 +1,69 @@
-use std::sync::Arc;
-
-use openraft::raft::AppendEntriesRequest;
-use openraft::raft::AppendEntriesResponse;
-use openraft::raft::InstallSnapshotRequest;
-use openraft::raft::InstallSnapshotResponse;
-use openraft::raft::VoteRequest;
-use openraft::raft::VoteResponse;
-use toy_rpc::macros::export_impl;
-
-use crate::raft_cluster::app::App;
-use crate::raft_cluster::TypeConfig;
-
-/// Raft protocol service.
-pub struct Raft {
-    app: Arc<App>,
-}
-
-#[export_impl]
-impl Raft {
-    pub fn new(app: Arc<App>) -> Self {
-        Self { app }
-    }
-
-    #[export_method]
-    pub async fn vote(&self, vote: VoteRequest<TypeConfig>) -> Result<VoteResponse<TypeConfig>, toy_rpc::Error> {
-        self.app.raft.vote(vote).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
-    }
-
-    #[export_method]
-    pub async fn append(
-        &self,
-        req: AppendEntriesRequest<TypeConfig>,
-    ) -> Result<AppendEntriesResponse<TypeConfig>, toy_rpc::Error> {
-        self.app.raft.append_entries(req).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
-    }
-
-    #[export_method]
-    pub async fn snapshot(
-        &self,
-        req: InstallSnapshotRequest<TypeConfig>,
-    ) -> Result<InstallSnapshotResponse<TypeConfig>, toy_rpc::Error> {
-        self.app.raft.install_snapshot(req).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
-    }
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use toy_rpc::macros::export_impl;
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::cluster_auth;
+use crate::raft_cluster::cluster_auth::Authenticated;
+use crate::raft_cluster::log_codec::AppendPayload;
+use crate::raft_cluster::TypeConfig;
+
+/// Raft protocol service of every group a node serves, keyed by shard.
+pub struct Raft {
+    groups: BTreeMap<usize, Arc<App>>,
+}
+
+#[export_impl]
+impl Raft {
+    pub fn new(groups: BTreeMap<usize, Arc<App>>) -> Self {
+        Self { groups }
+    }
+
+    fn group(&self, shard: usize) -> Result<&Arc<App>, toy_rpc::Error> {
+        self.groups
+            .get(&shard)
+            .ok_or_else(|| toy_rpc::Error::ExecutionError(format!("This node does not serve shard {}", shard)))
+    }
+
+    #[export_method]
+    pub async fn vote(
+        &self,
+        vote: Authenticated<VoteRequest<TypeConfig>>,
+    ) -> Result<VoteResponse<TypeConfig>, toy_rpc::Error> {
+        let (shard, vote) = authorize(vote)?;
+        self.group(shard)?.raft.vote(vote).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
+    }
+
+    #[export_method]
+    pub async fn append(
+        &self,
+        req: Authenticated<AppendPayload>,
+    ) -> Result<AppendEntriesResponse<TypeConfig>, toy_rpc::Error> {
+        let (shard, payload) = authorize(req)?;
+        let req = payload.into_request().map_err(toy_rpc::Error::ExecutionError)?;
+        self.group(shard)?.raft.append_entries(req).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
+    }
+
+    #[export_method]
+    pub async fn snapshot(
+        &self,
+        req: Authenticated<InstallSnapshotRequest<TypeConfig>>,
+    ) -> Result<InstallSnapshotResponse<TypeConfig>, toy_rpc::Error> {
+        let (shard, req) = authorize(req)?;
+        self.group(shard)?.raft.install_snapshot(req).await.map_err(|e| toy_rpc::Error::Internal(Box::new(e)))
+    }
+}
+
+fn authorize<T>(req: Authenticated<T>) -> Result<(usize, T), toy_rpc::Error> {
+    if !cluster_auth::rpc_allowed(&req.secret) {
+        tracing::warn!("Rejected a Raft RPC with a wrong cluster secret");
+        return Err(toy_rpc::Error::ExecutionError("Invalid cluster secret".to_string()));
+    }
+    Ok((req.shard, req.req))
 }
\ No newline at end of file
@@ -1,148 +1,305 @@
-use std::any::Any;
-use std::fmt::Display;
-
-use openraft::error::InstallSnapshotError;
-use openraft::error::NetworkError;
-use openraft::error::RPCError;
-use openraft::error::RaftError;
-use openraft::error::RemoteError;
-use openraft::network::RPCOption;
-use openraft::network::RaftNetwork;
-use openraft::network::RaftNetworkFactory;
-use openraft::raft::AppendEntriesRequest;
-use openraft::raft::AppendEntriesResponse;
-use openraft::raft::InstallSnapshotRequest;
-use openraft::raft::InstallSnapshotResponse;
-use openraft::raft::VoteRequest;
-use openraft::raft::VoteResponse;
-use openraft::AnyError;
-use serde::de::DeserializeOwned;
-use toy_rpc::pubsub::AckModeNone;
-use toy_rpc::Client;
-
-use super::raft::RaftClientStub;
-use crate::raft_cluster::Node;
-use crate::raft_cluster::NodeId;
-use crate::raft_cluster::TypeConfig;
-
-#[derive(Debug)]
-pub struct Network {}
-
-// NOTE: This could be implemented also on `Arc<ExampleNetwork>`, but since it's empty, implemented
-// directly.
-impl RaftNetworkFactory<TypeConfig> for Network {
-    type Network = NetworkConnection;
-
-    // #[tracing::instrument(level = "debug", skip_all)]
-    #[tracing::instrument]
-    async fn new_client(&mut self, target: NodeId, node: &Node) -> Self::Network {
-        let addr = format!("ws://{}", node.rpc_addr);
-
-        let client = Client::dial_websocket(&addr).await.ok();
-        tracing::debug!("new_client: is_none: {}", client.is_none());
-
-        NetworkConnection { addr, client, target }
-    }
-}
-
-pub struct NetworkConnection {
-    addr: String,
-    client: Option<Client<AckModeNone>>,
-    target: NodeId,
-}
-impl NetworkConnection {
-    async fn c<E: std::error::Error + DeserializeOwned>(
-        &mut self,
-    ) -> Result<&Client<AckModeNone>, RPCError<TypeConfig, E>> {
-        if self.client.is_none() {
-            self.client = Client::dial_websocket(&self.addr).await.ok();
-        }
-        self.client.as_ref().ok_or_else(|| RPCError::Network(NetworkError::from(AnyError::default())))
-    }
-}
-
-#[derive(Debug)]
-struct ErrWrap(Box<dyn std::error::Error>);
-
-impl Display for ErrWrap {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.0.fmt(f)
-    }
-}
-
-impl std::error::Error for ErrWrap {}
-
-fn to_error<E: std::error::Error + 'static + Clone>(e: toy_rpc::Error, target: NodeId) -> RPCError<TypeConfig, E> {
-    match e {
-        toy_rpc::Error::IoError(e) => RPCError::Network(NetworkError::new(&e)),
-        toy_rpc::Error::ParseError(e) => RPCError::Network(NetworkError::new(&ErrWrap(e))),
-        toy_rpc::Error::Internal(e) => {
-            let any: &dyn Any = &e;
-            let error: &E = any.downcast_ref().unwrap();
-            RPCError::RemoteError(RemoteError::new(target, error.clone()))
-        }
-        e @ (toy_rpc::Error::InvalidArgument
-        | toy_rpc::Error::ServiceNotFound
-        | toy_rpc::Error::MethodNotFound
-        | toy_rpc::Error::ExecutionError(_)
-        | toy_rpc::Error::Canceled(_)
-        | toy_rpc::Error::Timeout(_)
-        | toy_rpc::Error::MaxRetriesReached(_)) => RPCError::Network(NetworkError::new(&e)),
-    }
-}
-
-// With nightly-2023-12-20, and `err(Debug)` in the instrument macro, this gives the following lint
-// warning. Without `err(Debug)` it is OK. Suppress it with `#[allow(clippy::blocks_in_conditions)]`
-//
-// warning: in a `match` scrutinee, avoid complex blocks or closures with blocks; instead, move the
-// block or closure higher and bind it with a `let`
-//
-//    --> src/network/raft_network_impl.rs:99:91
-//     |
-// 99  |       ) -> Result<AppendEntriesResponse<NodeId>, RPCError<C, RaftError<C>>>
-// {
-//     |  ___________________________________________________________________________________________^
-// 100 | |         tracing::debug!(req = debug(&req), "append_entries");
-// 101 | |
-// 102 | |         let c = self.c().await?;
-// ...   |
-// 108 | |         raft.append(req).await.map_err(|e| to_error(e, self.target))
-// 109 | |     }
-//     | |_____^
-//     |
-//     = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#blocks_in_conditions
-//     = note: `#[warn(clippy::blocks_in_conditions)]` on by default
-#[allow(clippy::blocks_in_conditions)]
-impl RaftNetwork<TypeConfig> for NetworkConnection {
-    #[tracing::instrument(level = "debug", skip_all, err(Debug))]
-    async fn append_entries(
-        &mut self,
-        req: AppendEntriesRequest<TypeConfig>,
-        _option: RPCOption,
-    ) -> Result<AppendEntriesResponse<TypeConfig>, RPCError<TypeConfig, RaftError<TypeConfig>>> {
-        let c = self.c().await?;
-
-        let raft = c.raft();
-
-        raft.append(req).await.map_err(|e| to_error(e, self.target))
-    }
-
-    #[tracing::instrument(level = "debug", skip_all, err(Debug))]
-    async fn install_snapshot(
-        &mut self,
-        req: InstallSnapshotRequest<TypeConfig>,
-        _option: RPCOption,
-    ) -> Result<InstallSnapshotResponse<TypeConfig>, RPCError<TypeConfig, RaftError<TypeConfig, InstallSnapshotError>>>
-    {
-        self.c().await?.raft().snapshot(req).await.map_err(|e| to_error(e, self.target))
-    }
-
-    #[tracing::instrument(level = "debug", skip_all, err(Debug))]
-    async fn vote(
-        &mut self,
-        req: VoteRequest<TypeConfig>,
-        _option: RPCOption,
-    ) -> Result<VoteResponse<TypeConfig>, RPCError<TypeConfig, RaftError<TypeConfig>>> {
-        self.c().await?.raft().vote(req).await.map_err(|e| to_error(e, self.target))
-    }
-}
\ No newline at end of file
+use std::any::Any;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use openraft::error::InstallSnapshotError;
+use openraft::error::NetworkError;
+use openraft::error::RPCError;
+use openraft::error::RaftError;
+use openraft::error::RemoteError;
+use openraft::error::Unreachable;
+use openraft::network::RPCOption;
+use openraft::network::RaftNetwork;
+use openraft::network::RaftNetworkFactory;
+use openraft::raft::AppendEntriesRequest;
+use openraft::raft::AppendEntriesResponse;
+use openraft::raft::InstallSnapshotRequest;
+use openraft::raft::InstallSnapshotResponse;
+use openraft::raft::VoteRequest;
+use openraft::raft::VoteResponse;
+use openraft::AnyError;
+use serde::de::DeserializeOwned;
+use tokio::time::error::Elapsed;
+use tokio::time::timeout;
+use toy_rpc::pubsub::AckModeNone;
+use toy_rpc::Client;
+
+use super::peer_stats::PeerStatsRegistry;
+use super::raft::RaftClientStub;
+use crate::raft_cluster::cluster_auth::Authenticated;
+use crate::raft_cluster::log_codec::AppendPayload;
+use crate::raft_cluster::log_codec::LogFormat;
+use crate::raft_cluster::tls;
+use crate::raft_cluster::Node;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::TypeConfig;
+
+/// Delay before the first redial of a failed connection, doubled on every further failure.
+const BACKOFF_BASE: Duration = Duration::from_millis(100);
+
+const BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+/// Upper bound for a dial. The first dial, made when the connection is created, has no
+/// `RPCOption` to take a deadline from.
+const DIAL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Connections of one Raft group. The groups a node serves share its RPC port, every RPC names
+/// the group it is for.
+#[derive(Debug, Clone)]
+pub struct Network {
+    stats: Arc<PeerStatsRegistry>,
+    shard: usize,
+}
+
+impl Network {
+    pub fn new(stats: Arc<PeerStatsRegistry>, shard: usize) -> Self {
+        Self { stats, shard }
+    }
+}
+
+impl RaftNetworkFactory<TypeConfig> for Network {
+    type Network = NetworkConnection;
+
+    // #[tracing::instrument(level = "debug", skip_all)]
+    #[tracing::instrument]
+    async fn new_client(&mut self, target: NodeId, node: &Node) -> Self::Network {
+        let addr = format!("{}://{}", tls::ws_scheme(), node.rpc_addr);
+
+        let mut conn = NetworkConnection {
+            addr,
+            client: None,
+            target,
+            shard: self.shard,
+            format: LogFormat::from_config(),
+            stats: self.stats.clone(),
+            failed_dials: 0,
+            next_dial: None,
+        };
+        conn.dial(DIAL_TIMEOUT).await;
+        tracing::debug!("new_client: is_none: {}", conn.client.is_none());
+
+        conn
+    }
+}
+
+pub struct NetworkConnection {
+    addr: String,
+    client: Option<Client<AckModeNone>>,
+    target: NodeId,
+    shard: usize,
+    /// Encoding of the entries in append requests, see [`AppendPayload`].
+    format: LogFormat,
+    stats: Arc<PeerStatsRegistry>,
+    failed_dials: u32,
+    /// No redial before this instant, see [`BACKOFF_BASE`].
+    next_dial: Option<Instant>,
+}
+
+impl NetworkConnection {
+    async fn c<E: std::error::Error + DeserializeOwned>(
+        &mut self,
+        ttl: Duration,
+    ) -> Result<&Client<AckModeNone>, RPCError<TypeConfig, E>> {
+        if self.client.is_none() {
+            if self.next_dial.is_some_and(|at| Instant::now() < at) {
+                return Err(RPCError::Unreachable(Unreachable::new(&AnyError::error(format!(
+                    "waiting to redial {}",
+                    self.addr
+                )))));
+            }
+            self.dial(ttl.min(DIAL_TIMEOUT)).await;
+        }
+        self.client.as_ref().ok_or_else(|| {
+            RPCError::Unreachable(Unreachable::new(&AnyError::error(format!("failed to dial {}", self.addr))))
+        })
+    }
+
+    async fn dial(&mut self, timeout: Duration) {
+        let addr = self.addr.clone();
+        let dial = async move {
+            if !tls::enabled() {
+                return Client::dial_websocket(&addr).await;
+            }
+            let config = tls::client_config()
+                .map_err(|e| toy_rpc::Error::IoError(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())))?;
+            Client::dial_websocket_with_tls_config(&addr, tls::host_of(&addr), config).await
+        };
+        let error = match tokio::time::timeout(timeout, dial).await {
+            Ok(Ok(client)) => {
+                self.client = Some(client);
+                self.failed_dials = 0;
+                self.next_dial = None;
+                self.stats.connected(self.target, self.addr.split_once("://").map_or(self.addr.as_str(), |(_, addr)| addr));
+                return;
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("dial timed out after {:?}", timeout),
+        };
+
+        self.failed_dials += 1;
+        let delay = backoff(self.failed_dials);
+        self.next_dial = Some(Instant::now() + delay);
+        tracing::debug!("dial {} failed ({}), next attempt in {:?}", self.addr, error, delay);
+        self.stats.failure(self.target, error);
+    }
+
+    /// Books the outcome of an RPC. Connections that failed on IO, sent garbage or did not
+    /// answer within the deadline are dropped, the next RPC redials.
+    fn finish<T, E>(
+        &mut self,
+        res: Result<Result<T, toy_rpc::Error>, Elapsed>,
+        started: Instant,
+        ttl: Duration,
+    ) -> Result<T, RPCError<TypeConfig, E>>
+    where
+        E: std::error::Error + 'static + Clone,
+    {
+        match res {
+            Ok(Ok(resp)) => {
+                self.stats.success(self.target, started.elapsed());
+                Ok(resp)
+            }
+            // the peer answered, with an error of its own
+            Ok(Err(e @ toy_rpc::Error::Internal(_))) => {
+                self.stats.success(self.target, started.elapsed());
+                Err(to_error(e, self.target))
+            }
+            Ok(Err(e)) => {
+                if matches!(e, toy_rpc::Error::IoError(_) | toy_rpc::Error::ParseError(_)) {
+                    self.drop_client();
+                }
+                self.stats.failure(self.target, e.to_string());
+                Err(to_error(e, self.target))
+            }
+            Err(elapsed) => {
+                self.drop_client();
+                self.stats.failure(self.target, format!("no answer within {:?}", ttl));
+                Err(RPCError::Network(NetworkError::new(&elapsed)))
+            }
+        }
+    }
+
+    fn drop_client(&mut self) {
+        if self.client.take().is_some() {
+            self.stats.disconnected(self.target);
+        }
+    }
+}
+
+/// Exponential backoff with +/-50% jitter, so peers that lost the same node do not redial it in
+/// lockstep.
+fn backoff(failures: u32) -> Duration {
+    let exp = BACKOFF_BASE.saturating_mul(1u32 << failures.saturating_sub(1).min(16)).min(BACKOFF_MAX);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    let jitter = 0.5 + (nanos % 1000) as f64 / 1000.0;
+    exp.mul_f64(jitter)
+}
+
+#[derive(Debug)]
+struct ErrWrap(Box<dyn std::error::Error>);
+
+impl Display for ErrWrap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for ErrWrap {}
+
+fn to_error<E: std::error::Error + 'static + Clone>(e: toy_rpc::Error, target: NodeId) -> RPCError<TypeConfig, E> {
+    match e {
+        toy_rpc::Error::IoError(e) => RPCError::Network(NetworkError::new(&e)),
+        toy_rpc::Error::ParseError(e) => RPCError::Network(NetworkError::new(&ErrWrap(e))),
+        toy_rpc::Error::Internal(e) => {
+            let any: &dyn Any = &e;
+            let error: &E = any.downcast_ref().unwrap();
+            RPCError::RemoteError(RemoteError::new(target, error.clone()))
+        }
+        e @ (toy_rpc::Error::InvalidArgument
+        | toy_rpc::Error::ServiceNotFound
+        | toy_rpc::Error::MethodNotFound
+        | toy_rpc::Error::ExecutionError(_)
+        | toy_rpc::Error::Canceled(_)
+        | toy_rpc::Error::Timeout(_)
+        | toy_rpc::Error::MaxRetriesReached(_)) => RPCError::Network(NetworkError::new(&e)),
+    }
+}
+
+// With nightly-2023-12-20, and `err(Debug)` in the instrument macro, this gives the following lint
+// warning. Without `err(Debug)` it is OK. Suppress it with `#[allow(clippy::blocks_in_conditions)]`
+//
+// warning: in a `match` scrutinee, avoid complex blocks or closures with blocks; instead, move the
+// block or closure higher and bind it with a `let`
+//
+//    --> src/network/raft_network_impl.rs:99:91
+//     |
+// 99  |       ) -> Result<AppendEntriesResponse<NodeId>, RPCError<C, RaftError<C>>>
+// {
+//     |  ___________________________________________________________________________________________^
+// 100 | |         tracing::debug!(req = debug(&req), "append_entries");
+// 101 | |
+// 102 | |         let c = self.c().await?;
+// ...   |
+// 108 | |         raft.append(req).await.map_err(|e| to_error(e, self.target))
+// 109 | |     }
+//     | |_____^
+//     |
+//     = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#blocks_in_conditions
+//     = note: `#[warn(clippy::blocks_in_conditions)]` on by default
+#[allow(clippy::blocks_in_conditions)]
+impl RaftNetwork<TypeConfig> for NetworkConnection {
+    #[tracing::instrument(level = "debug", skip_all, err(Debug))]
+    async fn append_entries(
+        &mut self,
+        req: AppendEntriesRequest<TypeConfig>,
+        option: RPCOption,
+    ) -> Result<AppendEntriesResponse<TypeConfig>, RPCError<TypeConfig, RaftError<TypeConfig>>> {
+        let ttl = option.hard_ttl();
+        let started = Instant::now();
+        let payload = AppendPayload::new(req, self.format)
+            .map_err(|e| RPCError::Network(NetworkError::new(&AnyError::error(e))))?;
+        let c = self.c(ttl).await?;
+
+        let raft = c.raft();
+
+        let res = timeout(ttl, raft.append(Authenticated::new(self.shard, payload))).await;
+        self.finish(res, started, ttl)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(Debug))]
+    async fn install_snapshot(
+        &mut self,
+        req: InstallSnapshotRequest<TypeConfig>,
+        option: RPCOption,
+    ) -> Result<InstallSnapshotResponse<TypeConfig>, RPCError<TypeConfig, RaftError<TypeConfig, InstallSnapshotError>>>
+    {
+        let ttl = option.hard_ttl();
+        let started = Instant::now();
+        let shard = self.shard;
+        let res = timeout(ttl, self.c(ttl).await?.raft().snapshot(Authenticated::new(shard, req))).await;
+
+        // A failed chunk is resent at the same offset over a redialed connection, and the follower
+        // writes it into the file it is streaming into, so a dropped connection costs one chunk.
+        // A transfer that starts over at offset 0, after a restart of either side or a new leader,
+        // sends everything again: the response has no way to tell the leader what the follower
+        // already holds.
+        self.finish(res, started, ttl)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, err(Debug))]
+    async fn vote(
+        &mut self,
+        req: VoteRequest<TypeConfig>,
+        option: RPCOption,
+    ) -> Result<VoteResponse<TypeConfig>, RPCError<TypeConfig, RaftError<TypeConfig>>> {
+        let ttl = option.hard_ttl();
+        let started = Instant::now();
+        let shard = self.shard;
+        let res = timeout(ttl, self.c(ttl).await?.raft().vote(Authenticated::new(shard, req))).await;
+        self.finish(res, started, ttl)
+    }
+}
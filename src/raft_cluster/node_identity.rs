@@ -0,0 +1,124 @@
+//! Identity of the node that owns a data directory.
+//!
+//! All state of a node lives under `Config::data_path()`: the engine database, the key-value
+//! storages and engine snapshots, and the Raft log, vote and snapshots in [`RAFT_DIR`], or in
+//! one [`shard_raft_dir`] per shard group the node serves. The directory carries an
+//! [`IDENTITY_FILE`] written on first start, and a node refuses to start on a directory that
+//! belongs to another node or cluster, or was written by a newer version.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::raft_cluster::NodeId;
+
+pub const IDENTITY_FILE: &str = "node.json";
+
+/// Sub directory of the data directory holding the Raft RocksDB.
+pub const RAFT_DIR: &str = "raft";
+
+/// Version of the data directory layout written by this build.
+pub const DATA_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NodeIdentity {
+    pub node_id: NodeId,
+    /// Set from `Config::cluster_id()`, once a cluster id is configured.
+    pub cluster_id: Option<String>,
+    pub format_version: u32,
+    pub created_at: String,
+}
+
+pub fn raft_dir(data_path: &Path) -> PathBuf {
+    data_path.join(RAFT_DIR)
+}
+
+/// Raft state of the shard group `shard`, for nodes serving shard groups.
+pub fn shard_raft_dir(data_path: &Path, shard: usize) -> PathBuf {
+    data_path.join(format!("{}-shard-{}", RAFT_DIR, shard))
+}
+
+/// Checks that `data_path` belongs to this node, creating the identity on first start.
+///
+/// A directory without identity file that already holds Raft state was written before identities
+/// existed and is adopted as is.
+pub fn verify_or_create(data_path: &Path, node_id: NodeId, cluster_id: Option<&str>) -> Result<NodeIdentity, String> {
+    let path = data_path.join(IDENTITY_FILE);
+
+    let mut identity = match fs::read(&path) {
+        Ok(data) => serde_json::from_slice::<NodeIdentity>(&data)
+            .map_err(|e| format!("Invalid node identity {:?}: {}", path, e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if raft_dir(data_path).exists() {
+                tracing::warn!("Adopting data directory {:?} without node identity for node {}", data_path, node_id);
+            }
+            let identity = NodeIdentity {
+                node_id,
+                cluster_id: cluster_id.map(str::to_string),
+                format_version: DATA_FORMAT_VERSION,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            };
+            write(&path, &identity)?;
+            return Ok(identity);
+        }
+        Err(e) => return Err(format!("Failed to read node identity {:?}: {}", path, e)),
+    };
+
+    if identity.node_id != node_id {
+        return Err(format!(
+            "Data directory {:?} belongs to node {}, not to node {}",
+            data_path, identity.node_id, node_id
+        ));
+    }
+    if identity.format_version > DATA_FORMAT_VERSION {
+        return Err(format!(
+            "Data directory {:?} has format version {}, this build supports up to {}",
+            data_path, identity.format_version, DATA_FORMAT_VERSION
+        ));
+    }
+
+    match (&identity.cluster_id, cluster_id) {
+        (Some(existing), Some(configured)) if existing != configured => {
+            return Err(format!(
+                "Data directory {:?} belongs to cluster {}, not to cluster {}",
+                data_path, existing, configured
+            ));
+        }
+        (None, Some(configured)) => {
+            identity.cluster_id = Some(configured.to_string());
+            write(&path, &identity)?;
+        }
+        _ => {}
+    }
+
+    Ok(identity)
+}
+
+/// Moves a Raft DB from the old location, `<rpc_addr>.db` in the working directory, into the data
+/// directory.
+pub fn migrate_legacy_raft_dir(legacy: &Path, data_path: &Path) -> Result<(), String> {
+    let target = raft_dir(data_path);
+    if !legacy.is_dir() || target.exists() {
+        return Ok(());
+    }
+
+    tracing::info!("Moving Raft data from {:?} to {:?}", legacy, target);
+    fs::rename(legacy, &target).map_err(|e| {
+        format!("Failed to move Raft data from {:?} to {:?}, move it manually: {}", legacy, target, e)
+    })
+}
+
+fn write(path: &Path, identity: &NodeIdentity) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+
+    let data = serde_json::to_vec_pretty(identity).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
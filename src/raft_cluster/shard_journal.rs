@@ -0,0 +1,216 @@
+//! State of a shard group whose nodes serve other groups too, kept apart from the engine.
+//!
+//! The groups of a node share its atinyvectors engine, so a snapshot of the engine holds the
+//! vectors of every group of the node and cannot be installed for one of them, see
+//! `ShardMap::shares_engine`. Such a group keeps instead, in its own RocksDB, the commands that
+//! rebuild its part of the engine: every space and version change in log order, and the last
+//! write of each of its vectors, keys and tokens. Its snapshots carry these commands in
+//! [`ENTRY`], and installing one replays them, so the group snapshots and purges its log like any
+//! other. Commands replayed twice are skipped, like a replay after a restart.
+//!
+//! Keys sort in the order they are replayed in: `schema/<log index>`, `token/<token>`,
+//! `kv/<space>/<key>` and `vector/<space>/<version id>/<vector id>`. Space names contain no `/`.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use rocksdb::ColumnFamily;
+use rocksdb::Direction;
+use rocksdb::IteratorMode;
+use rocksdb::WriteBatch;
+use rocksdb::DB;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+
+/// Column family holding the journal.
+pub const CF: &str = "shard_journal";
+
+/// Snapshot archive entry holding the journal, one [`Record`] per line.
+pub const ENTRY: &str = "shard/journal.jsonl";
+
+/// Set in the `store` column family once the journal covers the whole log.
+pub const COMPLETE_KEY: &[u8] = b"shard_journal_complete";
+
+const SCHEMA: &str = "schema/";
+const TOKEN: &str = "token/";
+const KV: &str = "kv/";
+const VECTOR: &str = "vector/";
+
+/// A change to the journal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalOp {
+    Put(String, Command),
+    Delete(String),
+    /// Removes every key starting with the prefix, which ends with `/`.
+    DeletePrefix(String),
+}
+
+/// A line of [`ENTRY`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub key: String,
+    pub command: Command,
+}
+
+/// Changes to the journal for `command`, applied at log `index`. `default_version` resolves the
+/// version id 0 of a space, so that vectors are kept under the version they were written to.
+pub fn journal_ops(index: u64, command: &Command, default_version: impl Fn(&str) -> i32) -> Vec<JournalOp> {
+    let schema = || JournalOp::Put(format!("{}{:020}", SCHEMA, index), command.clone());
+    let version = |space_name: &str, version_id: i32| {
+        if version_id == 0 {
+            default_version(space_name)
+        } else {
+            version_id
+        }
+    };
+
+    match command {
+        Command::CreateSpace { .. } | Command::UpdateSpace { .. } | Command::CreateVersion { .. } => vec![schema()],
+        Command::DeleteSpace { space_name, .. } => vec![
+            schema(),
+            JournalOp::DeletePrefix(format!("{}{}/", VECTOR, space_name)),
+            JournalOp::DeletePrefix(format!("{}{}/", KV, space_name)),
+        ],
+        Command::DeleteVersion { space_name, version_id } => vec![
+            schema(),
+            JournalOp::DeletePrefix(format!("{}{}/{}/", VECTOR, space_name, version(space_name, *version_id))),
+        ],
+        Command::UpsertVectors { space_name, version_id, value, index_name } => {
+            let version_id = version(space_name, *version_id);
+            value
+                .get("vectors")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|vector| {
+                    let id = vector.get("id").and_then(|v| v.as_u64())?;
+                    Some(JournalOp::Put(
+                        format!("{}{}/{}/{}", VECTOR, space_name, version_id, id),
+                        Command::UpsertVectors {
+                            space_name: space_name.clone(),
+                            version_id,
+                            value: json!({"vectors": [vector]}),
+                            index_name: index_name.clone(),
+                        },
+                    ))
+                })
+                .collect()
+        }
+        Command::StoragePutKey { space_name, key, .. } => {
+            vec![JournalOp::Put(format!("{}{}/{}", KV, space_name, key), command.clone())]
+        }
+        Command::StorageRemoveKey { space_name, key } => vec![JournalOp::Delete(format!("{}{}/{}", KV, space_name, key))],
+        // a token is replayed by creating it with its last value
+        Command::CreateRbacToken { token, value } | Command::UpdateRbacToken { token, value } => vec![JournalOp::Put(
+            format!("{}{}", TOKEN, token),
+            Command::CreateRbacToken {
+                token: token.clone(),
+                value: value.clone(),
+            },
+        )],
+        Command::DeleteRbacToken { token } => vec![JournalOp::Delete(format!("{}{}", TOKEN, token))],
+        // user snapshots are refused on these groups, see `ShardMap::shares_engine`
+        Command::CreateSnapshot { .. }
+        | Command::RestoreSnapshot { .. }
+        | Command::DeleteSnapshot { .. }
+        | Command::SyncSnapshot { .. } => vec![],
+    }
+}
+
+/// The command that undoes the key `key` of a journal once a snapshot without it is installed,
+/// for the keys the engine can remove.
+pub fn removal(key: &str) -> Option<Command> {
+    if let Some(token) = key.strip_prefix(TOKEN) {
+        return Some(Command::DeleteRbacToken { token: token.to_string() });
+    }
+    let (space_name, key) = key.strip_prefix(KV)?.split_once('/')?;
+    Some(Command::StorageRemoveKey {
+        space_name: space_name.to_string(),
+        key: key.to_string(),
+    })
+}
+
+fn cf(db: &DB) -> &ColumnFamily {
+    db.cf_handle(CF).unwrap()
+}
+
+/// End of the range of keys starting with `prefix`, which ends with `/`.
+fn prefix_end(prefix: &str) -> String {
+    format!("{}0", prefix.trim_end_matches('/'))
+}
+
+pub fn apply(db: &DB, ops: Vec<JournalOp>) -> Result<(), rocksdb::Error> {
+    if ops.is_empty() {
+        return Ok(());
+    }
+
+    let mut batch = WriteBatch::default();
+    for op in ops {
+        match op {
+            JournalOp::Put(key, command) => batch.put_cf(cf(db), key, serde_json::to_vec(&command).unwrap()),
+            JournalOp::Delete(key) => batch.delete_cf(cf(db), key),
+            JournalOp::DeletePrefix(prefix) => batch.delete_range_cf(cf(db), &prefix, prefix_end(&prefix)),
+        }
+    }
+    db.write(batch)
+}
+
+/// Keys of the tokens and key-value entries in the journal, the ones [`removal`] can undo.
+pub fn removable_keys(db: &DB) -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    for prefix in [TOKEN, KV] {
+        let iter = db.iterator_cf(cf(db), IteratorMode::From(prefix.as_bytes(), Direction::Forward));
+        for (key, _) in iter.flatten() {
+            let key = String::from_utf8_lossy(&key).into_owned();
+            if !key.starts_with(prefix) {
+                break;
+            }
+            keys.insert(key);
+        }
+    }
+    keys
+}
+
+pub fn clear(db: &DB) -> Result<(), rocksdb::Error> {
+    let mut batch = WriteBatch::default();
+    batch.delete_range_cf(cf(db), "", "~");
+    db.write(batch)
+}
+
+/// Writes the journal in replay order, one [`Record`] per line.
+pub fn export<W: Write>(db: &DB, w: &mut W) -> io::Result<()> {
+    for item in db.iterator_cf(cf(db), IteratorMode::Start) {
+        let (key, value) = item.map_err(io::Error::other)?;
+        let command: Command = serde_json::from_slice(&value).map_err(io::Error::other)?;
+        let record = Record {
+            key: String::from_utf8_lossy(&key).into_owned(),
+            command,
+        };
+        serde_json::to_writer(&mut *w, &record)?;
+        w.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Reads the next line written by [`export`], `None` at the end.
+pub fn next_record<R: BufRead>(r: &mut R) -> io::Result<Option<Record>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if r.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            return serde_json::from_str(&line).map(Some).map_err(io::Error::other);
+        }
+    }
+}
+
+pub fn put(db: &DB, record: &Record) -> Result<(), rocksdb::Error> {
+    db.put_cf(cf(db), &record.key, serde_json::to_vec(&record.command).unwrap())
+}
@@ -0,0 +1,193 @@
+//! On-disk layout of the state-machine snapshot exchanged between Raft nodes.
+//!
+//! A snapshot is a flat archive: an 8 byte magic followed by entries of
+//! `u32 path length | path | u64 data length | data` (big endian). Paths are relative and use
+//! `/` as separator:
+//!
+//! - `kvs.json`: the `StateMachineData::kvs` map.
+//! - `engine/<file>`: the archive produced by the atinyvectors snapshot service.
+//! - `space/...storage.rocksdb/<file>`: files of the per-space key-value storages, relative to
+//!   `Config::data_path()`.
+//! - `shard/journal.jsonl`: instead of the two above, the journal of a shard group sharing the
+//!   engine, see `shard_journal`.
+//!
+//! Snapshots written before this format existed are the bare `kvs` JSON and are still accepted.
+//!
+//! Archives are written and read as streams, so the size of a snapshot is bounded by disk space
+//! rather than memory.
+
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+
+pub const MAGIC: &[u8; 8] = b"ATVSNAP1";
+
+pub const KVS_ENTRY: &str = "kvs.json";
+pub const ENGINE_PREFIX: &str = "engine/";
+pub const SPACE_PREFIX: &str = "space/";
+
+const KV_STORAGE_SUFFIX: &str = "storage.rocksdb";
+
+pub fn is_archive(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn write_header<W: Write>(w: &mut W) -> io::Result<()> {
+    w.write_all(MAGIC)
+}
+
+pub fn write_entry<W: Write>(w: &mut W, path: &str, data: &[u8]) -> io::Result<()> {
+    write_entry_header(w, path, data.len() as u64)?;
+    w.write_all(data)
+}
+
+/// Appends the contents of `file` as an entry without loading it in memory.
+pub fn write_file_entry<W: Write>(w: &mut W, path: &str, file: &Path) -> io::Result<()> {
+    let mut f = fs::File::open(file)?;
+    let len = f.metadata()?.len();
+    write_entry_header(w, path, len)?;
+
+    let copied = io::copy(&mut (&mut f).take(len), w)?;
+    if copied != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("{} shrank while archived", file.display())));
+    }
+    Ok(())
+}
+
+fn write_entry_header<W: Write>(w: &mut W, path: &str, len: u64) -> io::Result<()> {
+    w.write_u32::<BigEndian>(path.len() as u32)?;
+    w.write_all(path.as_bytes())?;
+    w.write_u64::<BigEndian>(len)
+}
+
+pub fn read_header<R: Read>(r: &mut R) -> io::Result<()> {
+    let mut magic = [0u8; 8];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a state machine snapshot"));
+    }
+    Ok(())
+}
+
+/// Reads the path and length of the next entry, `None` at the end of the archive.
+///
+/// The caller must consume exactly `length` bytes of data before asking for the next entry.
+pub fn next_entry<R: Read>(r: &mut R) -> io::Result<Option<(String, u64)>> {
+    let path_len = match r.read_u32::<BigEndian>() {
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut path = vec![0u8; path_len];
+    r.read_exact(&mut path)?;
+    let path = String::from_utf8(path).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    check_relative(&path)?;
+
+    let len = r.read_u64::<BigEndian>()?;
+    Ok(Some((path, len)))
+}
+
+/// Writes the files of every per-space key-value storage found under `data_path`.
+pub fn write_kv_storages<W: Write>(w: &mut W, data_path: &Path) -> io::Result<()> {
+    for dir in kv_storage_dirs(data_path)? {
+        for file in files_under(&dir)? {
+            let rel = relative_path(data_path, &file)?;
+            write_file_entry(w, &rel, &file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes every per-space key-value storage under `data_path`, so a restored snapshot does
+/// not leave behind storages of spaces it does not contain.
+pub fn clear_kv_storages(data_path: &Path) -> io::Result<()> {
+    for dir in kv_storage_dirs(data_path)? {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Writes a `space/...` entry back to its place under `data_path`.
+pub fn restore_file<R: Read>(data_path: &Path, path: &str, data: &mut R) -> io::Result<()> {
+    check_relative(path)?;
+    let target = data_path.join(path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::File::create(target)?;
+    io::copy(data, &mut file)?;
+    Ok(())
+}
+
+fn kv_storage_dirs(data_path: &Path) -> io::Result<Vec<PathBuf>> {
+    let space_dir = data_path.join(SPACE_PREFIX.trim_end_matches('/'));
+    let mut found = Vec::new();
+    if !space_dir.is_dir() {
+        return Ok(found);
+    }
+
+    let mut pending = vec![space_dir];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let is_storage = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.ends_with(KV_STORAGE_SUFFIX))
+                .unwrap_or(false);
+            if is_storage {
+                found.push(path);
+            } else {
+                pending.push(path);
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+fn files_under(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn relative_path(root: &Path, path: &Path) -> io::Result<String> {
+    let rel = path.strip_prefix(root).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let parts: Vec<&str> = rel
+        .components()
+        .map(|c| c.as_os_str().to_str().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "non UTF-8 path")))
+        .collect::<io::Result<_>>()?;
+    Ok(parts.join("/"))
+}
+
+/// Entries must stay inside the directory they are restored into.
+fn check_relative(path: &str) -> io::Result<()> {
+    let safe = !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)));
+    if !safe {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid snapshot entry path: {}", path)));
+    }
+    Ok(())
+}
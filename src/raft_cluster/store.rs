@@ -1,503 +1,1090 @@
-use std::collections::BTreeMap;
-use std::fmt::Debug;
-use std::io::Cursor;
-use std::ops::RangeBounds;
-use std::path::Path;
-use std::sync::Arc;
-
-use byteorder::BigEndian;
-use byteorder::ReadBytesExt;
-use byteorder::WriteBytesExt;
-use openraft::storage::IOFlushed;
-use openraft::storage::LogState;
-use openraft::storage::RaftLogStorage;
-use openraft::storage::RaftStateMachine;
-use openraft::storage::Snapshot;
-use openraft::AnyError;
-use openraft::Entry;
-use openraft::EntryPayload;
-use openraft::ErrorSubject;
-use openraft::ErrorVerb;
-use openraft::LogId;
-use openraft::OptionalSend;
-use openraft::RaftLogReader;
-use openraft::RaftSnapshotBuilder;
-use openraft::SnapshotMeta;
-use openraft::StorageError;
-use openraft::StoredMembership;
-use openraft::Vote;
-use rocksdb::ColumnFamily;
-use rocksdb::ColumnFamilyDescriptor;
-use rocksdb::Direction;
-use rocksdb::Options;
-use rocksdb::DB;
-use serde::Deserialize;
-use serde::Serialize;
-use tokio::sync::RwLock;
-
-use crate::raft_cluster::typ;
-use crate::raft_cluster::NodeId;
-use crate::raft_cluster::SnapshotData;
-use crate::raft_cluster::TypeConfig;
-
-use crate::atinyvectors::atinyvectors_raft_command::ATinyVectorsRaftCommand;
-
-/**
- * Here you will set the types of request that will interact with the raft nodes.
- * For example the `Set` will be used to write data (key and value) to the raft database.
- * The `AddNode` will append a new node to the current existing shared list of nodes.
- * You will want to add any request that can write data in all nodes here.
- */
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub enum Request {
-    Set { key: String, value: String },
-}
-
-/**
- * Here you will defined what type of answer you expect from reading the data of a node.
- * In this example it will return a optional value from a given key in
- * the `ExampleRequest.Set`.
- *
- * TODO: Should we explain how to create multiple `AppDataResponse`?
- *
- */
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Response {
-    pub value: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct StoredSnapshot {
-    pub meta: SnapshotMeta<TypeConfig>,
-
-    /// The data of the state machine at the time of this snapshot.
-    pub data: Vec<u8>,
-}
-
-#[derive(Debug, Clone)]
-pub struct StateMachineStore {
-    pub data: StateMachineData,
-    pub atinyvectors_command: Arc<ATinyVectorsRaftCommand>,
-
-    /// snapshot index is not persisted in this example.
-    ///
-    /// It is only used as a suffix of snapshot id, and should be globally unique.
-    /// In practice, using a timestamp in micro-second would be good enough.
-    snapshot_idx: u64,
-
-    /// State machine stores snapshot in db.
-    db: Arc<DB>,
-}
-
-#[derive(Debug, Clone)]
-pub struct StateMachineData {
-    pub last_applied_log_id: Option<LogId<NodeId>>,
-
-    pub last_membership: StoredMembership<TypeConfig>,
-
-    /// State built from applying the raft logs
-    pub kvs: Arc<RwLock<BTreeMap<String, String>>>,
-}
-
-impl RaftSnapshotBuilder<TypeConfig> for StateMachineStore {
-    async fn build_snapshot(&mut self) -> Result<Snapshot<TypeConfig>, StorageError<TypeConfig>> {
-        let last_applied_log = self.data.last_applied_log_id;
-        let last_membership = self.data.last_membership.clone();
-
-        let kv_json = {
-            let kvs = self.data.kvs.read().await;
-            serde_json::to_vec(&*kvs).map_err(|e| StorageError::read_state_machine(&e))?
-        };
-
-        let snapshot_id = if let Some(last) = last_applied_log {
-            format!("{}-{}-{}", last.leader_id, last.index, self.snapshot_idx)
-        } else {
-            format!("--{}", self.snapshot_idx)
-        };
-
-        let meta = SnapshotMeta {
-            last_log_id: last_applied_log,
-            last_membership,
-            snapshot_id,
-        };
-
-        let snapshot = StoredSnapshot {
-            meta: meta.clone(),
-            data: kv_json.clone(),
-        };
-
-        self.set_current_snapshot_(snapshot)?;
-
-        Ok(Snapshot {
-            meta,
-            snapshot: Box::new(Cursor::new(kv_json)),
-        })
-    }
-}
-
-impl StateMachineStore {
-    async fn new(db: Arc<DB>, atinyvectors_command: Arc<ATinyVectorsRaftCommand>) -> Result<StateMachineStore, StorageError<TypeConfig>> {
-        let mut sm = Self {
-            data: StateMachineData {
-                last_applied_log_id: None,
-                last_membership: Default::default(),
-                kvs: Arc::new(Default::default()),
-            },
-            atinyvectors_command,
-            snapshot_idx: 0,
-            db,
-        };
-
-        let snapshot = sm.get_current_snapshot_()?;
-        if let Some(snap) = snapshot {
-            sm.update_state_machine_(snap).await?;
-        }
-
-        Ok(sm)
-    }
-
-    async fn update_state_machine_(&mut self, snapshot: StoredSnapshot) -> Result<(), StorageError<TypeConfig>> {
-        let kvs: BTreeMap<String, String> = serde_json::from_slice(&snapshot.data)
-            .map_err(|e| StorageError::read_snapshot(Some(snapshot.meta.signature()), &e))?;
-
-        self.data.last_applied_log_id = snapshot.meta.last_log_id;
-        self.data.last_membership = snapshot.meta.last_membership.clone();
-        let mut x = self.data.kvs.write().await;
-        *x = kvs;
-
-        Ok(())
-    }
-
-    fn get_current_snapshot_(&self) -> StorageResult<Option<StoredSnapshot>> {
-        Ok(self
-            .db
-            .get_cf(self.store(), b"snapshot")
-            .map_err(|e| StorageError::read(&e))?
-            .and_then(|v| serde_json::from_slice(&v).ok()))
-    }
-
-    fn set_current_snapshot_(&self, snap: StoredSnapshot) -> StorageResult<()> {
-        self.db
-            .put_cf(self.store(), b"snapshot", serde_json::to_vec(&snap).unwrap().as_slice())
-            .map_err(|e| StorageError::write_snapshot(Some(snap.meta.signature()), &e))?;
-        self.flush(ErrorSubject::Snapshot(Some(snap.meta.signature())), ErrorVerb::Write)?;
-        Ok(())
-    }
-
-    fn flush(&self, subject: ErrorSubject<TypeConfig>, verb: ErrorVerb) -> Result<(), StorageError<TypeConfig>> {
-        self.db.flush_wal(true).map_err(|e| StorageError::new(subject, verb, AnyError::new(&e)))?;
-        Ok(())
-    }
-
-    fn store(&self) -> &ColumnFamily {
-        self.db.cf_handle("store").unwrap()
-    }
-}
-
-impl RaftStateMachine<TypeConfig> for StateMachineStore {
-    type SnapshotBuilder = Self;
-
-    async fn applied_state(
-        &mut self,
-    ) -> Result<(Option<LogId<NodeId>>, StoredMembership<TypeConfig>), StorageError<TypeConfig>> {
-        Ok((self.data.last_applied_log_id, self.data.last_membership.clone()))
-    }
-
-    async fn apply<I>(&mut self, entries: I) -> Result<Vec<Response>, StorageError<TypeConfig>>
-    where
-        I: IntoIterator<Item = typ::Entry> + OptionalSend,
-        I::IntoIter: OptionalSend,
-    {
-        let entries = entries.into_iter();
-        let mut replies = Vec::with_capacity(entries.size_hint().0);
-
-        for ent in entries {
-            self.data.last_applied_log_id = Some(ent.log_id);
-            let mut resp_value = None;
-
-            match ent.payload {
-                EntryPayload::Blank => {}
-                EntryPayload::Normal(req) => {
-                    if let Request::Set { key, value } = req {
-                        tracing::info!("apply : key={} value={}", key, value);
-
-                        if let Ok(parsed_value) = serde_json::from_str::<serde_json::Value>(&value) {
-                            if let Some(request_obj) = parsed_value.get("request") {
-                                if let Some(command) = request_obj.get("command").and_then(|v| v.as_str()) {
-                                    self.atinyvectors_command.clone().process_command(command, request_obj, &key, &value).await;
-                                } else {
-                                    tracing::warn!("No 'command' field found in 'request'");
-                                }
-                            } else {
-                                tracing::warn!("No 'request' field found in JSON");
-                            }
-                        } else {
-                            tracing::error!("Failed to parse value as JSON: {}", value);
-                        }
-                        resp_value = Some(value.clone());
-                    }
-                }
-                EntryPayload::Membership(mem) => {
-                    self.data.last_membership = StoredMembership::new(Some(ent.log_id), mem);
-                }
-            }
-
-            replies.push(Response { value: resp_value });
-        }
-
-        Ok(replies)
-    }
-    
-    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
-        self.snapshot_idx += 1;
-        self.clone()
-    }
-
-    async fn begin_receiving_snapshot(&mut self) -> Result<Box<Cursor<Vec<u8>>>, StorageError<TypeConfig>> {
-        Ok(Box::new(Cursor::new(Vec::new())))
-    }
-
-    async fn install_snapshot(
-        &mut self,
-        meta: &SnapshotMeta<TypeConfig>,
-        snapshot: Box<SnapshotData>,
-    ) -> Result<(), StorageError<TypeConfig>> {
-        let new_snapshot = StoredSnapshot {
-            meta: meta.clone(),
-            data: snapshot.into_inner(),
-        };
-
-        self.update_state_machine_(new_snapshot.clone()).await?;
-
-        self.set_current_snapshot_(new_snapshot)?;
-
-        Ok(())
-    }
-
-    async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<TypeConfig>>, StorageError<TypeConfig>> {
-        let x = self.get_current_snapshot_()?;
-        Ok(x.map(|s| Snapshot {
-            meta: s.meta.clone(),
-            snapshot: Box::new(Cursor::new(s.data.clone())),
-        }))
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct LogStore {
-    db: Arc<DB>,
-}
-type StorageResult<T> = Result<T, StorageError<TypeConfig>>;
-
-/// converts an id to a byte vector for storing in the database.
-/// Note that we're using big endian encoding to ensure correct sorting of keys
-fn id_to_bin(id: u64) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(8);
-    buf.write_u64::<BigEndian>(id).unwrap();
-    buf
-}
-
-fn bin_to_id(buf: &[u8]) -> u64 {
-    (&buf[0..8]).read_u64::<BigEndian>().unwrap()
-}
-
-impl LogStore {
-    fn store(&self) -> &ColumnFamily {
-        self.db.cf_handle("store").unwrap()
-    }
-
-    fn logs(&self) -> &ColumnFamily {
-        self.db.cf_handle("logs").unwrap()
-    }
-
-    fn flush(&self, subject: ErrorSubject<TypeConfig>, verb: ErrorVerb) -> Result<(), StorageError<TypeConfig>> {
-        self.db.flush_wal(true).map_err(|e| StorageError::new(subject, verb, AnyError::new(&e)))?;
-        Ok(())
-    }
-
-    fn get_last_purged_(&self) -> StorageResult<Option<LogId<u64>>> {
-        Ok(self
-            .db
-            .get_cf(self.store(), b"last_purged_log_id")
-            .map_err(|e| StorageError::read(&e))?
-            .and_then(|v| serde_json::from_slice(&v).ok()))
-    }
-
-    fn set_last_purged_(&self, log_id: LogId<u64>) -> StorageResult<()> {
-        self.db
-            .put_cf(
-                self.store(),
-                b"last_purged_log_id",
-                serde_json::to_vec(&log_id).unwrap().as_slice(),
-            )
-            .map_err(|e| StorageError::write(&e))?;
-
-        self.flush(ErrorSubject::Store, ErrorVerb::Write)?;
-        Ok(())
-    }
-
-    fn set_committed_(&self, committed: &Option<LogId<NodeId>>) -> Result<(), StorageError<TypeConfig>> {
-        let json = serde_json::to_vec(committed).unwrap();
-
-        self.db.put_cf(self.store(), b"committed", json).map_err(|e| StorageError::write(&e))?;
-
-        self.flush(ErrorSubject::Store, ErrorVerb::Write)?;
-        Ok(())
-    }
-
-    fn get_committed_(&self) -> StorageResult<Option<LogId<NodeId>>> {
-        Ok(self
-            .db
-            .get_cf(self.store(), b"committed")
-            .map_err(|e| StorageError::read(&e))?
-            .and_then(|v| serde_json::from_slice(&v).ok()))
-    }
-
-    fn set_vote_(&self, vote: &Vote<NodeId>) -> StorageResult<()> {
-        self.db
-            .put_cf(self.store(), b"vote", serde_json::to_vec(vote).unwrap())
-            .map_err(|e| StorageError::write_vote(&e))?;
-
-        self.flush(ErrorSubject::Vote, ErrorVerb::Write)?;
-        Ok(())
-    }
-
-    fn get_vote_(&self) -> StorageResult<Option<Vote<NodeId>>> {
-        Ok(self
-            .db
-            .get_cf(self.store(), b"vote")
-            .map_err(|e| StorageError::write_vote(&e))?
-            .and_then(|v| serde_json::from_slice(&v).ok()))
-    }
-}
-
-impl RaftLogReader<TypeConfig> for LogStore {
-    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
-        &mut self,
-        range: RB,
-    ) -> StorageResult<Vec<Entry<TypeConfig>>> {
-        let start = match range.start_bound() {
-            std::ops::Bound::Included(x) => id_to_bin(*x),
-            std::ops::Bound::Excluded(x) => id_to_bin(*x + 1),
-            std::ops::Bound::Unbounded => id_to_bin(0),
-        };
-        self.db
-            .iterator_cf(self.logs(), rocksdb::IteratorMode::From(&start, Direction::Forward))
-            .map(|res| {
-                let (id, val) = res.unwrap();
-                let entry: StorageResult<Entry<_>> =
-                    serde_json::from_slice(&val).map_err(|e| StorageError::read_logs(&e));
-                let id = bin_to_id(&id);
-
-                assert_eq!(Ok(id), entry.as_ref().map(|e| e.log_id.index));
-                (id, entry)
-            })
-            .take_while(|(id, _)| range.contains(id))
-            .map(|x| x.1)
-            .collect()
-    }
-
-    async fn read_vote(&mut self) -> Result<Option<Vote<NodeId>>, StorageError<TypeConfig>> {
-        self.get_vote_()
-    }
-}
-
-impl RaftLogStorage<TypeConfig> for LogStore {
-    type LogReader = Self;
-
-    async fn get_log_state(&mut self) -> StorageResult<LogState<TypeConfig>> {
-        let last = self.db.iterator_cf(self.logs(), rocksdb::IteratorMode::End).next().and_then(|res| {
-            let (_, ent) = res.unwrap();
-            Some(serde_json::from_slice::<Entry<TypeConfig>>(&ent).ok()?.log_id)
-        });
-
-        let last_purged_log_id = self.get_last_purged_()?;
-
-        let last_log_id = match last {
-            None => last_purged_log_id,
-            Some(x) => Some(x),
-        };
-        Ok(LogState {
-            last_purged_log_id,
-            last_log_id,
-        })
-    }
-
-    async fn save_committed(&mut self, _committed: Option<LogId<NodeId>>) -> Result<(), StorageError<TypeConfig>> {
-        self.set_committed_(&_committed)?;
-        Ok(())
-    }
-
-    async fn read_committed(&mut self) -> Result<Option<LogId<NodeId>>, StorageError<TypeConfig>> {
-        let c = self.get_committed_()?;
-        Ok(c)
-    }
-
-    #[tracing::instrument(level = "trace", skip(self))]
-    async fn save_vote(&mut self, vote: &Vote<NodeId>) -> Result<(), StorageError<TypeConfig>> {
-        self.set_vote_(vote)
-    }
-
-    #[tracing::instrument(level = "trace", skip_all)]
-    async fn append<I>(&mut self, entries: I, callback: IOFlushed<TypeConfig>) -> StorageResult<()>
-    where
-        I: IntoIterator<Item = Entry<TypeConfig>> + Send,
-        I::IntoIter: Send,
-    {
-        for entry in entries {
-            let id = id_to_bin(entry.log_id.index);
-            assert_eq!(bin_to_id(&id), entry.log_id.index);
-            self.db
-                .put_cf(
-                    self.logs(),
-                    id,
-                    serde_json::to_vec(&entry).map_err(|e| StorageError::write_logs(&e))?,
-                )
-                .map_err(|e| StorageError::write_logs(&e))?;
-        }
-
-        callback.io_completed(Ok(()));
-
-        Ok(())
-    }
-
-    #[tracing::instrument(level = "debug", skip(self))]
-    async fn truncate(&mut self, log_id: LogId<NodeId>) -> StorageResult<()> {
-        tracing::debug!("delete_log: [{:?}, +oo)", log_id);
-
-        let from = id_to_bin(log_id.index);
-        let to = id_to_bin(0xff_ff_ff_ff_ff_ff_ff_ff);
-        self.db.delete_range_cf(self.logs(), &from, &to).map_err(|e| StorageError::write_logs(&e))
-    }
-
-    #[tracing::instrument(level = "debug", skip(self))]
-    async fn purge(&mut self, log_id: LogId<NodeId>) -> Result<(), StorageError<TypeConfig>> {
-        tracing::debug!("delete_log: [0, {:?}]", log_id);
-
-        self.set_last_purged_(log_id)?;
-        let from = id_to_bin(0);
-        let to = id_to_bin(log_id.index + 1);
-        self.db.delete_range_cf(self.logs(), &from, &to).map_err(|e| StorageError::write_logs(&e))
-    }
-
-    async fn get_log_reader(&mut self) -> Self::LogReader {
-        self.clone()
-    }
-}
-
-pub(crate) async fn new_storage<P: AsRef<Path>>(db_path: P, atinyvectors_command: Arc<ATinyVectorsRaftCommand>) -> (LogStore, StateMachineStore) {
-    let mut db_opts = Options::default();
-    db_opts.create_missing_column_families(true);
-    db_opts.create_if_missing(true);
-
-    let store = ColumnFamilyDescriptor::new("store", Options::default());
-    let logs = ColumnFamilyDescriptor::new("logs", Options::default());
-
-    let db = DB::open_cf_descriptors(&db_opts, db_path, vec![store, logs]).unwrap();
-    let db = Arc::new(db);
-
-    let log_store = LogStore { db: db.clone() };
-    let sm_store = StateMachineStore::new(db, atinyvectors_command.clone()).await.unwrap();
-
-    (log_store, sm_store)
-}
\ No newline at end of file
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::ops::RangeBounds;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::time::Duration;
+
+use byteorder::BigEndian;
+use byteorder::ReadBytesExt;
+use byteorder::WriteBytesExt;
+use openraft::storage::IOFlushed;
+use openraft::storage::LogState;
+use openraft::storage::RaftLogStorage;
+use openraft::storage::RaftStateMachine;
+use openraft::storage::Snapshot;
+use openraft::AnyError;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::ErrorSubject;
+use openraft::ErrorVerb;
+use openraft::LogId;
+use openraft::OptionalSend;
+use openraft::RaftLogReader;
+use openraft::RaftSnapshotBuilder;
+use openraft::SnapshotMeta;
+use openraft::StorageError;
+use openraft::StoredMembership;
+use openraft::Vote;
+use rocksdb::ColumnFamily;
+use rocksdb::ColumnFamilyDescriptor;
+use rocksdb::Direction;
+use rocksdb::Options;
+use rocksdb::WriteBatch;
+use rocksdb::WriteOptions;
+use rocksdb::DB;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::RwLock;
+
+use crate::config::Config;
+use crate::raft_cluster::log_codec;
+use crate::raft_cluster::log_codec::LogFormat;
+use crate::raft_cluster::shard_journal;
+use crate::raft_cluster::snapshot_archive;
+use crate::raft_cluster::typ;
+use crate::raft_cluster::NodeId;
+use crate::raft_cluster::SnapshotData;
+use crate::raft_cluster::TypeConfig;
+
+use crate::atinyvectors::atinyvectors_raft_command::ATinyVectorsRaftCommand;
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+use crate::atinyvectors::atinyvectors_raft_command::CommandError;
+use crate::atinyvectors::atinyvectors_raft_command::CommandOutput;
+use crate::atinyvectors::atinyvectors_raft_command::CommandResult;
+use crate::atinyvectors::atinyvectors_raft_command::COMMAND_SCHEMA_VERSION;
+
+/**
+ * Here you will set the types of request that will interact with the raft nodes.
+ * Every write is a typed `Command` tagged with the schema version it was encoded with.
+ * `Set` is the legacy stringly-typed form; it is only kept so that logs written by older
+ * versions can still be decoded and applied.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Request {
+    Set { key: String, value: String },
+    Command { schema_version: u32, command: Command },
+}
+
+impl Request {
+    /// Wraps a validated command for proposal to the cluster, its vector data rounded to f32.
+    pub fn command(mut command: Command) -> Result<Self, String> {
+        command.validate()?;
+        command.round_vector_data();
+        Ok(Request::Command {
+            schema_version: COMMAND_SCHEMA_VERSION,
+            command,
+        })
+    }
+
+    /// Decodes the command carried by a log entry.
+    pub fn into_command(self) -> Result<Command, String> {
+        match self {
+            Request::Set { value, .. } => Command::from_legacy_json(&value),
+            Request::Command { schema_version, command } => {
+                if schema_version > COMMAND_SCHEMA_VERSION {
+                    return Err(format!(
+                        "Unsupported command schema version {} (max supported: {})",
+                        schema_version, COMMAND_SCHEMA_VERSION
+                    ));
+                }
+                Ok(command)
+            }
+        }
+    }
+}
+
+/**
+ * Here you will defined what type of answer you expect from applying a request.
+ * `result` carries what the state machine actually did on the leader, so handlers can answer
+ * with the real outcome instead of assuming success once the entry is committed.
+ */
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Response {
+    /// Name of the applied command, `None` for blank and membership entries.
+    pub value: Option<String>,
+    pub result: CommandResult,
+}
+
+/// The current snapshot as recorded in the `store` column family.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StoredSnapshot {
+    pub meta: SnapshotMeta<TypeConfig>,
+
+    /// Name of the file, in the snapshot directory, holding the data of the state machine at the
+    /// time of this snapshot. It is laid out as described in [`snapshot_archive`].
+    pub file: String,
+}
+
+/// Snapshot record of older versions, which kept the data inline in RocksDB.
+#[derive(Deserialize)]
+struct InlineSnapshot {
+    meta: SnapshotMeta<TypeConfig>,
+    data: Vec<u8>,
+}
+
+/// How far the state machine got, persisted after every applied entry.
+///
+/// Without it a restart would fall back to the last snapshot and re-apply every later entry,
+/// although atinyvectors already persisted their effects.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AppliedState {
+    last_applied_log_id: Option<LogId<NodeId>>,
+    last_membership: StoredMembership<TypeConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StateMachineStore {
+    pub data: StateMachineData,
+    pub atinyvectors_command: Arc<ATinyVectorsRaftCommand>,
+
+    /// snapshot index is not persisted in this example.
+    ///
+    /// It is only used as a suffix of snapshot id, and should be globally unique.
+    /// In practice, using a timestamp in micro-second would be good enough.
+    snapshot_idx: u64,
+
+    /// State captured by `get_snapshot_builder`, consumed by `build_snapshot`.
+    captured_state: Option<StorageResult<PathBuf>>,
+
+    /// Directory holding snapshot files, both complete and in progress.
+    snapshot_dir: PathBuf,
+
+    /// File handed out by `begin_receiving_snapshot` for the snapshot being streamed in.
+    receiving: Option<PathBuf>,
+
+    /// Entries up to this index were committed before the last restart and may have been
+    /// applied already, they go through `ATinyVectorsRaftCommand::replay_command`.
+    replay_until: Option<u64>,
+
+    /// Other shard groups of this node apply to the same engine, their writes may already be
+    /// there. Commands go through `ATinyVectorsRaftCommand::process_shared_command`, and
+    /// snapshots carry the group's [`shard_journal`] instead of the engine.
+    shares_engine: bool,
+
+    /// Whether the journal of a group sharing the engine covers everything it applied.
+    journal_complete: bool,
+
+    /// State machine stores snapshot metadata in db.
+    db: Arc<DB>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StateMachineData {
+    pub last_applied_log_id: Option<LogId<NodeId>>,
+
+    pub last_membership: StoredMembership<TypeConfig>,
+
+    /// State built from applying the raft logs
+    pub kvs: Arc<RwLock<BTreeMap<String, String>>>,
+}
+
+impl RaftSnapshotBuilder<TypeConfig> for StateMachineStore {
+    async fn build_snapshot(&mut self) -> Result<Snapshot<TypeConfig>, StorageError<TypeConfig>> {
+        let last_applied_log = self.data.last_applied_log_id;
+        let last_membership = self.data.last_membership.clone();
+
+        let captured = match self.captured_state.take() {
+            Some(captured) => captured?,
+            None => self.capture_state_().await?,
+        };
+
+        let snapshot_id = if let Some(last) = last_applied_log {
+            format!("{}-{}-{}", last.leader_id, last.index, self.snapshot_idx)
+        } else {
+            format!("--{}", self.snapshot_idx)
+        };
+
+        let meta = SnapshotMeta {
+            last_log_id: last_applied_log,
+            last_membership,
+            snapshot_id,
+        };
+
+        let file = snapshot_file_name(&meta.snapshot_id);
+        std::fs::rename(&captured, self.snapshot_dir.join(&file))
+            .map_err(|e| StorageError::write_snapshot(Some(meta.signature()), &e))?;
+
+        let snapshot = StoredSnapshot {
+            meta: meta.clone(),
+            file,
+        };
+
+        self.set_current_snapshot_(&snapshot)?;
+
+        Ok(Snapshot {
+            meta,
+            snapshot: Box::new(self.open_snapshot_(&snapshot).await?),
+        })
+    }
+}
+
+/// Snapshot ids embed the leader id, keep only characters that are safe in a file name.
+fn snapshot_file_name(snapshot_id: &str) -> String {
+    let id: String = snapshot_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}.snapshot", id)
+}
+
+impl StateMachineStore {
+    async fn new(
+        db: Arc<DB>,
+        snapshot_dir: PathBuf,
+        atinyvectors_command: Arc<ATinyVectorsRaftCommand>,
+        shares_engine: bool,
+    ) -> Result<StateMachineStore, StorageError<TypeConfig>> {
+        std::fs::create_dir_all(&snapshot_dir).map_err(|e| StorageError::write_snapshot(None, &e))?;
+
+        let mut sm = Self {
+            data: StateMachineData {
+                last_applied_log_id: None,
+                last_membership: Default::default(),
+                kvs: Arc::new(Default::default()),
+            },
+            atinyvectors_command,
+            snapshot_idx: 0,
+            captured_state: None,
+            snapshot_dir,
+            receiving: None,
+            replay_until: None,
+            shares_engine,
+            journal_complete: false,
+            db,
+        };
+
+        // The engine and key-value storages persist on their own, only the metadata and the kv
+        // map have to be reloaded. Restoring their files here would roll back applied writes.
+        let snapshot = sm.get_current_snapshot_()?;
+        sm.remove_stale_snapshot_files_(snapshot.as_ref().map(|s| s.file.as_str()));
+        if let Some(snap) = snapshot {
+            sm.update_state_machine_(&snap, false).await?;
+        }
+
+        if let Some(applied) = sm.get_applied_state_()? {
+            if applied.last_applied_log_id > sm.data.last_applied_log_id {
+                sm.data.last_applied_log_id = applied.last_applied_log_id;
+                sm.data.last_membership = applied.last_membership;
+            }
+        }
+
+        // The applied index is written after the command ran, and without fsync, so what follows
+        // it up to the commit index may have reached atinyvectors already.
+        let committed = sm.get_committed_()?;
+        if committed > sm.data.last_applied_log_id {
+            sm.replay_until = committed.map(|log_id| log_id.index);
+        }
+        tracing::info!(
+            "State machine recovered: last_applied={:?} replay_until={:?}",
+            sm.data.last_applied_log_id,
+            sm.replay_until
+        );
+
+        if sm.shares_engine {
+            sm.journal_complete = sm.backfill_journal_()?;
+        }
+
+        Ok(sm)
+    }
+
+    /// Whether this group cannot take snapshots: it shares the engine and its log was purged
+    /// before it kept a journal, so the journal misses what the purged entries wrote.
+    pub fn keeps_whole_log(&self) -> bool {
+        self.shares_engine && !self.journal_complete
+    }
+
+    /// Fills the journal of a group sharing the engine from its log, the first time it starts
+    /// with one. Returns whether the journal covers everything applied.
+    fn backfill_journal_(&self) -> StorageResult<bool> {
+        let read_err = |e: rocksdb::Error| StorageError::read_state_machine(&e);
+        if self.db.get_cf(self.store(), shard_journal::COMPLETE_KEY).map_err(read_err)?.is_some() {
+            return Ok(true);
+        }
+        if self.db.get_cf(self.store(), b"last_purged_log_id").map_err(read_err)?.is_some() {
+            tracing::warn!("The log of this shard group was purged before it kept a journal, it keeps its whole log");
+            return Ok(false);
+        }
+
+        let applied = self.data.last_applied_log_id.map(|log_id| log_id.index).unwrap_or(0);
+        let logs = self.db.cf_handle("logs").unwrap();
+        for res in self.db.iterator_cf(logs, rocksdb::IteratorMode::Start) {
+            let (_, val) = res.map_err(|e| StorageError::read_logs(&e))?;
+            let entry = log_codec::decode_entry(&val).map_err(|e| StorageError::read_logs(&std::io::Error::other(e)))?;
+            if entry.log_id.index > applied {
+                break;
+            }
+            if let EntryPayload::Normal(req) = entry.payload {
+                if let Ok(command) = req.into_command() {
+                    self.journal_(entry.log_id.index, &command)?;
+                }
+            }
+        }
+
+        self.set_journal_complete_()?;
+        tracing::info!("Journal of the shard group filled from its log up to index {}", applied);
+        Ok(true)
+    }
+
+    fn journal_(&self, index: u64, command: &Command) -> StorageResult<()> {
+        let ops = shard_journal::journal_ops(index, command, |space_name| {
+            self.atinyvectors_command.default_version_id(space_name)
+        });
+        shard_journal::apply(&self.db, ops).map_err(|e| StorageError::write_state_machine(&e))
+    }
+
+    fn set_journal_complete_(&self) -> StorageResult<()> {
+        self.db
+            .put_cf(self.store(), shard_journal::COMPLETE_KEY, b"1")
+            .map_err(|e| StorageError::write_state_machine(&e))
+    }
+
+    /// Replaces the journal with the one of a snapshot and replays it into the engine. Tokens
+    /// and keys the snapshot no longer holds are removed; spaces and versions are removed by
+    /// the deletes the journal replays.
+    async fn install_journal_<R: std::io::BufRead>(&mut self, data: &mut R, snapshot_id: &str) -> std::io::Result<()> {
+        let removable = shard_journal::removable_keys(&self.db);
+        shard_journal::clear(&self.db).map_err(std::io::Error::other)?;
+
+        let mut kept = BTreeSet::new();
+        while let Some(record) = shard_journal::next_record(data)? {
+            shard_journal::put(&self.db, &record).map_err(std::io::Error::other)?;
+            if let Err(e) = self.atinyvectors_command.process_shared_command(record.command).await {
+                tracing::warn!("Failed to replay {} of snapshot {}: {}", record.key, snapshot_id, e);
+            }
+            kept.insert(record.key);
+        }
+
+        for key in removable.difference(&kept) {
+            if let Some(command) = shard_journal::removal(key) {
+                if let Err(e) = self.atinyvectors_command.process_shared_command(command).await {
+                    tracing::warn!("Failed to remove {} missing from snapshot {}: {}", key, snapshot_id, e);
+                }
+            }
+        }
+
+        self.set_journal_complete_().map_err(std::io::Error::other)?;
+        self.journal_complete = true;
+        Ok(())
+    }
+
+    fn get_applied_state_(&self) -> StorageResult<Option<AppliedState>> {
+        Ok(self
+            .db
+            .get_cf(self.store(), b"applied_state")
+            .map_err(|e| StorageError::read_state_machine(&e))?
+            .and_then(|v| serde_json::from_slice(&v).ok()))
+    }
+
+    /// Records the applied index; made durable by the next `flush`.
+    fn set_applied_state_(&self) -> StorageResult<()> {
+        let applied = AppliedState {
+            last_applied_log_id: self.data.last_applied_log_id,
+            last_membership: self.data.last_membership.clone(),
+        };
+        self.db
+            .put_cf(self.store(), b"applied_state", serde_json::to_vec(&applied).unwrap())
+            .map_err(|e| StorageError::write_state_machine(&e))
+    }
+
+    /// Index of the last `create_snapshot` command that produced a user snapshot, so that a
+    /// replay after a restart does not take it a second time.
+    fn get_user_snapshot_index_(&self) -> StorageResult<Option<u64>> {
+        Ok(self
+            .db
+            .get_cf(self.store(), b"user_snapshot_index")
+            .map_err(|e| StorageError::read_state_machine(&e))?
+            .and_then(|v| serde_json::from_slice(&v).ok()))
+    }
+
+    /// Records the index of an applied `create_snapshot`; made durable by the next `flush`.
+    fn set_user_snapshot_index_(&self, index: u64) -> StorageResult<()> {
+        self.db
+            .put_cf(self.store(), b"user_snapshot_index", serde_json::to_vec(&index).unwrap())
+            .map_err(|e| StorageError::write_state_machine(&e))
+    }
+
+    fn get_committed_(&self) -> StorageResult<Option<LogId<NodeId>>> {
+        Ok(self
+            .db
+            .get_cf(self.store(), b"committed")
+            .map_err(|e| StorageError::read(&e))?
+            .and_then(|v| serde_json::from_slice(&v).ok()))
+    }
+
+    /// Writes the kv map, the engine state and the per-space key-value storages to a new file
+    /// in the snapshot directory. A group sharing the engine writes its journal instead of the
+    /// engine state and the storages.
+    ///
+    /// Must be called between two `apply` calls so that all three reflect the same log index.
+    /// The file I/O runs on the blocking pool, so heartbeats and RPCs go on while it is written.
+    async fn capture_state_(&self) -> StorageResult<PathBuf> {
+        let kv_json = {
+            let kvs = self.data.kvs.read().await;
+            serde_json::to_vec(&*kvs).map_err(|e| StorageError::read_state_machine(&e))?
+        };
+
+        let path = self.snapshot_dir.join(format!("building-{}.tmp", self.snapshot_idx));
+        let data_path = PathBuf::from(Config::data_path());
+        let atinyvectors_command = self.atinyvectors_command.clone();
+        let journal_db = self.shares_engine.then(|| self.db.clone());
+
+        let building = path.clone();
+        let write = move || -> std::io::Result<()> {
+            let mut w = BufWriter::new(std::fs::File::create(&building)?);
+            snapshot_archive::write_header(&mut w)?;
+            snapshot_archive::write_entry(&mut w, snapshot_archive::KVS_ENTRY, &kv_json)?;
+            if let Some(db) = journal_db {
+                // the entry header needs the length, so the journal goes through a file
+                let journal = building.with_extension("journal");
+                let exported = std::fs::File::create(&journal).and_then(|file| {
+                    let mut jw = BufWriter::new(file);
+                    shard_journal::export(&db, &mut jw)?;
+                    jw.into_inner().map_err(|e| e.into_error())?.sync_all()
+                });
+                let written = exported.and_then(|_| snapshot_archive::write_file_entry(&mut w, shard_journal::ENTRY, &journal));
+                let _ = std::fs::remove_file(&journal);
+                written?;
+                return w.into_inner().map_err(|e| e.into_error())?.sync_all();
+            }
+            atinyvectors_command
+                .export_engine_state(|file_name, file| {
+                    let entry = format!("{}{}", snapshot_archive::ENGINE_PREFIX, file_name);
+                    snapshot_archive::write_file_entry(&mut w, &entry, file)
+                })
+                .map_err(std::io::Error::other)?;
+            snapshot_archive::write_kv_storages(&mut w, &data_path)?;
+            w.into_inner().map_err(|e| e.into_error())?.sync_all()
+        };
+
+        let written = tokio::task::spawn_blocking(write)
+            .await
+            .map_err(std::io::Error::other)
+            .and_then(|written| written);
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&path);
+            return Err(StorageError::read_state_machine(&e));
+        }
+        Ok(path)
+    }
+
+    /// Loads a snapshot into the state machine. With `restore_data` the engine state and the
+    /// key-value storages it carries replace the local ones, otherwise only the kv map is read.
+    async fn update_state_machine_(
+        &mut self,
+        snapshot: &StoredSnapshot,
+        restore_data: bool,
+    ) -> Result<(), StorageError<TypeConfig>> {
+        let signature = snapshot.meta.signature();
+        let read_err = |e: std::io::Error| StorageError::read_snapshot(Some(signature.clone()), &e);
+        let write_err = |e: std::io::Error| StorageError::write_snapshot(Some(signature.clone()), &e);
+
+        let file = std::fs::File::open(self.snapshot_dir.join(&snapshot.file)).map_err(read_err)?;
+        let mut r = BufReader::new(file);
+
+        let mut head = Vec::with_capacity(snapshot_archive::MAGIC.len());
+        (&mut r).take(snapshot_archive::MAGIC.len() as u64).read_to_end(&mut head).map_err(read_err)?;
+        r.seek(SeekFrom::Start(0)).map_err(read_err)?;
+
+        let kvs: BTreeMap<String, String> = if snapshot_archive::is_archive(&head) {
+            snapshot_archive::read_header(&mut r).map_err(read_err)?;
+
+            let data_path = PathBuf::from(Config::data_path());
+            if restore_data && !self.shares_engine {
+                snapshot_archive::clear_kv_storages(&data_path).map_err(write_err)?;
+            }
+
+            let mut kvs = BTreeMap::new();
+            while let Some((path, len)) = snapshot_archive::next_entry(&mut r).map_err(read_err)? {
+                let mut data = (&mut r).take(len);
+
+                if path == snapshot_archive::KVS_ENTRY {
+                    kvs = serde_json::from_reader(&mut data)
+                        .map_err(|e| StorageError::read_snapshot(Some(signature.clone()), &e))?;
+                } else if !restore_data {
+                    // only the kv map is needed
+                } else if self.shares_engine {
+                    if path == shard_journal::ENTRY {
+                        tracing::info!("Replaying the journal of snapshot {}", snapshot.meta.snapshot_id);
+                        self.install_journal_(&mut data, &snapshot.meta.snapshot_id).await.map_err(write_err)?;
+                    } else {
+                        tracing::warn!("Ignoring snapshot entry {}, this group shares the engine", path);
+                    }
+                } else if let Some(file_name) = path.strip_prefix(snapshot_archive::ENGINE_PREFIX) {
+                    tracing::info!("Restoring engine state from snapshot {}", snapshot.meta.snapshot_id);
+                    self.atinyvectors_command
+                        .import_engine_state(file_name, &mut data)
+                        .map_err(|e| write_err(std::io::Error::other(e)))?;
+                } else if path.starts_with(snapshot_archive::SPACE_PREFIX) {
+                    snapshot_archive::restore_file(&data_path, &path, &mut data).map_err(write_err)?;
+                } else {
+                    tracing::warn!("Ignoring unknown snapshot entry: {}", path);
+                }
+
+                // skip whatever the entry handler did not consume
+                std::io::copy(&mut data, &mut std::io::sink()).map_err(read_err)?;
+            }
+            kvs
+        } else {
+            // snapshots taken before engine data was included only hold the kv map
+            serde_json::from_reader(r).map_err(|e| StorageError::read_snapshot(Some(signature.clone()), &e))?
+        };
+
+        self.data.last_applied_log_id = snapshot.meta.last_log_id;
+        self.data.last_membership = snapshot.meta.last_membership.clone();
+        let mut x = self.data.kvs.write().await;
+        *x = kvs;
+
+        Ok(())
+    }
+
+    async fn open_snapshot_(&self, snapshot: &StoredSnapshot) -> StorageResult<SnapshotData> {
+        tokio::fs::File::open(self.snapshot_dir.join(&snapshot.file))
+            .await
+            .map_err(|e| StorageError::read_snapshot(Some(snapshot.meta.signature()), &e))
+    }
+
+    fn get_current_snapshot_(&self) -> StorageResult<Option<StoredSnapshot>> {
+        let value = match self.db.get_cf(self.store(), b"snapshot").map_err(|e| StorageError::read(&e))? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        if let Ok(snapshot) = serde_json::from_slice::<StoredSnapshot>(&value) {
+            return Ok(Some(snapshot));
+        }
+
+        // Move snapshots stored inline by older versions out to a file.
+        let inline: InlineSnapshot = match serde_json::from_slice(&value) {
+            Ok(inline) => inline,
+            Err(_) => return Ok(None),
+        };
+        let snapshot = StoredSnapshot {
+            file: snapshot_file_name(&inline.meta.snapshot_id),
+            meta: inline.meta,
+        };
+        std::fs::write(self.snapshot_dir.join(&snapshot.file), &inline.data)
+            .map_err(|e| StorageError::write_snapshot(Some(snapshot.meta.signature()), &e))?;
+        self.set_current_snapshot_(&snapshot)?;
+
+        Ok(Some(snapshot))
+    }
+
+    fn set_current_snapshot_(&self, snap: &StoredSnapshot) -> StorageResult<()> {
+        self.db
+            .put_cf(self.store(), b"snapshot", serde_json::to_vec(snap).unwrap().as_slice())
+            .map_err(|e| StorageError::write_snapshot(Some(snap.meta.signature()), &e))?;
+        self.flush(ErrorSubject::Snapshot(Some(snap.meta.signature())), ErrorVerb::Write)?;
+
+        // Readers of a replaced snapshot keep their open file until they are done with it.
+        if let Ok(entries) = std::fs::read_dir(&self.snapshot_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name.ends_with(".snapshot") && name != snap.file.as_str() {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes snapshot files left behind by an interrupted build or transfer.
+    fn remove_stale_snapshot_files_(&self, current: Option<&str>) {
+        if let Ok(entries) = std::fs::read_dir(&self.snapshot_dir) {
+            for entry in entries.flatten() {
+                if Some(entry.file_name().to_string_lossy().as_ref()) != current {
+                    tracing::debug!("Removing stale snapshot file {:?}", entry.path());
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
+
+    fn flush(&self, subject: ErrorSubject<TypeConfig>, verb: ErrorVerb) -> Result<(), StorageError<TypeConfig>> {
+        self.db.flush_wal(true).map_err(|e| StorageError::new(subject, verb, AnyError::new(&e)))?;
+        Ok(())
+    }
+
+    fn store(&self) -> &ColumnFamily {
+        self.db.cf_handle("store").unwrap()
+    }
+}
+
+impl RaftStateMachine<TypeConfig> for StateMachineStore {
+    type SnapshotBuilder = Self;
+
+    async fn applied_state(
+        &mut self,
+    ) -> Result<(Option<LogId<NodeId>>, StoredMembership<TypeConfig>), StorageError<TypeConfig>> {
+        Ok((self.data.last_applied_log_id, self.data.last_membership.clone()))
+    }
+
+    async fn apply<I>(&mut self, entries: I) -> Result<Vec<Response>, StorageError<TypeConfig>>
+    where
+        I: IntoIterator<Item = typ::Entry> + OptionalSend,
+        I::IntoIter: OptionalSend,
+    {
+        let entries = entries.into_iter();
+        let mut replies = Vec::with_capacity(entries.size_hint().0);
+
+        for ent in entries {
+            self.data.last_applied_log_id = Some(ent.log_id);
+            let mut resp_value = None;
+            let mut result = Ok(CommandOutput::default());
+
+            match ent.payload {
+                EntryPayload::Blank => {}
+                EntryPayload::Normal(req) => {
+                    match req.into_command() {
+                        Ok(command) => {
+                            tracing::info!("apply : log_id={} command={}", ent.log_id, command.name());
+                            resp_value = Some(command.name().to_string());
+                            let replay = self.replay_until.map(|until| ent.log_id.index <= until).unwrap_or(false);
+                            let creates_snapshot = matches!(command, Command::CreateSnapshot { .. });
+                            let journaled = self.shares_engine.then(|| command.clone());
+                            result = if replay && creates_snapshot && Some(ent.log_id.index) <= self.get_user_snapshot_index_()? {
+                                tracing::info!("Skipping replayed create_snapshot at log_id={}, already taken", ent.log_id);
+                                Ok(CommandOutput::default())
+                            } else if self.shares_engine {
+                                self.atinyvectors_command.process_shared_command(command).await
+                            } else if replay {
+                                self.atinyvectors_command.replay_command(command).await
+                            } else {
+                                self.atinyvectors_command.process_command(command).await
+                            };
+                            if creates_snapshot && result.is_ok() {
+                                self.set_user_snapshot_index_(ent.log_id.index)?;
+                            }
+                            if let (Some(command), Ok(_)) = (journaled, &result) {
+                                self.journal_(ent.log_id.index, &command)?;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to decode command at log_id={}: {}", ent.log_id, e);
+                            result = Err(CommandError::invalid_argument(e));
+                        }
+                    }
+                }
+                EntryPayload::Membership(mem) => {
+                    self.data.last_membership = StoredMembership::new(Some(ent.log_id), mem);
+                }
+            }
+
+            self.set_applied_state_()?;
+            replies.push(Response { value: resp_value, result });
+        }
+
+        if let Some(until) = self.replay_until {
+            if self.data.last_applied_log_id.map(|log_id| log_id.index >= until).unwrap_or(false) {
+                tracing::info!("Replay after restart finished at index {}", until);
+                self.replay_until = None;
+            }
+        }
+
+        self.flush(ErrorSubject::StateMachine, ErrorVerb::Write)?;
+        Ok(replies)
+    }
+    
+    async fn get_snapshot_builder(&mut self) -> Self::SnapshotBuilder {
+        self.snapshot_idx += 1;
+
+        // `build_snapshot` runs concurrently with `apply`, so the state is captured here, while
+        // no entry is being applied. Applying waits for the capture, the runtime does not.
+        let mut builder = self.clone();
+        builder.captured_state = Some(self.capture_state_().await);
+        builder
+    }
+
+    async fn begin_receiving_snapshot(&mut self) -> Result<Box<SnapshotData>, StorageError<TypeConfig>> {
+        // Called for the chunk at offset 0 only; later chunks, resent ones included, are written
+        // at their offset into the file returned here. A transfer starting at offset 0 again
+        // resends the whole snapshot, so the file of one that was abandoned is of no use.
+        if let Some(previous) = self.receiving.take() {
+            let _ = std::fs::remove_file(previous);
+        }
+
+        self.snapshot_idx += 1;
+        let path = self.snapshot_dir.join(format!("receiving-{}.tmp", self.snapshot_idx));
+        let file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .await
+            .map_err(|e| StorageError::write_snapshot(None, &e))?;
+
+        self.receiving = Some(path);
+        Ok(Box::new(file))
+    }
+
+    async fn install_snapshot(
+        &mut self,
+        meta: &SnapshotMeta<TypeConfig>,
+        mut snapshot: Box<SnapshotData>,
+    ) -> Result<(), StorageError<TypeConfig>> {
+        let write_err = |e: std::io::Error| StorageError::write_snapshot(Some(meta.signature()), &e);
+
+        snapshot.flush().await.map_err(write_err)?;
+        snapshot.sync_all().await.map_err(write_err)?;
+        drop(snapshot);
+
+        let received = self
+            .receiving
+            .take()
+            .ok_or_else(|| write_err(std::io::Error::other("no snapshot is being received")))?;
+        let new_snapshot = StoredSnapshot {
+            meta: meta.clone(),
+            file: snapshot_file_name(&meta.snapshot_id),
+        };
+        std::fs::rename(received, self.snapshot_dir.join(&new_snapshot.file)).map_err(write_err)?;
+
+        self.update_state_machine_(&new_snapshot, true).await?;
+        self.set_applied_state_()?;
+
+        self.set_current_snapshot_(&new_snapshot)?;
+
+        Ok(())
+    }
+
+    async fn get_current_snapshot(&mut self) -> Result<Option<Snapshot<TypeConfig>>, StorageError<TypeConfig>> {
+        match self.get_current_snapshot_()? {
+            Some(s) => Ok(Some(Snapshot {
+                meta: s.meta.clone(),
+                snapshot: Box::new(self.open_snapshot_(&s).await?),
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+/// When appended log entries are made durable, and so when `append` reports them flushed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogSyncMode {
+    /// Every append is fsynced before it is reported.
+    Batch,
+    /// Appends are reported once a background fsync, run every interval, covers them.
+    Periodic(Duration),
+    /// Appends are reported once written to the WAL. They survive a process crash but not an OS
+    /// crash or power loss.
+    None,
+}
+
+impl LogSyncMode {
+    pub fn from_config() -> LogSyncMode {
+        match Config::raft_log_sync().as_str() {
+            "batch" => LogSyncMode::Batch,
+            "periodic" => LogSyncMode::Periodic(Duration::from_millis(Config::raft_log_sync_interval().max(1))),
+            "none" => LogSyncMode::None,
+            other => {
+                tracing::warn!("Unknown raft log sync mode {}, using batch", other);
+                LogSyncMode::Batch
+            }
+        }
+    }
+}
+
+/// Appends waiting for the next periodic fsync.
+#[derive(Default)]
+struct PendingFlushes(Mutex<Vec<IOFlushed<TypeConfig>>>);
+
+impl Debug for PendingFlushes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PendingFlushes({})", self.0.lock().map(|p| p.len()).unwrap_or(0))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogStore {
+    db: Arc<DB>,
+
+    /// Encoding of appended entries; entries of either format are always readable.
+    format: LogFormat,
+
+    sync_mode: LogSyncMode,
+    pending_flushes: Arc<PendingFlushes>,
+}
+type StorageResult<T> = Result<T, StorageError<TypeConfig>>;
+
+/// converts an id to a byte vector for storing in the database.
+/// Note that we're using big endian encoding to ensure correct sorting of keys
+fn id_to_bin(id: u64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(8);
+    buf.write_u64::<BigEndian>(id).unwrap();
+    buf
+}
+
+fn bin_to_id(buf: &[u8]) -> u64 {
+    (&buf[0..8]).read_u64::<BigEndian>().unwrap()
+}
+
+impl LogStore {
+    fn store(&self) -> &ColumnFamily {
+        self.db.cf_handle("store").unwrap()
+    }
+
+    fn logs(&self) -> &ColumnFamily {
+        self.db.cf_handle("logs").unwrap()
+    }
+
+    fn flush(&self, subject: ErrorSubject<TypeConfig>, verb: ErrorVerb) -> Result<(), StorageError<TypeConfig>> {
+        self.db.flush_wal(true).map_err(|e| StorageError::new(subject, verb, AnyError::new(&e)))?;
+        Ok(())
+    }
+
+    fn get_last_purged_(&self) -> StorageResult<Option<LogId<u64>>> {
+        Ok(self
+            .db
+            .get_cf(self.store(), b"last_purged_log_id")
+            .map_err(|e| StorageError::read(&e))?
+            .and_then(|v| serde_json::from_slice(&v).ok()))
+    }
+
+    fn set_last_purged_(&self, log_id: LogId<u64>) -> StorageResult<()> {
+        self.db
+            .put_cf(
+                self.store(),
+                b"last_purged_log_id",
+                serde_json::to_vec(&log_id).unwrap().as_slice(),
+            )
+            .map_err(|e| StorageError::write(&e))?;
+
+        self.flush(ErrorSubject::Store, ErrorVerb::Write)?;
+        Ok(())
+    }
+
+    fn set_committed_(&self, committed: &Option<LogId<NodeId>>) -> Result<(), StorageError<TypeConfig>> {
+        let json = serde_json::to_vec(committed).unwrap();
+
+        self.db.put_cf(self.store(), b"committed", json).map_err(|e| StorageError::write(&e))?;
+
+        self.flush(ErrorSubject::Store, ErrorVerb::Write)?;
+        Ok(())
+    }
+
+    fn get_committed_(&self) -> StorageResult<Option<LogId<NodeId>>> {
+        Ok(self
+            .db
+            .get_cf(self.store(), b"committed")
+            .map_err(|e| StorageError::read(&e))?
+            .and_then(|v| serde_json::from_slice(&v).ok()))
+    }
+
+    fn set_vote_(&self, vote: &Vote<NodeId>) -> StorageResult<()> {
+        self.db
+            .put_cf(self.store(), b"vote", serde_json::to_vec(vote).unwrap())
+            .map_err(|e| StorageError::write_vote(&e))?;
+
+        self.flush(ErrorSubject::Vote, ErrorVerb::Write)?;
+        Ok(())
+    }
+
+    fn get_vote_(&self) -> StorageResult<Option<Vote<NodeId>>> {
+        Ok(self
+            .db
+            .get_cf(self.store(), b"vote")
+            .map_err(|e| StorageError::write_vote(&e))?
+            .and_then(|v| serde_json::from_slice(&v).ok()))
+    }
+}
+
+impl RaftLogReader<TypeConfig> for LogStore {
+    async fn try_get_log_entries<RB: RangeBounds<u64> + Clone + Debug + OptionalSend>(
+        &mut self,
+        range: RB,
+    ) -> StorageResult<Vec<Entry<TypeConfig>>> {
+        let start = match range.start_bound() {
+            std::ops::Bound::Included(x) => id_to_bin(*x),
+            std::ops::Bound::Excluded(x) => id_to_bin(*x + 1),
+            std::ops::Bound::Unbounded => id_to_bin(0),
+        };
+        self.db
+            .iterator_cf(self.logs(), rocksdb::IteratorMode::From(&start, Direction::Forward))
+            .map(|res| {
+                let (id, val) = res.unwrap();
+                let entry: StorageResult<Entry<_>> =
+                    log_codec::decode_entry(&val).map_err(|e| StorageError::read_logs(&std::io::Error::other(e)));
+                let id = bin_to_id(&id);
+
+                assert_eq!(Ok(id), entry.as_ref().map(|e| e.log_id.index));
+                (id, entry)
+            })
+            .take_while(|(id, _)| range.contains(id))
+            .map(|x| x.1)
+            .collect()
+    }
+
+    async fn read_vote(&mut self) -> Result<Option<Vote<NodeId>>, StorageError<TypeConfig>> {
+        self.get_vote_()
+    }
+}
+
+impl RaftLogStorage<TypeConfig> for LogStore {
+    type LogReader = Self;
+
+    async fn get_log_state(&mut self) -> StorageResult<LogState<TypeConfig>> {
+        let last = self.db.iterator_cf(self.logs(), rocksdb::IteratorMode::End).next().and_then(|res| {
+            let (_, ent) = res.unwrap();
+            Some(log_codec::decode_entry(&ent).ok()?.log_id)
+        });
+
+        let last_purged_log_id = self.get_last_purged_()?;
+
+        let last_log_id = match last {
+            None => last_purged_log_id,
+            Some(x) => Some(x),
+        };
+        Ok(LogState {
+            last_purged_log_id,
+            last_log_id,
+        })
+    }
+
+    async fn save_committed(&mut self, _committed: Option<LogId<NodeId>>) -> Result<(), StorageError<TypeConfig>> {
+        self.set_committed_(&_committed)?;
+        Ok(())
+    }
+
+    async fn read_committed(&mut self) -> Result<Option<LogId<NodeId>>, StorageError<TypeConfig>> {
+        let c = self.get_committed_()?;
+        Ok(c)
+    }
+
+    #[tracing::instrument(level = "trace", skip(self))]
+    async fn save_vote(&mut self, vote: &Vote<NodeId>) -> Result<(), StorageError<TypeConfig>> {
+        self.set_vote_(vote)
+    }
+
+    #[tracing::instrument(level = "trace", skip_all)]
+    async fn append<I>(&mut self, entries: I, callback: IOFlushed<TypeConfig>) -> StorageResult<()>
+    where
+        I: IntoIterator<Item = Entry<TypeConfig>> + Send,
+        I::IntoIter: Send,
+    {
+        // all entries of one call go to RocksDB as a single atomic write
+        let mut batch = WriteBatch::default();
+        for entry in entries {
+            let id = id_to_bin(entry.log_id.index);
+            assert_eq!(bin_to_id(&id), entry.log_id.index);
+            batch.put_cf(
+                self.logs(),
+                id,
+                log_codec::encode_entry(&entry, self.format)
+                    .map_err(|e| StorageError::write_logs(&std::io::Error::other(e)))?,
+            );
+        }
+
+        let mut write_opts = WriteOptions::default();
+        write_opts.set_sync(self.sync_mode == LogSyncMode::Batch);
+        self.db.write_opt(batch, &write_opts).map_err(|e| StorageError::write_logs(&e))?;
+
+        match self.sync_mode {
+            LogSyncMode::Periodic(_) => self.pending_flushes.0.lock().unwrap().push(callback),
+            LogSyncMode::Batch | LogSyncMode::None => callback.io_completed(Ok(())),
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn truncate(&mut self, log_id: LogId<NodeId>) -> StorageResult<()> {
+        tracing::debug!("delete_log: [{:?}, +oo)", log_id);
+
+        let from = id_to_bin(log_id.index);
+        let to = id_to_bin(0xff_ff_ff_ff_ff_ff_ff_ff);
+        self.db.delete_range_cf(self.logs(), &from, &to).map_err(|e| StorageError::write_logs(&e))
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn purge(&mut self, log_id: LogId<NodeId>) -> Result<(), StorageError<TypeConfig>> {
+        tracing::debug!("delete_log: [0, {:?}]", log_id);
+
+        self.set_last_purged_(log_id)?;
+        let from = id_to_bin(0);
+        let to = id_to_bin(log_id.index + 1);
+        self.db.delete_range_cf(self.logs(), &from, &to).map_err(|e| StorageError::write_logs(&e))
+    }
+
+    async fn get_log_reader(&mut self) -> Self::LogReader {
+        self.clone()
+    }
+}
+
+pub(crate) async fn new_storage<P: AsRef<Path>>(
+    db_path: P,
+    atinyvectors_command: Arc<ATinyVectorsRaftCommand>,
+    shares_engine: bool,
+) -> (LogStore, StateMachineStore) {
+    let mut db_opts = Options::default();
+    db_opts.create_missing_column_families(true);
+    db_opts.create_if_missing(true);
+
+    let store = ColumnFamilyDescriptor::new("store", Options::default());
+    let logs = ColumnFamilyDescriptor::new("logs", Options::default());
+    let journal = ColumnFamilyDescriptor::new(shard_journal::CF, Options::default());
+
+    // snapshot files live next to the column families, RocksDB ignores unknown sub directories
+    let snapshot_dir = db_path.as_ref().join("snapshots");
+
+    let db = DB::open_cf_descriptors(&db_opts, db_path, vec![store, logs, journal]).unwrap();
+    let db = Arc::new(db);
+
+    let format = LogFormat::from_config();
+
+    let sync_mode = LogSyncMode::from_config();
+    let pending_flushes = Arc::new(PendingFlushes::default());
+    if let LogSyncMode::Periodic(interval) = sync_mode {
+        spawn_log_flusher(Arc::downgrade(&db), pending_flushes.clone(), interval);
+    }
+
+    let log_store = LogStore {
+        db: db.clone(),
+        format,
+        sync_mode,
+        pending_flushes,
+    };
+    let sm_store = StateMachineStore::new(db, snapshot_dir, atinyvectors_command.clone(), shares_engine).await.unwrap();
+
+    (log_store, sm_store)
+}
+
+/// Fsyncs the WAL every `interval` and reports the appends it made durable. Stops once the
+/// database is dropped.
+fn spawn_log_flusher(db: Weak<DB>, pending: Arc<PendingFlushes>, interval: Duration) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(interval);
+
+        let db = match db.upgrade() {
+            Some(db) => db,
+            None => return,
+        };
+
+        // take the callbacks first: appends queued after this point may not be covered
+        let callbacks = std::mem::take(&mut *pending.0.lock().unwrap());
+        if callbacks.is_empty() {
+            continue;
+        }
+
+        let res = db.flush_wal(true);
+        if let Err(e) = &res {
+            tracing::error!("Failed to sync raft log: {}", e);
+        }
+        for callback in callbacks {
+            callback.io_completed(match &res {
+                Ok(()) => Ok(()),
+                Err(e) => Err(std::io::Error::other(e.to_string())),
+            });
+        }
+    });
+}
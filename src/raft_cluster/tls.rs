@@ -0,0 +1,224 @@
+//! Optional TLS for the API and between the nodes of a cluster.
+//!
+//! With `tls_cert` and `tls_key` set, a node serves its API and `/cluster/*` endpoints over HTTPS
+//! and its Raft RPCs over `wss://`, and calls other nodes with `https://` and `wss://`. Other
+//! nodes are trusted when their certificate is signed by `tls_ca`, or without a CA when they
+//! present the node's own certificate, which suits a cluster sharing one certificate. With
+//! `tls_ca` set, nodes also present their certificate when they call each other and the RPC port
+//! only accepts peers with a certificate signed by the CA.
+//!
+//! Certificates are checked against host names, so nodes must be addressed by names their
+//! certificates cover. All the nodes of a cluster have to switch at once: a node without TLS
+//! cannot reach one with it.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+
+use anyhow::Context as _;
+use async_std::net::TcpListener;
+use async_std::net::TcpStream;
+use futures::io::AsyncRead;
+use futures::io::AsyncWrite;
+use futures::StreamExt;
+use futures_rustls::server::TlsStream;
+use futures_rustls::TlsAcceptor;
+
+use crate::raft_cluster::Server;
+
+/// Whether this node serves and calls other nodes over TLS.
+pub fn enabled() -> bool {
+    !crate::Config::tls_cert().is_empty() || !crate::Config::tls_key().is_empty()
+}
+
+pub fn http_scheme() -> &'static str {
+    if enabled() {
+        "https"
+    } else {
+        "http"
+    }
+}
+
+pub fn ws_scheme() -> &'static str {
+    if enabled() {
+        "wss"
+    } else {
+        "ws"
+    }
+}
+
+/// Base URL of the API at `addr`, an address with or without a scheme.
+pub fn base_url(addr: &str) -> String {
+    if addr.starts_with("http://") || addr.starts_with("https://") {
+        addr.trim_end_matches('/').to_string()
+    } else {
+        format!("{}://{}", http_scheme(), addr)
+    }
+}
+
+/// Host part of a `host:port` address, the name its certificate has to cover.
+pub fn host_of(addr: &str) -> &str {
+    let addr = addr.split_once("://").map_or(addr, |(_, rest)| rest);
+    match addr.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host.trim_start_matches('[').trim_end_matches(']'),
+        _ => addr,
+    }
+}
+
+/// Loads the certificates and key once, so a misconfigured node fails on startup rather than on
+/// its first connection.
+pub fn check() -> anyhow::Result<()> {
+    if crate::Config::tls_cert().is_empty() || crate::Config::tls_key().is_empty() {
+        anyhow::bail!("tls_cert and tls_key have to be set together");
+    }
+    server_config()?;
+    client_config()?;
+    Ok(())
+}
+
+fn load_certs(path: &str) -> anyhow::Result<Vec<rustls::Certificate>> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| format!("Failed to open {}", path))?);
+    let certs = rustls_pemfile::certs(&mut reader).with_context(|| format!("Failed to read {}", path))?;
+    if certs.is_empty() {
+        anyhow::bail!("No certificate in {}", path);
+    }
+    Ok(certs.into_iter().map(rustls::Certificate).collect())
+}
+
+fn load_key(path: &str) -> anyhow::Result<rustls::PrivateKey> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| format!("Failed to open {}", path))?);
+    loop {
+        match rustls_pemfile::read_one(&mut reader).with_context(|| format!("Failed to read {}", path))? {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => return Ok(rustls::PrivateKey(key)),
+            Some(_) => {}
+            None => anyhow::bail!("No private key in {}", path),
+        }
+    }
+}
+
+/// Certificates other nodes are trusted with: the CA, or without one the node's own certificate.
+fn roots() -> anyhow::Result<rustls::RootCertStore> {
+    let ca = crate::Config::tls_ca();
+    let path = if ca.is_empty() { crate::Config::tls_cert() } else { ca };
+
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in load_certs(&path)? {
+        roots.add(&cert).with_context(|| format!("Invalid certificate in {}", path))?;
+    }
+    Ok(roots)
+}
+
+pub fn server_config() -> anyhow::Result<rustls::ServerConfig> {
+    let builder = rustls::ServerConfig::builder().with_safe_defaults();
+    let builder = if crate::Config::tls_ca().is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        builder.with_client_cert_verifier(rustls::server::AllowAnyAuthenticatedClient::new(roots()?))
+    };
+    Ok(builder.with_single_cert(load_certs(&crate::Config::tls_cert())?, load_key(&crate::Config::tls_key())?)?)
+}
+
+pub fn client_config() -> anyhow::Result<rustls::ClientConfig> {
+    let builder = rustls::ClientConfig::builder().with_safe_defaults().with_root_certificates(roots()?);
+    if crate::Config::tls_ca().is_empty() {
+        Ok(builder.with_no_client_auth())
+    } else {
+        Ok(builder.with_single_cert(load_certs(&crate::Config::tls_cert())?, load_key(&crate::Config::tls_key())?)?)
+    }
+}
+
+/// HTTP client for calls to other nodes, trusting and presenting the certificates above.
+pub fn http_client() -> reqwest::Client {
+    if !enabled() {
+        return reqwest::Client::new();
+    }
+    build_http_client().unwrap_or_else(|e| {
+        tracing::error!("Failed to set up TLS for calls to other nodes: {:#}", e);
+        reqwest::Client::new()
+    })
+}
+
+fn build_http_client() -> anyhow::Result<reqwest::Client> {
+    let ca = crate::Config::tls_ca();
+    let path = if ca.is_empty() { crate::Config::tls_cert() } else { ca.clone() };
+
+    let mut builder = reqwest::Client::builder().use_rustls_tls();
+    for cert in load_certs(&path)? {
+        builder = builder.add_root_certificate(reqwest::Certificate::from_der(&cert.0)?);
+    }
+    if !ca.is_empty() {
+        let mut pem = std::fs::read(crate::Config::tls_key())?;
+        pem.extend(std::fs::read(crate::Config::tls_cert())?);
+        builder = builder.identity(reqwest::Identity::from_pem(&pem)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Serves `app` over HTTPS on `addr`.
+pub async fn listen(app: Server, addr: String) -> std::io::Result<()> {
+    let config = server_config().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind(&addr).await?;
+
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to accept a connection: {}", e);
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        async_std::task::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => SharedStream(Arc::new(Mutex::new(stream))),
+                Err(e) => {
+                    tracing::debug!("TLS handshake failed: {}", e);
+                    return;
+                }
+            };
+            let served = async_h1::accept(stream, |req| {
+                let app = app.clone();
+                async move { app.respond::<tide::http::Request, tide::http::Response>(req).await }
+            })
+            .await;
+            if let Err(e) = served {
+                tracing::debug!("Connection closed with an error: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// A TLS stream async-h1 can clone for its reader and writer halves.
+#[derive(Clone)]
+struct SharedStream(Arc<Mutex<TlsStream<TcpStream>>>);
+
+impl AsyncRead for SharedStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for SharedStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_close(cx)
+    }
+}
@@ -0,0 +1,313 @@
+//! Authentication and authorization of the HTTP API.
+//!
+//! [`Authenticate`] runs in front of every route and resolves the bearer token once into a
+//! [`Principal`], kept in the request extensions for the handlers and the audit log. Each route
+//! then states the resource and level it needs with [`authorize`], which also enforces the space
+//! a token is bound to against the `:space_name` of the route.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+use serde_json::Value;
+use tide::Body;
+use tide::Endpoint;
+use tide::Middleware;
+use tide::Next;
+use tide::Request;
+use tide::Response;
+use tide::StatusCode;
+
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+use crate::config::Config;
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::cluster_auth;
+
+/// Permission level needed to read a resource.
+pub const READ: i32 = 1;
+
+/// Permission level needed to change a resource.
+pub const WRITE: i32 = 2;
+
+/// Resource types a token holds a permission level for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    System,
+    Space,
+    Version,
+    Vector,
+    Search,
+    Snapshot,
+    Security,
+    KeyValue,
+}
+
+/// Permission levels of a token, `0` none, [`READ`] or [`WRITE`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Permissions {
+    pub system: i32,
+    pub space: i32,
+    pub version: i32,
+    pub vector: i32,
+    pub search: i32,
+    pub snapshot: i32,
+    pub security: i32,
+    pub keyvalue: i32,
+}
+
+impl Permissions {
+    fn of(bo: &ATinyVectorsBO, token: &str) -> Self {
+        let rbac = &bo.rbac_token;
+        Self {
+            system: rbac.get_system_permission(token),
+            space: rbac.get_space_permission(token),
+            version: rbac.get_version_permission(token),
+            vector: rbac.get_vector_permission(token),
+            search: rbac.get_search_permission(token),
+            snapshot: rbac.get_snapshot_permission(token),
+            security: rbac.get_security_permission(token),
+            keyvalue: rbac.get_keyvalue_permission(token),
+        }
+    }
+
+    pub fn level(&self, resource: Resource) -> i32 {
+        match resource {
+            Resource::System => self.system,
+            Resource::Space => self.space,
+            Resource::Version => self.version,
+            Resource::Vector => self.vector,
+            Resource::Search => self.search,
+            Resource::Snapshot => self.snapshot,
+            Resource::Security => self.security,
+            Resource::KeyValue => self.keyvalue,
+        }
+    }
+}
+
+/// Who a request is made by.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Principal {
+    /// Security is disabled, every request is allowed.
+    Anonymous,
+    /// Another node of the cluster, presenting the cluster secret.
+    Cluster,
+    /// The bootstrap token of the node configuration, holding every permission.
+    Admin,
+    Token(TokenPrincipal),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenPrincipal {
+    /// Id of the token record, `None` for an unknown token. The token itself is never logged.
+    pub id: Option<u64>,
+    /// Space the token is bound to, `None` for a token valid in every space.
+    pub space_id: Option<u64>,
+    pub permissions: Permissions,
+}
+
+impl Principal {
+    pub fn allows(&self, resource: Resource, level: i32) -> bool {
+        match self {
+            Principal::Anonymous | Principal::Cluster | Principal::Admin => true,
+            Principal::Token(token) => token.permissions.level(resource) >= level,
+        }
+    }
+
+    /// Whether a request for the space with id `space_id` may be made, `None` for routes that
+    /// are not about a single space, which only unbound tokens may call.
+    pub fn allows_space(&self, space_id: Option<u64>) -> bool {
+        match self {
+            Principal::Anonymous | Principal::Cluster | Principal::Admin => true,
+            Principal::Token(TokenPrincipal { space_id: None, .. }) => true,
+            Principal::Token(TokenPrincipal { space_id: Some(scope), .. }) => space_id == Some(*scope),
+        }
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Principal::Anonymous => write!(f, "anonymous"),
+            Principal::Cluster => write!(f, "cluster"),
+            Principal::Admin => write!(f, "admin token"),
+            Principal::Token(TokenPrincipal { id: None, .. }) => write!(f, "unknown token"),
+            Principal::Token(TokenPrincipal { id: Some(id), space_id: None, .. }) => write!(f, "token {}", id),
+            Principal::Token(TokenPrincipal { id: Some(id), space_id: Some(space_id), .. }) => {
+                write!(f, "token {} (space {})", id, space_id)
+            }
+        }
+    }
+}
+
+/// Bearer token of `req`, empty without an `Authorization` header.
+pub fn bearer_token<State>(req: &Request<State>) -> String {
+    req.header("Authorization")
+        .and_then(|header| header.get(0))
+        .map(|value| value.as_str().trim_start_matches("Bearer ").to_string())
+        .unwrap_or_default()
+}
+
+/// Id and bound space of `token` in the JSON array returned by `list_tokens`. A `space_id` of
+/// `0` means the token is not bound to a space.
+pub fn token_record(tokens: &str, token: &str) -> Option<(u64, Option<u64>)> {
+    let tokens: Vec<Value> = serde_json::from_str(tokens).ok()?;
+    let record = tokens.iter().find(|t| t.get("token").and_then(Value::as_str) == Some(token))?;
+    let id = record.get("id").and_then(Value::as_u64)?;
+    let space_id = record.get("space_id").and_then(Value::as_u64).filter(|&space_id| space_id != 0);
+    Some((id, space_id))
+}
+
+/// How long a resolved token is reused, so that a token expiring in the engine is noticed even
+/// when no token command is applied.
+const TOKEN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Number of tokens kept resolved, the cache starts over once it is full.
+const TOKEN_CACHE_CAPACITY: usize = 10_000;
+
+struct CachedToken {
+    generation: u64,
+    resolved_at: Instant,
+    principal: TokenPrincipal,
+}
+
+/// Resolved known tokens, valid while the token generation they were resolved at is current.
+static TOKEN_CACHE: Lazy<Mutex<HashMap<String, CachedToken>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn cached_token(token: &str, generation: u64) -> Option<TokenPrincipal> {
+    let cache = TOKEN_CACHE.lock().unwrap();
+    cache
+        .get(token)
+        .filter(|cached| cached.generation == generation && cached.resolved_at.elapsed() < TOKEN_CACHE_TTL)
+        .map(|cached| cached.principal.clone())
+}
+
+fn cache_token(token: String, generation: u64, principal: TokenPrincipal) {
+    let mut cache = TOKEN_CACHE.lock().unwrap();
+    if cache.len() >= TOKEN_CACHE_CAPACITY && !cache.contains_key(&token) {
+        cache.clear();
+    }
+    cache.insert(token, CachedToken { generation, resolved_at: Instant::now(), principal });
+}
+
+fn resolve_token(bo: &ATinyVectorsBO, token: &str) -> TokenPrincipal {
+    let permissions = Permissions::of(bo, token);
+    let record = bo.rbac_token.list_tokens().ok().and_then(|tokens| token_record(&tokens, token));
+    TokenPrincipal {
+        id: record.map(|(id, _)| id),
+        space_id: record.and_then(|(_, space_id)| space_id),
+        permissions,
+    }
+}
+
+fn resolve(req: &Request<Arc<App>>) -> Principal {
+    if Config::enable_security() == 0 {
+        return Principal::Anonymous;
+    }
+
+    let secret = req.header(cluster_auth::SECRET_HEADER).and_then(|header| header.get(0));
+    if secret.is_some_and(|secret| cluster_auth::secret_matches(secret.as_str())) {
+        return Principal::Cluster;
+    }
+
+    let token = bearer_token(req);
+    let admin_token = Config::admin_token();
+    if !admin_token.is_empty() && cluster_auth::constant_time_eq(admin_token.as_bytes(), token.as_bytes()) {
+        return Principal::Admin;
+    }
+
+    if token.is_empty() {
+        return Principal::Token(TokenPrincipal { id: None, space_id: None, permissions: Permissions::default() });
+    }
+
+    // The generation is read before resolving, so a token command applied meanwhile leaves the
+    // entry stale rather than hiding the change.
+    let bo = &req.state().atinyvectors_bo;
+    let generation = bo.rbac_token.generation();
+    if let Some(principal) = cached_token(&token, generation) {
+        return Principal::Token(principal);
+    }
+    let principal = resolve_token(bo, &token);
+    if principal.id.is_some() {
+        cache_token(token, generation, principal.clone());
+    }
+    Principal::Token(principal)
+}
+
+/// Resolves the [`Principal`] of every request and stores it in the request extensions.
+pub struct Authenticate;
+
+#[tide::utils::async_trait]
+impl Middleware<Arc<App>> for Authenticate {
+    async fn handle(&self, mut req: Request<Arc<App>>, next: Next<'_, Arc<App>>) -> tide::Result {
+        let principal = resolve(&req);
+        tracing::debug!("{} {} by {}", req.method(), logged_path(&req), principal);
+        req.set_ext(principal);
+        Ok(next.run(req).await)
+    }
+}
+
+fn space_id(bo: &ATinyVectorsBO, space_name: &str) -> Option<u64> {
+    let space: Value = serde_json::from_str(&bo.space.get_by_space_name(space_name).ok()?).ok()?;
+    space.get("space_id").or_else(|| space.get("id")).and_then(Value::as_u64)
+}
+
+/// Path of `req` for the logs, with a token passed in the path masked.
+fn logged_path(req: &Request<Arc<App>>) -> String {
+    let path = req.url().path().to_string();
+    match req.param("token") {
+        Ok(token) if !token.is_empty() => path.replace(token, "***"),
+        _ => path,
+    }
+}
+
+fn forbidden(error: String) -> tide::Result {
+    Ok(Response::builder(StatusCode::Forbidden)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&json!({"error": error}))?)
+        .build())
+}
+
+/// Lets a request through only when its principal holds `level` on `resource` and, for a token
+/// bound to a space, when the route is about that space. Writes are logged for audit.
+pub fn authorize<E>(resource: Resource, level: i32, endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let principal = match req.ext::<Principal>() {
+                Some(principal) => principal.clone(),
+                None => resolve(&req),
+            };
+
+            if !principal.allows(resource, level) {
+                tracing::info!("denied {} {} to {}: {:?} permission", req.method(), logged_path(&req), principal, resource);
+                return forbidden("Forbidden".to_string());
+            }
+
+            if !principal.allows_space(None) {
+                let space_name = req.param("space_name").ok();
+                let space_id = space_name.and_then(|name| space_id(&req.state().atinyvectors_bo, name));
+                if !principal.allows_space(space_id) {
+                    tracing::info!("denied {} {} to {}: out of scope", req.method(), logged_path(&req), principal);
+                    return forbidden(match space_name {
+                        Some(name) => format!("Token is not valid for space '{}'", name),
+                        None => "Token is bound to a space".to_string(),
+                    });
+                }
+            }
+
+            if level >= WRITE {
+                tracing::info!("audit: {} {} by {}", req.method(), logged_path(&req), principal);
+            }
+            endpoint.call(req).await
+        }
+    }
+}
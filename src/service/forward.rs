@@ -0,0 +1,215 @@
+//! Forwarding of writes that reach a follower.
+//!
+//! Only the leader can append to the Raft log. When a write handler answers with
+//! [`NotLeader`], the request is sent on to the leader (`proxy`) or the client is pointed at it
+//! with a 307 (`redirect`), depending on `Config::leader_forwarding()`.
+
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+use tide::http::Method;
+use tide::http::Url;
+use tide::{Body, Endpoint, Request, Response, StatusCode};
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::tls;
+use crate::raft_cluster::NodeRole;
+use crate::service::handlers::raft_response::NotLeader;
+use crate::service::sharding;
+use crate::service::sharding::SHARD_HEADER;
+
+/// Set on proxied requests so they are not forwarded a second time while leadership moves.
+const FORWARDED_HEADER: &str = "X-ATV-Forwarded";
+
+/// Headers that describe a single connection and must not be copied to the next hop.
+const HOP_HEADERS: &[&str] = &["host", "connection", "content-length", "transfer-encoding", "keep-alive"];
+
+static PROXY_CLIENT: Lazy<reqwest::Client> = Lazy::new(tls::http_client);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForwardMode {
+    Off,
+    Proxy,
+    Redirect,
+}
+
+impl ForwardMode {
+    /// Replica nodes redirect writes instead of proxying them, so that write traffic does not
+    /// pass through them. With sharding writes are always proxied, a redirect cannot name the
+    /// shard group the leader should apply it to.
+    pub fn from_config() -> ForwardMode {
+        let mode = match crate::Config::leader_forwarding().as_str() {
+            "off" => ForwardMode::Off,
+            "redirect" => ForwardMode::Redirect,
+            "proxy" => ForwardMode::Proxy,
+            other => {
+                tracing::warn!("Unknown leader_forwarding '{}', using 'proxy'", other);
+                ForwardMode::Proxy
+            }
+        };
+        match mode {
+            ForwardMode::Redirect if sharding::shard_map().is_some() => ForwardMode::Proxy,
+            ForwardMode::Proxy if NodeRole::from_config() == NodeRole::Replica && sharding::shard_map().is_none() => {
+                ForwardMode::Redirect
+            }
+            mode => mode,
+        }
+    }
+}
+
+/// Wraps a write endpoint so that any node of the cluster can accept it.
+pub fn forward_writes<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let mode = ForwardMode::from_config();
+            if mode == ForwardMode::Off || req.header(FORWARDED_HEADER).is_some() {
+                return endpoint.call(req).await;
+            }
+
+            // The handler consumes the body, keep a copy in case it has to be sent again.
+            let bytes = req.take_body().into_bytes().await?;
+            let mut body = Body::from_bytes(bytes.clone());
+            if let Some(mime) = req.content_type() {
+                body.set_mime(mime);
+            }
+            req.set_body(body);
+
+            let method = req.method();
+            let url = req.url().clone();
+            let headers: Vec<(String, String)> = req
+                .iter()
+                .filter(|(name, _)| !is_hop_header(name.as_str()))
+                .flat_map(|(name, values)| values.iter().map(move |v| (name.to_string(), v.to_string())))
+                .collect();
+            let app = req.state().clone();
+
+            let mut res = endpoint.call(req).await?;
+            let leader_addr = match res.ext::<NotLeader>().and_then(|n| n.leader_api_addr.clone()) {
+                Some(addr) => addr,
+                None => return Ok(res),
+            };
+            let target = leader_url(&leader_addr, &url);
+
+            match mode {
+                ForwardMode::Redirect => {
+                    res.set_status(StatusCode::TemporaryRedirect);
+                    res.insert_header("Location", target);
+                    Ok(res)
+                }
+                _ => proxy(&app, method, target, headers, bytes).await,
+            }
+        }
+    }
+}
+
+fn leader_url(api_addr: &str, url: &Url) -> String {
+    let base = tls::base_url(api_addr);
+
+    match url.query() {
+        Some(query) => format!("{}{}?{}", base, url.path(), query),
+        None => format!("{}{}", base, url.path()),
+    }
+}
+
+/// Sends the request to the leader and relays its answer.
+async fn proxy(
+    app: &App,
+    method: Method,
+    target: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+) -> tide::Result {
+    tracing::debug!("Forwarding {} to the leader: {}", method, target);
+
+    let mut builder = match request(method, &target) {
+        Ok(builder) => builder.header(FORWARDED_HEADER, "1").body(body),
+        Err(e) => return bad_gateway(&e),
+    };
+    if let Some(shard) = app.shard {
+        builder = builder.header(SHARD_HEADER, shard.to_string());
+    }
+    let headers = headers.into_iter().filter(|(name, _)| !name.eq_ignore_ascii_case(SHARD_HEADER)).collect();
+
+    let relayed = match send(app, builder, headers).await {
+        Ok(relayed) => relayed,
+        Err(e) => return bad_gateway(&e),
+    };
+
+    let mut res = Response::new(relayed.status);
+    res.set_body(Body::from_bytes(relayed.body));
+    for (name, value) in relayed.headers {
+        // `set_body` already set a content type, replace it with the leader's.
+        if name == "content-type" {
+            res.insert_header(name.as_str(), value);
+        } else {
+            res.append_header(name.as_str(), value);
+        }
+    }
+    Ok(res)
+}
+
+/// Whether `name` describes a single connection and must not be copied to the next hop.
+pub fn is_hop_header(name: &str) -> bool {
+    HOP_HEADERS.iter().any(|hop| hop.eq_ignore_ascii_case(name))
+}
+
+/// A request to another node, with the client shared by every hop.
+pub fn request(method: Method, url: &str) -> Result<reqwest::RequestBuilder, String> {
+    let method = reqwest::Method::from_bytes(method.to_string().as_bytes()).map_err(|e| e.to_string())?;
+    Ok(PROXY_CLIENT.request(method, url))
+}
+
+/// Answer of another node to [`send`].
+pub struct Relayed {
+    pub status: u16,
+    /// Headers of the answer, hop-by-hop ones left out.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Sends `builder` with `headers`, leaving out the hop-by-hop ones, and reads the answer.
+/// reqwest needs a tokio context, which tide handlers do not have, so the call runs on the
+/// runtime kept in `App`.
+pub async fn send(app: &App, builder: reqwest::RequestBuilder, headers: Vec<(String, String)>) -> Result<Relayed, String> {
+    let mut builder = builder;
+    for (name, value) in headers {
+        if !is_hop_header(&name) {
+            builder = builder.header(name, value);
+        }
+    }
+
+    let result = app
+        .runtime
+        .spawn(async move {
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let headers: Vec<(String, String)> = response
+                .headers()
+                .iter()
+                .filter(|(name, _)| !is_hop_header(name.as_str()))
+                .filter_map(|(name, value)| value.to_str().ok().map(|v| (name.to_string(), v.to_string())))
+                .collect();
+            let body = response.bytes().await?.to_vec();
+            Ok::<_, reqwest::Error>(Relayed { status, headers, body })
+        })
+        .await;
+
+    match result {
+        Ok(Ok(relayed)) => Ok(relayed),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn bad_gateway(message: &str) -> tide::Result {
+    Ok(Response::builder(StatusCode::BadGateway)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&json!({"error": format!("Failed to forward the request to the leader: {}", message)}))?)
+        .build())
+}
@@ -1,8 +1,8 @@
-pub mod keyvalue_dto;
-pub mod rerank_dto;
-pub mod search_dto;
-pub mod security_dto;
-pub mod snapshot_dto;
-pub mod space_dto;
-pub mod vector_dto;
+pub mod keyvalue_dto;
+pub mod rerank_dto;
+pub mod search_dto;
+pub mod security_dto;
+pub mod snapshot_dto;
+pub mod space_dto;
+pub mod vector_dto;
 pub mod version_dto;
\ No newline at end of file
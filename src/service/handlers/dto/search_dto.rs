@@ -1,27 +1,83 @@
-// INFO: this file is not used in the project, it is just a reference for the OpenAPI documentation
-
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
-
-/// Request DTO for search operations
-#[derive(Serialize, Deserialize, ToSchema)]
-pub struct SearchRequest {
-    /// The vector used for searching
-    vector: Vec<f32>,
-}
-
-/// Response DTO for search results
-#[derive(Serialize, Deserialize, ToSchema)]
-pub struct SearchResponse {
-    /// Distance between the input vector and the found vector
-    distance: f64,
-    /// Label corresponding to the found vector
-    label: u64,
-}
-
-/// ErrorResponse DTO for search operations
-#[derive(Serialize, Deserialize, ToSchema)]
-pub struct SearchErrorResponse {
-    /// Error message
-    error: String,
-}
+// INFO: this file is not used in the project, it is just a reference for the OpenAPI documentation
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Request DTO for search operations
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SearchRequest {
+    /// The vector used for searching
+    vector: Vec<f32>,
+}
+
+/// Response DTO for search results
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SearchResponse {
+    /// Distance between the input vector and the found vector
+    distance: f64,
+    /// Label corresponding to the found vector
+    label: u64,
+}
+
+/// ErrorResponse DTO for search operations
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SearchErrorResponse {
+    /// Error message
+    error: String,
+}
+
+/// Request DTO for batch search, the queries run independently of each other
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BatchSearchRequest {
+    /// Queries to run
+    queries: Vec<BatchSearchQuery>,
+}
+
+/// One query of a batch search
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BatchSearchQuery {
+    /// The vector used for searching
+    vector: Vec<f32>,
+    /// Number of results to return, 10 by default
+    #[serde(default)]
+    top_k: Option<usize>,
+    /// Filter expression on the metadata
+    #[serde(default)]
+    filter: Option<String>,
+    /// Index to search, the default index if missing
+    #[serde(default)]
+    index_name: Option<String>,
+}
+
+/// Outcome of one query of a batch search, either `results` or `error`
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BatchSearchResult {
+    /// Hits of the query
+    #[serde(default)]
+    results: Option<Vec<SearchResponse>>,
+    /// Why the query failed
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Request DTO for recommendations built from vectors already stored in the space
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RecommendRequest {
+    /// Ids of the stored vectors the results should be close to
+    positive: Vec<u64>,
+    /// Ids of the stored vectors the results should be far from
+    #[serde(default)]
+    negative: Option<Vec<u64>>,
+    /// "average" (default) or "best_score"
+    #[serde(default)]
+    strategy: Option<String>,
+    /// Number of results to return, 10 by default
+    #[serde(default)]
+    top_k: Option<usize>,
+    /// Filter expression on the metadata
+    #[serde(default)]
+    filter: Option<String>,
+    /// Index to search, the default index if missing
+    #[serde(default)]
+    index_name: Option<String>,
+}
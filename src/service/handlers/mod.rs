@@ -1,10 +1,13 @@
-pub mod kvstorage_handler;
-pub mod rerank_handler;
-pub mod space_handler;
-pub mod version_handler;
-pub mod vector_handler;
-pub mod search_handler;
-pub mod snapshot_handler;
-pub mod security_handler;
-
+pub mod kvstorage_handler;
+pub mod rerank_handler;
+pub mod space_handler;
+pub mod version_handler;
+pub mod vector_handler;
+pub mod search_handler;
+pub mod snapshot_handler;
+pub mod security_handler;
+pub mod recommend_handler;
+pub mod raft_response;
+pub mod read_consistency;
+
 pub mod dto;
\ No newline at end of file
@@ -0,0 +1,73 @@
+use tide::{Body, Response, StatusCode};
+use serde_json::json;
+
+use crate::atinyvectors::atinyvectors_raft_command::{CommandError, CommandErrorCode};
+use crate::raft_cluster::typ;
+use crate::raft_cluster::NodeId;
+
+/// Attached to the response when a write reached a follower, so the forwarding middleware can
+/// send the request on to the leader.
+#[derive(Debug, Clone)]
+pub struct NotLeader {
+    pub leader_id: Option<NodeId>,
+    pub leader_api_addr: Option<String>,
+}
+
+/// HTTP status matching the error raised while applying a command.
+pub fn status_code(e: &CommandError) -> StatusCode {
+    match e.code {
+        CommandErrorCode::InvalidArgument => StatusCode::BadRequest,
+        CommandErrorCode::NotFound => StatusCode::NotFound,
+        CommandErrorCode::Conflict => StatusCode::Conflict,
+        CommandErrorCode::Internal => StatusCode::InternalServerError,
+    }
+}
+
+pub fn error_response(e: &CommandError) -> tide::Result {
+    Ok(Response::builder(status_code(e))
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&json!({"error": e.message, "code": e.code}))?)
+        .build())
+}
+
+/// Converts the result of `raft.client_write` into the HTTP answer, reflecting what the
+/// command actually did when it was applied on the leader.
+pub fn write_response(res: Result<typ::ClientWriteResponse, typ::RaftError<typ::ClientWriteError>>) -> tide::Result {
+    match res {
+        Ok(raft_res) => match raft_res.data.result {
+            Ok(output) => Ok(
+                Response::builder(StatusCode::Ok)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from_json(&json!({"result": "success", "affected": output.affected}))?)
+                    .build()),
+            Err(e) => error_response(&e),
+        },
+        Err(e) => raft_error_response(&e),
+    }
+}
+
+/// HTTP answer for a write that Raft did not accept. A follower answers 421 with the leader it
+/// knows of, which the forwarding middleware turns into a proxied request or a redirect.
+pub fn raft_error_response(e: &typ::RaftError<typ::ClientWriteError>) -> tide::Result {
+    if let Some(forward) = e.forward_to_leader() {
+        let not_leader = NotLeader {
+            leader_id: forward.leader_id,
+            leader_api_addr: forward.leader_node.as_ref().map(|node| node.api_addr.clone()),
+        };
+        let mut res = Response::builder(StatusCode::MisdirectedRequest)
+            .header("Content-Type", "application/json")
+            .body(Body::from_json(&json!({
+                "error": e.to_string(),
+                "leader_id": not_leader.leader_id,
+                "leader_api_addr": not_leader.leader_api_addr,
+            }))?)
+            .build();
+        res.insert_ext(not_leader);
+        return Ok(res);
+    }
+
+    Ok(Response::builder(StatusCode::InternalServerError)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&json!({"error": e.to_string()}))?)
+        .build())
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::json;
+use tide::{Body, Request, Response, StatusCode};
+use utoipa::ToSchema;
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::typ;
+use crate::raft_cluster::wait_for_metrics;
+use crate::raft_cluster::NodeId;
+
+/// How long a linearizable read waits for this node to apply the read index.
+const READ_INDEX_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Freshness required by a read, chosen per request with `?consistency=`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Consistency {
+    /// Serve from this node's state, which may lag behind the leader.
+    #[default]
+    Local,
+    /// Serve only on the node that considers itself leader. Cheap, but a deposed leader that has
+    /// not noticed yet may still answer.
+    Leader,
+    /// Confirm leadership with a quorum and wait until everything committed before the read is
+    /// applied (read index).
+    Linearizable,
+}
+
+#[derive(Deserialize)]
+struct ConsistencyParams {
+    consistency: Option<Consistency>,
+}
+
+/// Makes sure this node may serve the read with the consistency asked for in the query string.
+///
+/// `Err` holds the response to send instead: 400 for an unknown level, 421 with the leader when
+/// this node is not the leader, 503 when the read index cannot be reached in time.
+pub async fn ensure_read_consistency(req: &Request<Arc<App>>) -> Result<(), tide::Result> {
+    let consistency = match req.query::<ConsistencyParams>() {
+        Ok(params) => params.consistency.unwrap_or_default(),
+        Err(_) => return Err(error_response(
+            StatusCode::BadRequest,
+            "Invalid 'consistency', expected one of: local, leader, linearizable",
+            None,
+        )),
+    };
+
+    let app = req.state();
+    match consistency {
+        Consistency::Local => Ok(()),
+        Consistency::Leader => {
+            let leader = app.raft.current_leader().await;
+            if leader == Some(app.id) {
+                Ok(())
+            } else {
+                Err(not_leader_response(app, leader))
+            }
+        }
+        Consistency::Linearizable => ensure_linearizable(app).await,
+    }
+}
+
+/// Confirms leadership with a quorum and waits until this node has applied everything committed
+/// before the call, so reads that follow see every acknowledged write.
+pub async fn ensure_linearizable(app: &App) -> Result<(), tide::Result> {
+    let read_log_id = match app.raft.get_read_log_id().await {
+        Ok((read_log_id, _)) => read_log_id,
+        Err(typ::RaftError::APIError(typ::CheckIsLeaderError::ForwardToLeader(forward))) => {
+            return Err(not_leader_response(app, forward.leader_id));
+        }
+        Err(e) => return Err(error_response(StatusCode::ServiceUnavailable, &e.to_string(), None)),
+    };
+
+    let applied = wait_for_metrics(&app.raft, &app.runtime, READ_INDEX_TIMEOUT, move |m| {
+        (m.last_applied >= read_log_id).then_some(())
+    })
+    .await;
+    applied.ok_or_else(|| {
+        error_response(
+            StatusCode::ServiceUnavailable,
+            "Timed out waiting for the read index to be applied",
+            None,
+        )
+    })
+}
+
+fn not_leader_response(app: &App, leader_id: Option<NodeId>) -> tide::Result {
+    error_response(StatusCode::MisdirectedRequest, "This node is not the leader", leader_id.map(|id| (id, app)))
+}
+
+fn error_response(status: StatusCode, message: &str, leader: Option<(NodeId, &App)>) -> tide::Result {
+    let mut body = json!({"error": message});
+    if let Some((leader_id, app)) = leader {
+        body["leader_id"] = json!(leader_id);
+        let metrics = app.raft.metrics().borrow().clone();
+        if let Some(node) = metrics.membership_config.membership().get_node(&leader_id) {
+            body["leader_api_addr"] = json!(node.api_addr);
+        }
+    }
+
+    Ok(Response::builder(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&body)?)
+        .build())
+}
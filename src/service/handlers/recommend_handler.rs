@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tide::{Body, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use crate::raft_cluster::app::App;
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+
+use crate::service::handlers::read_consistency::{ensure_read_consistency, Consistency};
+use crate::service::handlers::search_handler::{require_index, top_k};
+use crate::service::sharding::{merge_top_k, ShardReads};
+
+use crate::service::handlers::dto::search_dto::{
+    RecommendRequest, SearchResponse, SearchErrorResponse
+};
+
+/// Stored examples, positive and negative together, a request may name.
+pub const MAX_EXAMPLES: usize = 64;
+
+/// How the stored examples are turned into results.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendStrategy {
+    /// One search for `avg(positive) + (avg(positive) - avg(negative))`.
+    #[default]
+    Average,
+    /// One search per example; a candidate ranks by its distance to the closest positive and is
+    /// dropped when a negative is closer.
+    BestScore,
+}
+
+#[derive(Deserialize)]
+struct RecommendBody {
+    positive: Vec<u64>,
+    #[serde(default)]
+    negative: Vec<u64>,
+    #[serde(default)]
+    strategy: RecommendStrategy,
+    top_k: Option<usize>,
+    filter: Option<String>,
+    index_name: Option<String>,
+}
+
+// POST /api/space/{space_name}/recommend
+#[utoipa::path(
+    post,
+    path = "/api/space/{space_name}/recommend",
+    request_body = RecommendRequest,
+    params(
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "Closest vectors to the examples, the examples excluded", body = [SearchResponse]),
+        (status = 400, description = "No positive example, too many examples or mismatching dimensions", body = SearchErrorResponse),
+        (status = 403, description = "Forbidden", body = SearchErrorResponse),
+        (status = 404, description = "Example vector or vector index not found", body = SearchErrorResponse),
+        (status = 501, description = "The vector index is not the default index of the version", body = SearchErrorResponse)
+    )
+)]
+pub async fn recommend(mut req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id: i32 = req.param("version_id").unwrap_or("0").parse().unwrap_or(0);
+
+    let body: RecommendBody = match req.body_json().await {
+        Ok(body) => body,
+        Err(e) => return error(StatusCode::BadRequest, format!("Invalid request: {}", e)),
+    };
+    if body.positive.is_empty() {
+        return error(StatusCode::BadRequest, "At least one 'positive' id is required".to_string());
+    }
+    if body.positive.len() + body.negative.len() > MAX_EXAMPLES {
+        return error(StatusCode::BadRequest, format!("At most {} examples per request", MAX_EXAMPLES));
+    }
+
+    let bo = req.state().atinyvectors_bo.clone();
+    if let Err(res) = require_index(&bo, &space_name, version_id, body.index_name.as_deref()) {
+        return res;
+    }
+
+    let reads = ShardReads::of(&req);
+    let reads = reads.as_ref();
+    let positives = match stored_vectors(&bo, reads, &space_name, version_id, &body.positive).await {
+        Ok(positives) => positives,
+        Err((status, e)) => return error(status, e),
+    };
+    let negatives = match stored_vectors(&bo, reads, &space_name, version_id, &body.negative).await {
+        Ok(negatives) => negatives,
+        Err((status, e)) => return error(status, e),
+    };
+
+    let k = body.top_k.filter(|k| *k > 0).unwrap_or(10);
+    let exclude: BTreeSet<u64> = body.positive.iter().chain(&body.negative).copied().collect();
+    // the examples themselves are usually among the closest hits, ask for enough to drop them
+    let query = |vector: &[f32], k: usize| -> Value {
+        let mut query = json!({"vector": vector, "top_k": k + exclude.len()});
+        if let Some(filter) = &body.filter {
+            query["filter"] = json!(filter);
+        }
+        query
+    };
+
+    let result = match body.strategy {
+        RecommendStrategy::Average => match average_query(&positives, &negatives) {
+            Ok(vector) => search(&bo, reads, &space_name, version_id, &query(&vector, k))
+                .await
+                .map(|hits| exclude_ids(hits, &exclude, k))
+                .map_err(|e| (StatusCode::InternalServerError, e)),
+            Err(e) => Err((StatusCode::BadRequest, e)),
+        },
+        RecommendStrategy::BestScore => {
+            // a wider pool per example, as candidates must show up in the positive searches
+            let pool = k * 4;
+            let queries = |examples: &[Vec<f32>]| -> Vec<Value> {
+                examples.iter().map(|example| query(example, pool)).collect()
+            };
+            let positive_hits = search_each(&bo, reads, &space_name, version_id, queries(&positives)).await;
+            let negative_hits = search_each(&bo, reads, &space_name, version_id, queries(&negatives)).await;
+            match (positive_hits, negative_hits) {
+                (Ok(positive_hits), Ok(negative_hits)) => Ok(best_score(positive_hits, negative_hits, &exclude, k)),
+                (Err(e), _) | (_, Err(e)) => Err((StatusCode::InternalServerError, e)),
+            }
+        }
+    };
+
+    match result {
+        Ok(hits) => Ok(Response::builder(StatusCode::Ok)
+            .header("Content-Type", "application/json")
+            .body(Body::from_json(&Value::Array(hits))?)
+            .build()),
+        Err((status, e)) => error(status, e),
+    }
+}
+
+fn error(status: StatusCode, error: String) -> tide::Result {
+    Ok(Response::builder(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&json!({"error": error}))?)
+        .build())
+}
+
+/// Dense data of the stored vectors `ids`. With sharding, a vector of another group is fetched
+/// from that group. The local ones are read in a single pass over the version.
+async fn stored_vectors(
+    bo: &ATinyVectorsBO,
+    reads: Option<&ShardReads>,
+    space_name: &str,
+    version_id: i32,
+    ids: &[u64],
+) -> Result<Vec<Vec<f32>>, (StatusCode, String)> {
+    let is_local = |id: u64| !matches!(reads, Some(reads) if !reads.is_local(id));
+    let local_ids: Vec<u64> = ids.iter().copied().filter(|id| is_local(*id)).collect();
+    let local = if local_ids.is_empty() {
+        BTreeMap::new()
+    } else {
+        bo.vector.get_vectors_by_ids(space_name, version_id, &local_ids)
+    };
+
+    let mut vectors = Vec::with_capacity(ids.len());
+    for id in ids {
+        let vector = match reads {
+            Some(reads) if !is_local(*id) => reads
+                .get_vector(space_name, version_id, *id)
+                .await
+                .map_err(|(status, e)| (StatusCode::try_from(status).unwrap_or(StatusCode::BadGateway), e))?,
+            _ => local
+                .get(id)
+                .cloned()
+                .ok_or_else(|| (StatusCode::NotFound, format!("Vector not found: {}", id)))?,
+        };
+        vectors.push(dense_data(&vector).ok_or_else(|| (StatusCode::NotFound, format!("Vector {} has no dense data", id)))?);
+    }
+    Ok(vectors)
+}
+
+fn dense_data(vector: &Value) -> Option<Vec<f32>> {
+    vector.get("data")
+        .and_then(Value::as_array)
+        .and_then(|data| data.iter().map(|v| v.as_f64().map(|f| f as f32)).collect::<Option<Vec<f32>>>())
+        .filter(|data| !data.is_empty())
+}
+
+/// Hits of `query`, with sharding merged over every group.
+async fn search(
+    bo: &ATinyVectorsBO,
+    reads: Option<&ShardReads>,
+    space_name: &str,
+    version_id: i32,
+    query: &Value,
+) -> Result<Vec<Value>, String> {
+    let k = top_k(query);
+    let hits = bo.search.search(space_name, version_id, &query.to_string(), k)?;
+    let hits: Vec<Value> = serde_json::from_str(&hits).map_err(|e| format!("Failed to parse search results: {}", e))?;
+    match reads {
+        Some(reads) => {
+            let mut results = reads.search_others(space_name, version_id, query).await?;
+            results.push(hits);
+            Ok(merge_top_k(results, k))
+        }
+        None => Ok(hits),
+    }
+}
+
+async fn search_each(
+    bo: &ATinyVectorsBO,
+    reads: Option<&ShardReads>,
+    space_name: &str,
+    version_id: i32,
+    queries: Vec<Value>,
+) -> Result<Vec<Vec<Value>>, String> {
+    let mut results = Vec::with_capacity(queries.len());
+    for query in &queries {
+        results.push(search(bo, reads, space_name, version_id, query).await?);
+    }
+    Ok(results)
+}
+
+fn mean(vectors: &[Vec<f32>]) -> Vec<f32> {
+    let mut sum = vec![0.0f32; vectors[0].len()];
+    for vector in vectors {
+        for (total, value) in sum.iter_mut().zip(vector) {
+            *total += value;
+        }
+    }
+    sum.iter().map(|total| total / vectors.len() as f32).collect()
+}
+
+/// Query of the average strategy: the mean of the positives, moved away from the mean of the
+/// negatives by as much as they differ.
+pub fn average_query(positives: &[Vec<f32>], negatives: &[Vec<f32>]) -> Result<Vec<f32>, String> {
+    let dimension = positives.first().map(Vec::len).ok_or_else(|| "At least one positive example is required".to_string())?;
+    if let Some(other) = positives.iter().chain(negatives).find(|vector| vector.len() != dimension) {
+        return Err(format!("Examples have different dimensions: {} and {}", dimension, other.len()));
+    }
+
+    let positive = mean(positives);
+    if negatives.is_empty() {
+        return Ok(positive);
+    }
+    let negative = mean(negatives);
+    Ok(positive.iter().zip(&negative).map(|(p, n)| p + (p - n)).collect())
+}
+
+fn label(hit: &Value) -> Option<u64> {
+    hit.get("label").and_then(Value::as_u64)
+}
+
+fn distance(hit: &Value) -> f64 {
+    hit.get("distance").and_then(Value::as_f64).unwrap_or(f64::INFINITY)
+}
+
+/// The `k` closest hits that are not one of the examples.
+pub fn exclude_ids(hits: Vec<Value>, exclude: &BTreeSet<u64>, k: usize) -> Vec<Value> {
+    hits.into_iter()
+        .filter(|hit| !label(hit).is_some_and(|label| exclude.contains(&label)))
+        .take(k)
+        .collect()
+}
+
+/// Results of the best-score strategy from the hits of one search per positive and per negative
+/// example. A candidate scores its smallest distance to a positive and is dropped when a negative
+/// is at least as close.
+pub fn best_score(positive_hits: Vec<Vec<Value>>, negative_hits: Vec<Vec<Value>>, exclude: &BTreeSet<u64>, k: usize) -> Vec<Value> {
+    let closest = |searches: Vec<Vec<Value>>| {
+        let mut closest: BTreeMap<u64, f64> = BTreeMap::new();
+        for hit in searches.into_iter().flatten() {
+            if let Some(label) = label(&hit) {
+                let best = closest.entry(label).or_insert(f64::INFINITY);
+                *best = best.min(distance(&hit));
+            }
+        }
+        closest
+    };
+    let positive = closest(positive_hits);
+    let negative = closest(negative_hits);
+
+    let mut candidates: Vec<(u64, f64)> = positive
+        .into_iter()
+        .filter(|(label, _)| !exclude.contains(label))
+        .filter(|(label, distance)| !negative.get(label).is_some_and(|negative| negative <= distance))
+        .collect();
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+    candidates.into_iter()
+        .take(k)
+        .map(|(label, distance)| json!({"label": label, "distance": distance}))
+        .collect()
+}
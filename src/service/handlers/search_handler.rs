@@ -1,147 +1,243 @@
-use std::sync::Arc;
-use tide::{Body, Request, Response, StatusCode};
-use serde_json::Value;
-use serde_json::json;
-use crate::config::Config;
-use crate::raft_cluster::app::App;
-
-use utoipa::{
-    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
-    Modify, OpenApi,
-};
-
-use crate::service::handlers::dto::search_dto::{
-    SearchRequest, SearchResponse, SearchErrorResponse
-};
-
-// Helper function to check search permissions
-fn extract_token(req: &Request<Arc<App>>) -> String {
-    req.header("Authorization")
-        .and_then(|header| header.get(0))
-        .map(|header_value| header_value.as_str().trim_start_matches("Bearer ").to_string())
-        .unwrap_or_default()
-}
-
-async fn check_read_permission(req: &Request<Arc<App>>) -> tide::Result<bool> {
-    if Config::enable_security() != 0 {
-        let token = extract_token(req);
-        let bo = req.state().atinyvectors_bo.clone();
-        if bo.rbac_token.get_search_permission(&token) < 1 {
-            return Ok(false);
-        }
-    }
-    Ok(true)
-}
-
-async fn check_write_permission(req: &Request<Arc<App>>) -> tide::Result<bool> {
-    if Config::enable_security() != 0 {
-        let token = extract_token(req);
-        let bo = req.state().atinyvectors_bo.clone();
-        if bo.rbac_token.get_search_permission(&token) < 2 {
-            return Ok(false);
-        }
-    }
-    Ok(true)
-}
-
-// POST /api/space/{space_name}/search
-#[utoipa::path(
-    post,
-    path = "/api/space/{space_name}/search",
-    request_body = SearchRequest,
-    responses(
-        (status = 200, description = "Search results successfully retrieved", body = [SearchResponse]),
-        (status = 403, description = "Forbidden", body = SearchErrorResponse)
-    )
-)]
-pub async fn search(mut req: Request<Arc<App>>) -> tide::Result {
-    if !check_read_permission(&req).await? {
-        return Ok(
-            Response::builder(StatusCode::Forbidden)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": "Forbidden"}))?)
-                .build());
-    }
-
-    let space_name = req.param("space_name").unwrap_or("default").to_string();
-    let version_id = 0;
-
-    let body: Value = req.body_json().await?;
-    let k = if let Some(top_k) = body.get("top_k").and_then(|v| v.as_u64()) {
-        top_k as usize
-    } else if let Some(k_value) = body.get("k").and_then(|v| v.as_u64()) {
-        k_value as usize
-    } else {
-        10
-    };
-
-    let k = if k <= 0 { 10 } else { k };
-    let bo = req.state().atinyvectors_bo.clone();
-    let result = bo.search.search(&space_name, version_id, &body.to_string(), k);
-
-    match result {
-        Ok(versions) => {
-            Ok(Response::builder(StatusCode::Ok)
-                .header("Content-Type", "application/json")
-                .body(Body::from_string(versions))
-                .build())
-        },
-        Err(e) => {
-            Ok(Response::builder(StatusCode::InternalServerError)
-                .header("Content-Type", "application/text")
-                .body(Body::from_string(e))
-                .build())
-        }
-    }
-}
-
-// POST /api/space/{space_name}/version/{version_id}/search
-#[utoipa::path(
-    post,
-    path = "/api/space/{space_name}/version/{version_id}/search",
-    request_body = SearchRequest,
-    responses(
-        (status = 200, description = "Search results successfully retrieved", body = [SearchResponse]),
-        (status = 403, description = "Forbidden", body = SearchErrorResponse)
-    )
-)]
-pub async fn search_with_version(mut req: Request<Arc<App>>) -> tide::Result {
-    if !check_read_permission(&req).await? {
-        return Ok(
-            Response::builder(StatusCode::Forbidden)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": "Forbidden"}))?)
-                .build());
-    }
-
-    let space_name = req.param("space_name").unwrap_or("default").to_string();
-    let version_id: i32 = req.param("version_id").unwrap_or("0").parse().unwrap_or(0);
-    
-    let body: Value = req.body_json().await?;
-    let k = if let Some(top_k) = body.get("top_k").and_then(|v| v.as_u64()) {
-        top_k as usize
-    } else if let Some(k_value) = body.get("k").and_then(|v| v.as_u64()) {
-        k_value as usize
-    } else {
-        10
-    };
-
-    let k = if k <= 0 { 10 } else { k };
-    let bo = req.state().atinyvectors_bo.clone();
-    let result = bo.search.search(&space_name, version_id, &body.to_string(), k);
-
-    match result {
-        Ok(versions) => {
-            Ok(Response::builder(StatusCode::Ok)
-                .header("Content-Type", "application/json")
-                .body(Body::from_string(versions))
-                .build())
-        },
-        Err(e) => {
-            Ok(Response::builder(StatusCode::InternalServerError)
-                .header("Content-Type", "application/text")
-                .body(Body::from_string(e))
-                .build())
-        }
-    }
-}
+use std::sync::Arc;
+use tide::{Body, Request, Response, StatusCode};
+use serde_json::Value;
+use serde_json::json;
+use futures::stream;
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use tokio::sync::Semaphore;
+use crate::config::Config;
+use crate::raft_cluster::app::App;
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+use crate::atinyvectors::vector_index;
+use crate::atinyvectors::vector_index::IndexError;
+
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::service::handlers::read_consistency::{ensure_read_consistency, Consistency};
+
+use crate::service::handlers::dto::search_dto::{
+    SearchRequest, SearchResponse, SearchErrorResponse, BatchSearchRequest, BatchSearchResult
+};
+
+/// Queries of a batch search beyond which the request is refused.
+pub const MAX_BATCH_QUERIES: usize = 1024;
+
+/// Queries of batch searches running at the same time, over every request, see
+/// `Config::search_batch_workers`.
+static SEARCH_WORKERS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(Config::search_batch_workers()));
+
+/// Number of hits asked for by a query, `top_k` or `k`, 10 by default.
+pub fn top_k(query: &Value) -> usize {
+    query.get("top_k").and_then(|v| v.as_u64())
+        .or_else(|| query.get("k").and_then(|v| v.as_u64()))
+        .filter(|k| *k > 0)
+        .map(|k| k as usize)
+        .unwrap_or(10)
+}
+
+/// Queries of a batch search body, `{"queries": [..]}` or a plain array.
+pub fn batch_queries(body: &Value) -> Result<Vec<Value>, String> {
+    let queries = body.as_array()
+        .or_else(|| body.get("queries").and_then(|v| v.as_array()))
+        .ok_or_else(|| "Missing 'queries' array".to_string())?;
+    if queries.is_empty() {
+        return Err("'queries' cannot be empty".to_string());
+    }
+    if queries.len() > MAX_BATCH_QUERIES {
+        return Err(format!("Too many queries: {}, at most {} per batch", queries.len(), MAX_BATCH_QUERIES));
+    }
+    Ok(queries.clone())
+}
+
+/// Checks the index named in a route or request, if any, answering 404 for an index the version
+/// does not have and 501 for one the engine cannot address, see `vector_index`.
+pub fn require_index(
+    bo: &ATinyVectorsBO,
+    space_name: &str,
+    version_id: i32,
+    index_name: Option<&str>,
+) -> Result<(), tide::Result> {
+    let index_name = match index_name {
+        Some(index_name) => index_name,
+        None => return Ok(()),
+    };
+
+    let (status, e) = match vector_index::resolve(bo, space_name, version_id, Some(index_name)) {
+        Ok(_) => return Ok(()),
+        Err(e @ IndexError::NotFound(_)) => (StatusCode::NotFound, e),
+        Err(e @ IndexError::NotAddressable(_)) => (StatusCode::NotImplemented, e),
+    };
+    Err(Body::from_json(&json!({"error": e.to_string()}))
+        .map(|body| Response::builder(status)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .build()))
+}
+
+// POST /api/space/{space_name}/search
+#[utoipa::path(
+    post,
+    path = "/api/space/{space_name}/search",
+    request_body = SearchRequest,
+    params(
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "Search results successfully retrieved", body = [SearchResponse]),
+        (status = 403, description = "Forbidden", body = SearchErrorResponse),
+        (status = 404, description = "Vector index not found", body = SearchErrorResponse),
+        (status = 501, description = "The vector index is not the default index of the version", body = SearchErrorResponse)
+    )
+)]
+pub async fn search(mut req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id = 0;
+
+    let body: Value = req.body_json().await?;
+    let k = top_k(&body);
+    let bo = req.state().atinyvectors_bo.clone();
+    if let Err(res) = require_index(&bo, &space_name, version_id, req.param("index_name").ok()) {
+        return res;
+    }
+    let result = bo.search.search(&space_name, version_id, &body.to_string(), k);
+
+    match result {
+        Ok(versions) => {
+            Ok(Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_string(versions))
+                .build())
+        },
+        Err(e) => {
+            Ok(Response::builder(StatusCode::InternalServerError)
+                .header("Content-Type", "application/text")
+                .body(Body::from_string(e))
+                .build())
+        }
+    }
+}
+
+// POST /api/space/{space_name}/version/{version_id}/search
+#[utoipa::path(
+    post,
+    path = "/api/space/{space_name}/version/{version_id}/search",
+    request_body = SearchRequest,
+    params(
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "Search results successfully retrieved", body = [SearchResponse]),
+        (status = 403, description = "Forbidden", body = SearchErrorResponse),
+        (status = 404, description = "Vector index not found", body = SearchErrorResponse),
+        (status = 501, description = "The vector index is not the default index of the version", body = SearchErrorResponse)
+    )
+)]
+pub async fn search_with_version(mut req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id: i32 = req.param("version_id").unwrap_or("0").parse().unwrap_or(0);
+    
+    let body: Value = req.body_json().await?;
+    let k = top_k(&body);
+    let bo = req.state().atinyvectors_bo.clone();
+    if let Err(res) = require_index(&bo, &space_name, version_id, req.param("index_name").ok()) {
+        return res;
+    }
+    let result = bo.search.search(&space_name, version_id, &body.to_string(), k);
+
+    match result {
+        Ok(versions) => {
+            Ok(Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_string(versions))
+                .build())
+        },
+        Err(e) => {
+            Ok(Response::builder(StatusCode::InternalServerError)
+                .header("Content-Type", "application/text")
+                .body(Body::from_string(e))
+                .build())
+        }
+    }
+}
+
+// POST /api/space/{space_name}/search/batch
+#[utoipa::path(
+    post,
+    path = "/api/space/{space_name}/search/batch",
+    request_body = BatchSearchRequest,
+    params(
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "One result or error per query, in the order of the queries", body = [BatchSearchResult]),
+        (status = 400, description = "No queries or too many of them", body = SearchErrorResponse),
+        (status = 403, description = "Forbidden", body = SearchErrorResponse)
+    )
+)]
+pub async fn search_batch(mut req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id: i32 = req.param("version_id").unwrap_or("0").parse().unwrap_or(0);
+
+    let body: Value = req.body_json().await?;
+    let queries = match batch_queries(&body) {
+        Ok(queries) => queries,
+        Err(e) => return Ok(Response::builder(StatusCode::BadRequest)
+            .header("Content-Type", "application/json")
+            .body(Body::from_json(&json!({"error": e}))?)
+            .build()),
+    };
+
+    // the engine calls block, so each query runs on the blocking pool once it holds one of the
+    // workers shared by all batch requests
+    let bo = req.state().atinyvectors_bo.clone();
+    let results: Vec<Value> = stream::iter(queries)
+        .map(|query| {
+            let bo = bo.clone();
+            let space_name = space_name.clone();
+            async move {
+                let _worker = SEARCH_WORKERS.acquire().await.expect("the search worker semaphore is never closed");
+                async_std::task::spawn_blocking(move || search_one(&bo, &space_name, version_id, query)).await
+            }
+        })
+        .buffered(Config::search_batch_workers())
+        .collect()
+        .await;
+
+    Ok(Response::builder(StatusCode::Ok)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&Value::Array(results))?)
+        .build())
+}
+
+/// Runs one query of a batch, `{"results": [..]}` or `{"error": ".."}`.
+fn search_one(bo: &ATinyVectorsBO, space_name: &str, version_id: i32, query: Value) -> Value {
+    if !query.is_object() {
+        return json!({"error": "Query must be a JSON object"});
+    }
+    if let Some(index_name) = query.get("index_name").and_then(|v| v.as_str()) {
+        if let Err(e) = vector_index::resolve(bo, space_name, version_id, Some(index_name)) {
+            return json!({"error": e.to_string()});
+        }
+    }
+
+    match bo.search.search(space_name, version_id, &query.to_string(), top_k(&query)) {
+        Ok(hits) => json!({"results": serde_json::from_str::<Value>(&hits).unwrap_or(Value::String(hits))}),
+        Err(e) => json!({"error": e}),
+    }
+}
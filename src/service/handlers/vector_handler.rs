@@ -1,252 +1,368 @@
-use std::sync::Arc;
-use tide::{Body, Request, Response, StatusCode};
-use serde_json::Value;
-use serde_json::json;
-use serde::Deserialize;
-
-use utoipa::{
-    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
-    Modify, OpenApi,
-};
-
-use crate::config::Config;
-use crate::raft_cluster::app::App;
-use crate::raft_cluster::store::Request as RaftRequest;
-use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
-
-use crate::service::handlers::dto::vector_dto::{
-    VectorData, VectorRequest, VectorResponse, VectorErrorResponse, GetVectorsResponse, VectorDataResponse};
-
-#[derive(Deserialize)]
-struct QueryParams {
-    start: Option<usize>,
-    limit: Option<usize>,
-    filter: Option<String>,
-}
-    
-// Helper function to check snapshot permissions
-fn extract_token(req: &Request<Arc<App>>) -> String {
-    req.header("Authorization")
-        .and_then(|header| header.get(0))
-        .map(|header_value| header_value.as_str().trim_start_matches("Bearer ").to_string())
-        .unwrap_or_default()
-}
-
-async fn check_read_permission(req: &Request<Arc<App>>) -> tide::Result<bool> {
-    if Config::enable_security() != 0 {
-        let token = extract_token(req);
-        let bo = req.state().atinyvectors_bo.clone();
-        if bo.rbac_token.get_vector_permission(&token) < 1 {
-            return Ok(false);
-        }
-    }
-    Ok(true)
-}
-
-async fn check_write_permission(req: &Request<Arc<App>>) -> tide::Result<bool> {
-    if Config::enable_security() != 0 {
-        let token = extract_token(req);
-        let bo = req.state().atinyvectors_bo.clone();
-        if bo.rbac_token.get_vector_permission(&token) < 2 {
-            return Ok(false);
-        }
-    }
-    Ok(true)
-}
-
-// POST /space/{space_name}/vector
-#[utoipa::path(
-    post,
-    path = "/space/{space_name}/vector",
-    request_body = VectorRequest,
-    responses(
-        (status = 200, description = "Vector created successfully", body = VectorResponse),
-        (status = 403, description = "Forbidden", body = VectorErrorResponse)
-    )
-)]
-pub async fn vector(mut req: Request<Arc<App>>) -> tide::Result {
-    if !check_write_permission(&req).await? {
-        return Ok(
-            Response::builder(StatusCode::Forbidden)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": "Forbidden"}))?)
-                .build());
-    }
-
-    let space_name = req.param("space_name").unwrap_or("default").to_string();
-
-    let body: Value = req.body_json().await?;
-    let wrapped_body = json!({
-        "request": {
-            "command": "vector",
-            "space_name": space_name,
-            "value": body
-        }
-    });
-    let raft_req = RaftRequest::Set {
-        key: "vector".to_string(),
-        value: serde_json::to_string(&wrapped_body)?,
-    };
-
-    // Send a write request to the Raft client
-    let res = req.state().raft.client_write(raft_req).await;
-
-    // Handle response
-    match res {
-        Ok(_) => Ok(
-            Response::builder(StatusCode::Ok)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"result": "success"}))?)
-                .build()),
-        Err(e) => Ok(
-            Response::builder(StatusCode::InternalServerError)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": e.to_string()}))?)
-                .build()),
-    }
-}
-
-// POST /space/{space_name}/version/{version_id}/vector
-#[utoipa::path(
-    post,
-    path = "/space/{space_name}/version/{version_id}/vector",
-    request_body = VectorRequest,
-    responses(
-        (status = 200, description = "Vector added to version successfully", body = VectorResponse),
-        (status = 403, description = "Forbidden", body = VectorErrorResponse)
-    )
-)]
-pub async fn vector_with_version(mut req: Request<Arc<App>>) -> tide::Result {
-    if !check_write_permission(&req).await? {
-        return Ok(
-            Response::builder(StatusCode::Forbidden)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": "Forbidden"}))?)
-                .build());
-    }
-
-    let space_name = req.param("space_name").unwrap_or("default").to_string();
-    let version_id = req.param("version_id").unwrap_or("0").to_string();
-
-    let body: Value = req.body_json().await?;
-    let wrapped_body = json!({
-        "request": {
-            "command": "vector_with_version",
-            "space_name": space_name,
-            "version_id": version_id,
-            "value": body
-        }
-    });
-    let raft_req = RaftRequest::Set {
-        key: "vector_with_version".to_string(),
-        value: serde_json::to_string(&wrapped_body)?,
-    };
-
-    // Send a write request to the Raft client
-    let res = req.state().raft.client_write(raft_req).await;
-
-    // Handle response
-    match res {
-        Ok(_) => Ok(
-            Response::builder(StatusCode::Ok)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"result": "success"}))?)
-                .build()),
-        Err(e) => Ok(
-            Response::builder(StatusCode::InternalServerError)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": e.to_string()}))?)
-                .build()),
-    }
-}
-
-// GET /space/{space_name}/version/{version_id}/vectors?start=0&limit=10&filter=
-#[utoipa::path(
-    get,
-    path = "/space/{space_name}/version/{version_id}/vectors",
-    params(
-        ("start" = i32, Path, description = "Starting index of vectors"),
-        ("limit" = i32, Path, description = "Maximum number of vectors to retrieve"),
-        ("filter" = String, Query, description = "Filter to apply on vectors")
-    ),
-    responses(
-        (status = 200, description = "Vectors retrieved successfully", body = GetVectorsResponse),
-        (status = 403, description = "Forbidden", body = VectorErrorResponse),
-        (status = 404, description = "Vectors not found", body = VectorErrorResponse)
-    )
-)]
-pub async fn get_vectors_by_version_id(req: Request<Arc<App>>) -> tide::Result {
-    if !check_read_permission(&req).await? {
-        return Ok(
-            Response::builder(StatusCode::Forbidden)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": "Forbidden"}))?)
-                .build());
-    }
-
-    let space_name = req.param("space_name").unwrap_or("default").to_string();
-    let version_id: i32 = req.param("version_id").unwrap_or("0").parse().unwrap_or(0);
-    let start: i32 = req.param("start").unwrap_or("0").parse().unwrap_or(0);
-    let limit: i32 = req.param("limit").unwrap_or("10").parse().unwrap_or(0);
-    let filter = req.query::<QueryParams>()?.filter.unwrap_or_default();
-
-    let bo = req.state().atinyvectors_bo.clone();
-
-    let result = bo.vector.get_vectors_by_version_id(
-        space_name.as_str(), version_id, start, limit, filter.as_str());
-
-    match result {
-        Ok(vectors) => Ok(
-            Response::builder(StatusCode::Ok)
-                .header("Content-Type", "application/json")
-                .body(Body::from_string(vectors)).build()),
-        Err(e) => Ok(
-            Response::builder(StatusCode::NotFound).body(Body::from_string(e)).build()),
-    }
-}
-
-// GET /space/{space_name}/vectors?start=0&limit=10&filter=
-#[utoipa::path(
-    get,
-    path = "/space/{space_name}/vectors",
-    params(
-        ("start" = i32, Path, description = "Starting index of vectors"),
-        ("limit" = i32, Path, description = "Maximum number of vectors to retrieve"),
-        ("filter" = String, Query, description = "Filter to apply on vectors")
-    ),
-    responses(
-        (status = 200, description = "Vectors retrieved successfully", body = GetVectorsResponse),
-        (status = 403, description = "Forbidden", body = VectorErrorResponse),
-        (status = 404, description = "Vectors not found", body = VectorErrorResponse)
-    )
-)]
-pub async fn get_vectors_by_default_version(req: Request<Arc<App>>) -> tide::Result {
-    if !check_read_permission(&req).await? {
-        return Ok(
-            Response::builder(StatusCode::Forbidden)
-                .header("Content-Type", "application/json")
-                .body(Body::from_json(&json!({"error": "Forbidden"}))?)
-                .build());
-    }
-
-    let space_name = req.param("space_name").unwrap_or("default").to_string();
-    let version_id: i32 = 0; // default unique version
-    let query: QueryParams = req.query()?;
-    let start: i32 = query.start.unwrap_or(0) as i32;
-    let limit: i32 = query.limit.unwrap_or(10) as i32;
-    let filter = query.filter.unwrap_or_default();
-
-    let bo = req.state().atinyvectors_bo.clone();
-
-    let result = bo.vector.get_vectors_by_version_id(
-        space_name.as_str(), version_id, start, limit, filter.as_str());
-
-    match result {
-        Ok(vectors) => Ok(
-            Response::builder(StatusCode::Ok)
-                .header("Content-Type", "application/json")
-                .body(Body::from_string(vectors)).build()),
-        Err(e) => Ok(
-            Response::builder(StatusCode::NotFound).body(Body::from_string(e)).build()),
-    }
+use std::sync::Arc;
+use tide::{Body, Request, Response, StatusCode};
+use serde_json::Value;
+use serde_json::json;
+use serde::Deserialize;
+
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::store::Request as RaftRequest;
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+use crate::service::handlers::raft_response::write_response;
+use crate::service::handlers::read_consistency::{ensure_linearizable, ensure_read_consistency, Consistency};
+use crate::service::handlers::search_handler::require_index;
+use crate::atinyvectors::atinyvectors_bo::ATinyVectorsBO;
+use crate::atinyvectors::merge_patch::merge_patch;
+
+use crate::service::handlers::dto::vector_dto::{
+    VectorData, VectorRequest, VectorResponse, VectorErrorResponse, GetVectorsResponse, VectorDataResponse,
+    PatchVectorRequest};
+
+#[derive(Deserialize)]
+struct QueryParams {
+    start: Option<usize>,
+    limit: Option<usize>,
+    filter: Option<String>,
+}
+    
+// POST /space/{space_name}/vector
+#[utoipa::path(
+    post,
+    path = "/space/{space_name}/vector",
+    request_body = VectorRequest,
+    responses(
+        (status = 200, description = "Vector created successfully", body = VectorResponse),
+        (status = 400, description = "Invalid vectors, e.g. wrong dimension", body = VectorErrorResponse),
+        (status = 403, description = "Forbidden", body = VectorErrorResponse),
+        (status = 404, description = "Space, version or vector index not found", body = VectorErrorResponse),
+        (status = 501, description = "The vector index is not the default index of the version", body = VectorErrorResponse)
+    )
+)]
+pub async fn vector(mut req: Request<Arc<App>>) -> tide::Result {
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+
+    let body: Value = req.body_json().await?;
+    let index_name = req.param("index_name").ok().map(|index_name| index_name.to_string());
+    if let Err(res) = require_index(&req.state().atinyvectors_bo, &space_name, 0, index_name.as_deref()) {
+        return res;
+    }
+    let raft_req = match RaftRequest::command(Command::UpsertVectors {
+        space_name,
+        version_id: 0,
+        value: body,
+        index_name,
+    }) {
+        Ok(raft_req) => raft_req,
+        Err(e) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": e}))?)
+                .build()),
+    };
+
+    // Send a write request to the Raft client
+    let res = req.state().raft.client_write(raft_req).await;
+
+    // Handle response
+    write_response(res)
+}
+
+// POST /space/{space_name}/version/{version_id}/vector
+#[utoipa::path(
+    post,
+    path = "/space/{space_name}/version/{version_id}/vector",
+    request_body = VectorRequest,
+    responses(
+        (status = 200, description = "Vector added to version successfully", body = VectorResponse),
+        (status = 400, description = "Invalid vectors, e.g. wrong dimension", body = VectorErrorResponse),
+        (status = 403, description = "Forbidden", body = VectorErrorResponse),
+        (status = 404, description = "Space, version or vector index not found", body = VectorErrorResponse),
+        (status = 501, description = "The vector index is not the default index of the version", body = VectorErrorResponse)
+    )
+)]
+pub async fn vector_with_version(mut req: Request<Arc<App>>) -> tide::Result {
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id: i32 = match req.param("version_id").unwrap_or("0").parse() {
+        Ok(version_id) => version_id,
+        Err(_) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": "Invalid 'version_id', it should be an integer"}))?)
+                .build()),
+    };
+
+    let body: Value = req.body_json().await?;
+    let index_name = req.param("index_name").ok().map(|index_name| index_name.to_string());
+    if let Err(res) = require_index(&req.state().atinyvectors_bo, &space_name, version_id, index_name.as_deref()) {
+        return res;
+    }
+    let raft_req = match RaftRequest::command(Command::UpsertVectors {
+        space_name,
+        version_id,
+        value: body,
+        index_name,
+    }) {
+        Ok(raft_req) => raft_req,
+        Err(e) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": e}))?)
+                .build()),
+    };
+
+    // Send a write request to the Raft client
+    let res = req.state().raft.client_write(raft_req).await;
+
+    // Handle response
+    write_response(res)
+}
+
+// GET /space/{space_name}/version/{version_id}/vector/{vector_id}
+#[utoipa::path(
+    get,
+    path = "/space/{space_name}/version/{version_id}/vector/{vector_id}",
+    params(
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "Vector retrieved successfully", body = VectorDataResponse),
+        (status = 403, description = "Forbidden", body = VectorErrorResponse),
+        (status = 404, description = "Vector not found", body = VectorErrorResponse)
+    )
+)]
+pub async fn get_vector(req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let (version_id, vector_id) = match vector_path_ids(&req) {
+        Ok(ids) => ids,
+        Err(e) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": e}))?)
+                .build()),
+    };
+
+    let bo = req.state().atinyvectors_bo.clone();
+    match bo.vector.get_vector_by_id(&space_name, version_id, vector_id) {
+        Some(vector) => Ok(
+            Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_string(vector)).build()),
+        None => Ok(
+            Response::builder(StatusCode::NotFound)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": format!("Vector not found: {}", vector_id)}))?)
+                .build()),
+    }
+}
+
+// PATCH /space/{space_name}/version/{version_id}/vector/{vector_id}
+// The engine has no metadata-only update: the vector is upserted again with its stored data, which
+// re-indexes it. The lookup and the merge happen here on the leader, so the replicated command is a
+// plain upsert and applying it does not list the version. Patches are serialized on the leader and
+// read after everything committed before them, so concurrent patches of one vector do not lose
+// each other's changes.
+#[utoipa::path(
+    patch,
+    path = "/space/{space_name}/version/{version_id}/vector/{vector_id}",
+    request_body = PatchVectorRequest,
+    responses(
+        (status = 200, description = "Metadata updated, the vector was upserted again and its data re-indexed", body = VectorResponse),
+        (status = 400, description = "The patch changes more than the metadata", body = VectorErrorResponse),
+        (status = 403, description = "Forbidden", body = VectorErrorResponse),
+        (status = 404, description = "Space, version, vector index or vector not found", body = VectorErrorResponse),
+        (status = 501, description = "The vector index is not the default index of the version", body = VectorErrorResponse)
+    )
+)]
+pub async fn patch_vector(mut req: Request<Arc<App>>) -> tide::Result {
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let (version_id, vector_id) = match vector_path_ids(&req) {
+        Ok(ids) => ids,
+        Err(e) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": e}))?)
+                .build()),
+    };
+
+    // a merge-patch of the vector document that may only touch its metadata, and the index the
+    // vector was upserted into
+    let body: Value = req.body_json().await?;
+    let (patch, index_name) = match patch_fields(&body) {
+        Ok(fields) => fields,
+        Err(e) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": e}))?)
+                .build()),
+    };
+
+    let _guard = PATCH_LOCK.lock().await;
+    if let Err(res) = ensure_linearizable(req.state()).await {
+        return res;
+    }
+
+    let bo = req.state().atinyvectors_bo.clone();
+    if let Err(res) = require_index(&bo, &space_name, version_id, index_name.as_deref()) {
+        return res;
+    }
+    let vector = match bo.vector.get_vector_by_id(&space_name, version_id, vector_id)
+        .and_then(|stored| serde_json::from_str::<Value>(&stored).ok())
+    {
+        Some(vector) if vector.is_object() => vector,
+        _ => return Ok(
+            Response::builder(StatusCode::NotFound)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": format!("Vector not found: {}", vector_id)}))?)
+                .build()),
+    };
+
+    let raft_req = match RaftRequest::command(Command::UpsertVectors {
+        space_name,
+        version_id,
+        value: json!({"vectors": [patched_vector(vector, vector_id, &patch)]}),
+        index_name,
+    }) {
+        Ok(raft_req) => raft_req,
+        Err(e) => return Ok(
+            Response::builder(StatusCode::BadRequest)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({"error": e}))?)
+                .build()),
+    };
+
+    let res = req.state().raft.client_write(raft_req).await;
+    write_response(res)
+}
+
+/// Serializes the read-merge-write of metadata patches on this node.
+static PATCH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The metadata patch of a PATCH body and the optional index it names.
+pub fn patch_fields(body: &Value) -> Result<(Value, Option<String>), String> {
+    let fields = body.as_object().ok_or_else(|| "Only 'metadata' can be patched".to_string())?;
+    if !fields.contains_key("metadata") || fields.keys().any(|key| key != "metadata" && key != "index_name") {
+        return Err("Only 'metadata' can be patched".to_string());
+    }
+    let index_name = match fields.get("index_name") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(name)) if !name.is_empty() => Some(name.clone()),
+        Some(_) => return Err("Invalid 'index_name', it should be a non-empty string".to_string()),
+    };
+    Ok((fields["metadata"].clone(), index_name))
+}
+
+/// `vector` as stored with `patch` merged into its metadata; its data is kept as is.
+pub fn patched_vector(mut vector: Value, vector_id: u64, patch: &Value) -> Value {
+    if let Some(fields) = vector.as_object_mut() {
+        let metadata = fields.entry("metadata".to_string()).or_insert(Value::Null);
+        merge_patch(metadata, patch);
+        fields.insert("id".to_string(), Value::from(vector_id));
+    }
+    vector
+}
+
+fn vector_path_ids(req: &Request<Arc<App>>) -> Result<(i32, u64), String> {
+    let version_id = req.param("version_id").unwrap_or("0").parse()
+        .map_err(|_| "Invalid 'version_id', it should be an integer".to_string())?;
+    let vector_id = req.param("vector_id").unwrap_or("").parse()
+        .map_err(|_| "Invalid 'vector_id', it should be an integer".to_string())?;
+    Ok((version_id, vector_id))
+}
+
+// GET /space/{space_name}/version/{version_id}/vectors?start=0&limit=10&filter=
+#[utoipa::path(
+    get,
+    path = "/space/{space_name}/version/{version_id}/vectors",
+    params(
+        ("start" = i32, Query, description = "Starting index of vectors"),
+        ("limit" = i32, Query, description = "Maximum number of vectors to retrieve"),
+        ("filter" = String, Query, description = "Filter to apply on vectors"),
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "Vectors retrieved successfully", body = GetVectorsResponse),
+        (status = 403, description = "Forbidden", body = VectorErrorResponse),
+        (status = 404, description = "Vectors not found", body = VectorErrorResponse)
+    )
+)]
+pub async fn get_vectors_by_version_id(req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id: i32 = req.param("version_id").unwrap_or("0").parse().unwrap_or(0);
+    let query: QueryParams = req.query()?;
+    let start: i32 = query.start.unwrap_or(0) as i32;
+    let limit: i32 = query.limit.unwrap_or(10) as i32;
+    let filter = query.filter.unwrap_or_default();
+
+    let bo = req.state().atinyvectors_bo.clone();
+    // every vector the engine lists is in the default index, the only one it can address
+    if let Err(res) = require_index(&bo, &space_name, version_id, req.param("index_name").ok()) {
+        return res;
+    }
+
+    let result = bo.vector.get_vectors_by_version_id(
+        space_name.as_str(), version_id, start, limit, filter.as_str());
+
+    match result {
+        Ok(vectors) => Ok(
+            Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_string(vectors)).build()),
+        Err(e) => Ok(
+            Response::builder(StatusCode::NotFound).body(Body::from_string(e)).build()),
+    }
+}
+
+// GET /space/{space_name}/vectors?start=0&limit=10&filter=
+#[utoipa::path(
+    get,
+    path = "/space/{space_name}/vectors",
+    params(
+        ("start" = i32, Query, description = "Starting index of vectors"),
+        ("limit" = i32, Query, description = "Maximum number of vectors to retrieve"),
+        ("filter" = String, Query, description = "Filter to apply on vectors"),
+        ("consistency" = Option<Consistency>, Query, description = "Read consistency: local (default), leader or linearizable")
+    ),
+    responses(
+        (status = 200, description = "Vectors retrieved successfully", body = GetVectorsResponse),
+        (status = 403, description = "Forbidden", body = VectorErrorResponse),
+        (status = 404, description = "Vectors not found", body = VectorErrorResponse)
+    )
+)]
+pub async fn get_vectors_by_default_version(req: Request<Arc<App>>) -> tide::Result {
+    if let Err(res) = ensure_read_consistency(&req).await {
+        return res;
+    }
+
+    let space_name = req.param("space_name").unwrap_or("default").to_string();
+    let version_id: i32 = 0; // default unique version
+    let query: QueryParams = req.query()?;
+    let start: i32 = query.start.unwrap_or(0) as i32;
+    let limit: i32 = query.limit.unwrap_or(10) as i32;
+    let filter = query.filter.unwrap_or_default();
+
+    let bo = req.state().atinyvectors_bo.clone();
+
+    let result = bo.vector.get_vectors_by_version_id(
+        space_name.as_str(), version_id, start, limit, filter.as_str());
+
+    match result {
+        Ok(vectors) => Ok(
+            Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_string(vectors)).build()),
+        Err(e) => Ok(
+            Response::builder(StatusCode::NotFound).body(Body::from_string(e)).build()),
+    }
 }
\ No newline at end of file
@@ -1,2 +1,5 @@
-pub mod handlers;
-pub mod routes;
\ No newline at end of file
+pub mod auth;
+pub mod forward;
+pub mod handlers;
+pub mod routes;
+pub mod sharding;
\ No newline at end of file
@@ -0,0 +1,932 @@
+//! Horizontal sharding of vectors over several Raft groups.
+//!
+//! With `Config::shard_groups()` set, every group listed there is a Raft group of its own
+//! holding the vectors whose id hashes to its shard, see [`shard_of`]. A node serves every group
+//! listing its advertised API address, each with its own Raft instance, log and state machine,
+//! all on the same HTTP and RPC ports: Raft RPCs name their group, and HTTP requests pick it
+//! with [`SHARD_HEADER`], see [`ShardDispatch`].
+//!
+//! Any node routes the requests it receives: upserts are split by id and each part is written
+//! by its group, searches, reranks and listings go to every group and their answers are merged,
+//! and space and version changes are applied to every group so that all of them know the same
+//! spaces. Requests between groups are signed with the cluster secret, the node receiving the
+//! client request has authorized it already. Key-value storage, snapshots and tokens stay per
+//! group, requests without [`SHARD_HEADER`] use the first group a node serves.
+//!
+//! The atinyvectors engine keeps a single database per process, which the groups of a node
+//! share. See [`ShardMap::shares_engine`] for what this means for their snapshots.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
+use serde_json::json;
+use serde_json::Value;
+use tide::http::Method;
+use tide::http::Url;
+use tide::Middleware;
+use tide::Next;
+use tide::{Body, Endpoint, Request, Response, StatusCode};
+
+use crate::raft_cluster::app::App;
+use crate::raft_cluster::cluster_auth;
+use crate::raft_cluster::tls;
+use crate::raft_cluster::Server;
+use crate::service::forward;
+use crate::service::handlers::search_handler::{batch_queries, top_k};
+
+/// Set on requests that were already routed, so the receiving node serves them from the group
+/// named in [`SHARD_HEADER`] only.
+pub const SHARD_LOCAL_HEADER: &str = "X-ATV-Shard-Local";
+
+/// Shard group a request is meant for, an index into `Config::shard_groups()`.
+pub const SHARD_HEADER: &str = "X-ATV-Shard";
+
+/// Set on the copies of a space or version change that [`broadcast`] sends to the other groups.
+pub const SHARD_COPY_HEADER: &str = "X-ATV-Shard-Copy";
+
+/// Headers that are set anew for every hop to another group. The client's credentials stay
+/// behind, the other group accepts the request by the cluster secret.
+const ROUTING_HEADERS: &[&str] = &[
+    "authorization",
+    "x-atv-cluster-secret",
+    "x-atv-shard",
+    "x-atv-shard-local",
+    "x-atv-shard-copy",
+];
+
+static SHARD_MAP: Lazy<Option<ShardMap>> = Lazy::new(ShardMap::from_config);
+
+/// HTTP servers of the groups this node serves, keyed by shard.
+static LOCAL_GROUPS: OnceCell<BTreeMap<usize, Server>> = OnceCell::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardMap {
+    /// Shards of the groups this node serves, in ascending order.
+    pub local: Vec<usize>,
+    /// API addresses of the nodes of every group, indexed by shard.
+    pub groups: Vec<Vec<String>>,
+}
+
+impl ShardMap {
+    /// `None` unless at least two groups are configured.
+    pub fn from_config() -> Option<ShardMap> {
+        let groups = crate::Config::shard_groups();
+        if groups.len() < 2 {
+            return None;
+        }
+        Some(ShardMap::new(groups, &crate::Config::advertise_http_addr()))
+    }
+
+    /// Map of `groups` as seen by the node with the API address `api_addr`.
+    pub fn new(groups: Vec<Vec<String>>, api_addr: &str) -> ShardMap {
+        let local = (0..groups.len())
+            .filter(|shard| groups[*shard].iter().any(|addr| same_addr(addr, api_addr)))
+            .collect();
+        ShardMap { local, groups }
+    }
+
+    /// Whether a node of `shard` serves another group too. Such groups share the engine of
+    /// that node, and a snapshot of the engine cannot be installed for one of them without
+    /// overwriting the vectors of the others, so their snapshots carry a journal of their own
+    /// writes instead, see `shard_journal`. Every node computes the same answer from the
+    /// configuration.
+    pub fn shares_engine(&self, shard: usize) -> bool {
+        self.groups[shard].iter().any(|addr| {
+            self.groups
+                .iter()
+                .enumerate()
+                .any(|(other, nodes)| other != shard && nodes.iter().any(|node| same_addr(node, addr)))
+        })
+    }
+}
+
+fn same_addr(a: &str, b: &str) -> bool {
+    let strip = |addr: &str| -> String {
+        addr.trim_start_matches("http://").trim_start_matches("https://").trim_end_matches('/').to_string()
+    };
+    strip(a) == strip(b)
+}
+
+pub fn shard_map() -> Option<&'static ShardMap> {
+    SHARD_MAP.as_ref()
+}
+
+/// Registers the servers of the groups this node serves, once at startup.
+pub fn serve_groups(servers: BTreeMap<usize, Server>) {
+    if LOCAL_GROUPS.set(servers).is_err() {
+        tracing::warn!("The shard groups of this node are registered already");
+    }
+}
+
+/// Shard map and the group serving `req`, `None` without sharding or once the request was
+/// routed.
+fn routing(req: &Request<Arc<App>>) -> Option<(&'static ShardMap, usize)> {
+    let shards = shard_map()?;
+    if req.header(SHARD_LOCAL_HEADER).is_some() {
+        return None;
+    }
+    Some((shards, req.state().shard?))
+}
+
+/// Hands a request naming another group in [`SHARD_HEADER`] to the server of that group. Every
+/// group a node serves has a server of its own, the one listening serves requests without the
+/// header.
+pub struct ShardDispatch;
+
+#[tide::utils::async_trait]
+impl Middleware<Arc<App>> for ShardDispatch {
+    async fn handle(&self, req: Request<Arc<App>>, next: Next<'_, Arc<App>>) -> tide::Result {
+        let shard = match req.header(SHARD_HEADER).and_then(|header| header.get(0)) {
+            Some(value) => value.as_str().trim().parse::<usize>().ok(),
+            None => return Ok(next.run(req).await),
+        };
+        if shard.is_some() && shard == req.state().shard {
+            return Ok(next.run(req).await);
+        }
+
+        let server = match shard.and_then(|shard| LOCAL_GROUPS.get()?.get(&shard)) {
+            Some(server) => server.clone(),
+            None => {
+                return Ok(Response::builder(StatusCode::MisdirectedRequest)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from_json(&json!({"error": "This node does not serve the requested shard"}))?)
+                    .build())
+            }
+        };
+        let req: tide::http::Request = req.into();
+        let res: Response = server.respond(req).await?;
+        Ok(res)
+    }
+}
+
+/// Shard of the vector `id` among `shards`. FNV-1a of the id, so that every node and every build
+/// agree on it.
+pub fn shard_of(id: u64, shards: usize) -> usize {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in id.to_le_bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    (hash % shards as u64) as usize
+}
+
+/// Splits an upsert body into one body per shard, keeping the fields other than `vectors`.
+/// Shards that get no vector are `None`.
+pub fn split_vectors(body: &Value, shards: usize) -> Result<Vec<Option<Value>>, String> {
+    let vectors = body.get("vectors").and_then(Value::as_array)
+        .ok_or_else(|| "Missing 'vectors' array".to_string())?;
+
+    let mut parts: Vec<Vec<Value>> = vec![Vec::new(); shards];
+    for (i, vector) in vectors.iter().enumerate() {
+        let id = vector.get("id").and_then(Value::as_u64)
+            .ok_or_else(|| format!("vectors[{}] has no numeric 'id'", i))?;
+        parts[shard_of(id, shards)].push(vector.clone());
+    }
+
+    Ok(parts
+        .into_iter()
+        .map(|vectors| {
+            (!vectors.is_empty()).then(|| {
+                let mut part = body.clone();
+                part["vectors"] = Value::Array(vectors);
+                part
+            })
+        })
+        .collect())
+}
+
+/// Merges the hits of every shard into the `k` closest ones. The engine reports a smaller
+/// `distance` for a closer vector with every metric. The groups of a node share its engine, so
+/// its vectors show up in the answer of each of them; a vector is only kept once.
+pub fn merge_top_k(results: Vec<Vec<Value>>, k: usize) -> Vec<Value> {
+    let distance = |hit: &Value| hit.get("distance").and_then(Value::as_f64).unwrap_or(f64::INFINITY);
+    let id = |hit: &Value| hit.get("label").or_else(|| hit.get("id")).and_then(Value::as_u64);
+
+    let mut hits: Vec<Value> = results.into_iter().flatten().collect();
+    hits.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+    let mut seen = BTreeSet::new();
+    hits.retain(|hit| id(hit).map_or(true, |id| seen.insert(id)));
+    hits.truncate(k);
+    hits
+}
+
+/// Merges the reranked hits of every shard into the `k` with the highest `bm25_score`. Each
+/// group scores against the documents it holds, so scores of different groups are comparable
+/// only as far as their term statistics are alike. A vector is only kept once.
+pub fn merge_reranked(results: Vec<Vec<Value>>, k: usize) -> Vec<Value> {
+    let score = |hit: &Value| hit.get("bm25_score").and_then(Value::as_f64).unwrap_or(f64::NEG_INFINITY);
+    let id = |hit: &Value| hit.get("id").or_else(|| hit.get("label")).and_then(Value::as_u64);
+
+    let mut hits: Vec<Value> = results.into_iter().flatten().collect();
+    hits.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    let mut seen = BTreeSet::new();
+    hits.retain(|hit| id(hit).map_or(true, |id| seen.insert(id)));
+    hits.truncate(k);
+    hits
+}
+
+/// Merges the listings of every shard, answers of `(shard, body)`, into the page of `limit`
+/// vectors from `start`, ordered by id. A vector is taken from the answer of the group it
+/// belongs to only, since the groups of a node share its engine and list each other's vectors.
+/// `total_count` adds up the counts of the groups, which count such shared vectors once per
+/// group.
+pub fn merge_listings(listings: Vec<(usize, Value)>, groups: usize, start: usize, limit: usize) -> Value {
+    let mut total_count = 0;
+    let mut vectors = BTreeMap::new();
+    for (shard, listing) in listings {
+        total_count += listing.get("total_count").and_then(Value::as_u64).unwrap_or(0);
+        for vector in listing.get("vectors").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(id) = vector.get("id").and_then(Value::as_u64) {
+                if shard_of(id, groups) == shard {
+                    vectors.insert(id, vector.clone());
+                }
+            }
+        }
+    }
+
+    let page: Vec<Value> = vectors.into_values().skip(start).take(limit).collect();
+    json!({"vectors": page, "total_count": total_count})
+}
+
+/// Answer of one shard group.
+struct ShardAnswer {
+    shard: usize,
+    status: u16,
+    body: Value,
+}
+
+impl ShardAnswer {
+    fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({"shard": self.shard, "status": self.status, "body": self.body})
+    }
+}
+
+/// Wraps a vector upsert endpoint: the vectors are split by shard, the local part is written by
+/// `endpoint` and the other parts are sent to their groups.
+pub fn shard_vectors<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let (shards, here) = match routing(&req) {
+                Some(routing) => routing,
+                None => return endpoint.call(req).await,
+            };
+
+            let body: Value = req.body_json().await?;
+            let parts = match split_vectors(&body, shards.groups.len()) {
+                Ok(parts) => parts,
+                Err(e) => return Ok(Response::builder(StatusCode::BadRequest)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from_json(&json!({"error": e}))?)
+                    .build()),
+            };
+
+            let app = req.state().clone();
+            let method = req.method();
+            let target = path_and_query(req.url());
+            let headers = forward_headers(&req);
+
+            let mut local_part = None;
+            let mut remote = Vec::new();
+            for (shard, part) in parts.into_iter().enumerate() {
+                match part {
+                    Some(part) if shard == here => local_part = Some(part),
+                    Some(part) => remote.push(send_to_group(
+                        &app, shards, shard, method, &target, headers.clone(), serde_json::to_vec(&part)?,
+                    )),
+                    None => {}
+                }
+            }
+
+            let mut answers = join_all(remote).await;
+            if let Some(part) = local_part {
+                req.set_body(Body::from_json(&part)?);
+                req.insert_header(SHARD_LOCAL_HEADER, "1");
+                answers.push(call_local(endpoint.as_ref(), req, here).await?);
+            }
+
+            let affected: u64 = answers.iter()
+                .filter_map(|answer| answer.body.get("affected").and_then(Value::as_u64))
+                .sum();
+            combined_response(answers, json!({"result": "success", "affected": affected}))
+        }
+    }
+}
+
+/// Wraps a search endpoint: the query runs on every group and the closest hits are returned.
+pub fn fan_out_search<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    fan_out_ranked(endpoint, "Search failed on a shard", merge_top_k)
+}
+
+/// Wraps a rerank endpoint: every group reranks the vectors it holds and the best scored are
+/// returned, see [`merge_reranked`].
+pub fn fan_out_rerank<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    fan_out_ranked(endpoint, "Rerank failed on a shard", merge_reranked)
+}
+
+/// Sends the request to every group and merges the hits they answer with `merge`.
+fn fan_out_ranked<E>(endpoint: E, failure: &'static str, merge: fn(Vec<Vec<Value>>, usize) -> Vec<Value>) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let (shards, here) = match routing(&req) {
+                Some(routing) => routing,
+                None => return endpoint.call(req).await,
+            };
+
+            let body: Value = req.body_json().await?;
+            let k = body.get("top_k").or_else(|| body.get("k")).and_then(Value::as_u64).filter(|k| *k > 0).unwrap_or(10);
+
+            let app = req.state().clone();
+            let method = req.method();
+            let target = path_and_query(req.url());
+            let headers = forward_headers(&req);
+            let bytes = serde_json::to_vec(&body)?;
+
+            let remote = (0..shards.groups.len())
+                .filter(|shard| *shard != here)
+                .map(|shard| send_to_group(&app, shards, shard, method, &target, headers.clone(), bytes.clone()));
+            let mut answers = join_all(remote).await;
+
+            req.set_body(Body::from_json(&body)?);
+            req.insert_header(SHARD_LOCAL_HEADER, "1");
+            answers.push(call_local(endpoint.as_ref(), req, here).await?);
+
+            if let Some(failed) = answers.iter().find(|answer| !answer.is_success() || !answer.body.is_array()) {
+                return shard_error(failed.status, failure, &answers);
+            }
+
+            let hits = answers
+                .into_iter()
+                .map(|answer| match answer.body {
+                    Value::Array(hits) => hits,
+                    _ => Vec::new(),
+                })
+                .collect();
+            Ok(Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&Value::Array(merge(hits, k as usize)))?)
+                .build())
+        }
+    }
+}
+
+/// Wraps a listing of the vectors of a version: every group lists its first `start + limit`
+/// vectors and the page is cut from their merge, see [`merge_listings`].
+pub fn fan_out_listing<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let (shards, here) = match routing(&req) {
+                Some(routing) => routing,
+                None => return endpoint.call(req).await,
+            };
+
+            let number = |name: &str| {
+                req.url().query_pairs().find(|(key, _)| key == name).and_then(|(_, value)| value.parse::<usize>().ok())
+            };
+            let start = number("start").unwrap_or(0);
+            let limit = number("limit").unwrap_or(10);
+
+            // every group lists from its first vector, the page is only known once merged
+            let mut url = req.url().clone();
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .filter(|(key, _)| key != "start" && key != "limit")
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .chain([("start".to_string(), "0".to_string()), ("limit".to_string(), (start + limit).to_string())])
+                .collect();
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+
+            let app = req.state().clone();
+            let target = path_and_query(&url);
+            let headers = forward_headers(&req);
+
+            let remote = (0..shards.groups.len())
+                .filter(|shard| *shard != here)
+                .map(|shard| send_to_group(&app, shards, shard, Method::Get, &target, headers.clone(), Vec::new()));
+            let mut answers = join_all(remote).await;
+
+            let http_req: &mut tide::http::Request = req.as_mut();
+            *http_req.url_mut() = url;
+            req.insert_header(SHARD_LOCAL_HEADER, "1");
+            answers.push(call_local(endpoint.as_ref(), req, here).await?);
+
+            if let Some(failed) = answers.iter().find(|answer| !answer.is_success()) {
+                return shard_error(failed.status, "Listing failed on a shard", &answers);
+            }
+
+            let listings = answers.into_iter().map(|answer| (answer.shard, answer.body)).collect();
+            Ok(Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&merge_listings(listings, shards.groups.len(), start, limit))?)
+                .build())
+        }
+    }
+}
+
+/// Wraps a batch search endpoint: the batch runs on every group and the hits of each query are
+/// merged like those of a single search.
+pub fn fan_out_search_batch<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let (shards, here) = match routing(&req) {
+                Some(routing) => routing,
+                None => return endpoint.call(req).await,
+            };
+
+            let body: Value = req.body_json().await?;
+            let ks: Vec<usize> = match batch_queries(&body) {
+                Ok(queries) => queries.iter().map(top_k).collect(),
+                Err(e) => return Ok(Response::builder(StatusCode::BadRequest)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from_json(&json!({"error": e}))?)
+                    .build()),
+            };
+
+            let app = req.state().clone();
+            let method = req.method();
+            let target = path_and_query(req.url());
+            let headers = forward_headers(&req);
+            let bytes = serde_json::to_vec(&body)?;
+
+            let remote = (0..shards.groups.len())
+                .filter(|shard| *shard != here)
+                .map(|shard| send_to_group(&app, shards, shard, method, &target, headers.clone(), bytes.clone()));
+            let mut answers = join_all(remote).await;
+
+            req.set_body(Body::from_json(&body)?);
+            req.insert_header(SHARD_LOCAL_HEADER, "1");
+            answers.push(call_local(endpoint.as_ref(), req, here).await?);
+
+            if let Some(failed) = answers.iter().find(|answer| !answer.is_success() || !answer.body.is_array()) {
+                return shard_error(failed.status, "Batch search failed on a shard", &answers);
+            }
+
+            let results = answers
+                .into_iter()
+                .map(|answer| match answer.body {
+                    Value::Array(results) => results,
+                    _ => Vec::new(),
+                })
+                .collect();
+            Ok(Response::builder(StatusCode::Ok)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&Value::Array(merge_batch(results, &ks)))?)
+                .build())
+        }
+    }
+}
+
+/// Merges the batch results of every shard query by query. A query that failed on a shard
+/// reports that error, as its hits would be incomplete.
+pub fn merge_batch(shard_results: Vec<Vec<Value>>, ks: &[usize]) -> Vec<Value> {
+    ks.iter()
+        .enumerate()
+        .map(|(i, k)| {
+            let mut hits = Vec::new();
+            for results in &shard_results {
+                match results.get(i) {
+                    Some(result) => match result.get("results").and_then(Value::as_array) {
+                        Some(results) => hits.push(results.clone()),
+                        None => return result.clone(),
+                    },
+                    None => return json!({"error": "Missing result from a shard"}),
+                }
+            }
+            json!({"results": merge_top_k(hits, *k)})
+        })
+        .collect()
+}
+
+/// Wraps an endpoint about the single vector `:vector_id`: the request is handled by the group
+/// holding it.
+pub fn route_by_id<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let (shards, here) = match routing(&req) {
+                Some(routing) => routing,
+                None => return endpoint.call(req).await,
+            };
+            let shard = match req.param("vector_id").ok().and_then(|id| id.parse::<u64>().ok()) {
+                Some(id) => shard_of(id, shards.groups.len()),
+                None => return endpoint.call(req).await,
+            };
+            if shard == here {
+                req.insert_header(SHARD_LOCAL_HEADER, "1");
+                return endpoint.call(req).await;
+            }
+
+            let bytes = req.take_body().into_bytes().await?;
+            let app = req.state().clone();
+            let answer = send_to_group(&app, shards, shard, req.method(), &path_and_query(req.url()), forward_headers(&req), bytes).await;
+            Ok(Response::builder(answer.status)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&answer.body)?)
+                .build())
+        }
+    }
+}
+
+/// Reads of a handler that combines vectors of several groups, such as recommend: a vector is
+/// fetched from the group holding it and a search runs on every group. `None` from [`ShardReads::of`]
+/// without sharding, the handler then reads its own engine only.
+pub struct ShardReads {
+    shards: &'static ShardMap,
+    here: usize,
+    app: Arc<App>,
+    headers: Vec<(String, String)>,
+    query: Option<String>,
+}
+
+impl ShardReads {
+    pub fn of(req: &Request<Arc<App>>) -> Option<ShardReads> {
+        let (shards, here) = routing(req)?;
+        Some(ShardReads {
+            shards,
+            here,
+            app: req.state().clone(),
+            headers: forward_headers(req),
+            query: req.url().query().map(str::to_string),
+        })
+    }
+
+    /// Whether the vector `id` belongs to the group serving the request.
+    pub fn is_local(&self, id: u64) -> bool {
+        shard_of(id, self.shards.groups.len()) == self.here
+    }
+
+    /// The vector `id` as the group holding it answers a get of it, or the status and error of
+    /// that answer.
+    pub async fn get_vector(&self, space_name: &str, version_id: i32, id: u64) -> Result<Value, (u16, String)> {
+        let shard = shard_of(id, self.shards.groups.len());
+        let path = format!("/api/space/{}/version/{}/vector/{}", space_name, version_id, id);
+        let answer = self.send(shard, Method::Get, &path, Vec::new()).await;
+        if answer.is_success() {
+            Ok(answer.body)
+        } else {
+            Err((answer.status, error_of(&answer)))
+        }
+    }
+
+    /// Hits of `query` on every group but the one serving the request, one list per group.
+    pub async fn search_others(&self, space_name: &str, version_id: i32, query: &Value) -> Result<Vec<Vec<Value>>, String> {
+        let path = format!("/api/space/{}/version/{}/search", space_name, version_id);
+        let bytes = serde_json::to_vec(query).map_err(|e| e.to_string())?;
+        let searches = (0..self.shards.groups.len())
+            .filter(|shard| *shard != self.here)
+            .map(|shard| self.send(shard, Method::Post, &path, bytes.clone()));
+
+        join_all(searches)
+            .await
+            .into_iter()
+            .map(|answer| match answer.body {
+                Value::Array(hits) if answer.is_success() => Ok(hits),
+                _ => Err(format!("Search failed on shard {}: {}", answer.shard, error_of(&answer))),
+            })
+            .collect()
+    }
+
+    async fn send(&self, shard: usize, method: Method, path: &str, body: Vec<u8>) -> ShardAnswer {
+        let target = match &self.query {
+            Some(query) => format!("{}?{}", path, query),
+            None => path.to_string(),
+        };
+        send_to_group(&self.app, self.shards, shard, method, &target, self.headers.clone(), body).await
+    }
+}
+
+fn error_of(answer: &ShardAnswer) -> String {
+    match answer.body.get("error").and_then(Value::as_str) {
+        Some(error) => error.to_string(),
+        None => answer.body.to_string(),
+    }
+}
+
+/// Waits before sending a schema change again to the groups that failed it, e.g. during a
+/// leader election.
+const BROADCAST_RETRY_DELAYS: [Duration; 3] = [Duration::from_millis(200), Duration::from_secs(1), Duration::from_secs(3)];
+
+/// Wraps a schema write, e.g. creating a space: once `endpoint` applied it, the same request is
+/// sent to every other group, and sent again to those that failed it. A creation that still
+/// fails on a group is undone on every group, see [`undo_of`]. Other changes
+/// cannot be undone, the answer then lists the groups that applied the change and those that did
+/// not, so that it can be sent again to the latter.
+pub fn broadcast<E>(endpoint: E) -> impl Endpoint<Arc<App>>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let endpoint = Arc::new(endpoint);
+    move |mut req: Request<Arc<App>>| {
+        let endpoint = endpoint.clone();
+        async move {
+            let (shards, here) = match routing(&req) {
+                Some(routing) => routing,
+                None => return endpoint.call(req).await,
+            };
+
+            let bytes = req.take_body().into_bytes().await?;
+            let mut body = Body::from_bytes(bytes.clone());
+            if let Some(mime) = req.content_type() {
+                body.set_mime(mime);
+            }
+            req.set_body(body);
+
+            let app = req.state().clone();
+            let method = req.method();
+            let target = path_and_query(req.url());
+            let undo = undo_of(method, req.url().path(), &parse_body(&bytes));
+            let mut headers = forward_headers(&req);
+            headers.push((SHARD_COPY_HEADER.to_string(), "1".to_string()));
+
+            // The forwarding to the leader of this group copies the header, so the leader does
+            // not broadcast a second time.
+            req.insert_header(SHARD_LOCAL_HEADER, "1");
+            let res = endpoint.call(req).await?;
+            if !res.status().is_success() {
+                return Ok(res);
+            }
+
+            let remote = (0..shards.groups.len())
+                .filter(|shard| *shard != here)
+                .map(|shard| send_to_group(&app, shards, shard, method, &target, headers.clone(), bytes.clone()));
+            let mut answers = join_all(remote).await;
+            for delay in BROADCAST_RETRY_DELAYS {
+                let failed: Vec<usize> = answers.iter().filter(|answer| !copy_applied(answer, method)).map(|answer| answer.shard).collect();
+                if failed.is_empty() {
+                    break;
+                }
+                async_std::task::sleep(delay).await;
+                let retries = failed
+                    .iter()
+                    .map(|shard| send_to_group(&app, shards, *shard, method, &target, headers.clone(), bytes.clone()));
+                for retried in join_all(retries).await {
+                    if let Some(answer) = answers.iter_mut().find(|answer| answer.shard == retried.shard) {
+                        *answer = retried;
+                    }
+                }
+            }
+
+            let failed = match answers.iter().find(|answer| !copy_applied(answer, method)) {
+                Some(failed) => failed,
+                None => return Ok(res),
+            };
+            let applied: Vec<usize> = std::iter::once(here)
+                .chain(answers.iter().filter(|answer| copy_applied(answer, method)).map(|answer| answer.shard))
+                .collect();
+            let not_applied: Vec<usize> = answers.iter().filter(|answer| !copy_applied(answer, method)).map(|answer| answer.shard).collect();
+            let shard_answers: Vec<Value> = answers.iter().map(ShardAnswer::to_json).collect();
+
+            if let Some(undo) = undo {
+                // a group whose answer was lost may have applied it all the same
+                let every_shard: Vec<usize> = (0..shards.groups.len()).collect();
+                let still_applied = undo_on(&app, shards, &every_shard, &undo, &headers).await;
+                tracing::warn!(
+                    "{} {} failed on shards {:?}, undone on shards {:?}, still applied on {:?}",
+                    method, target, not_applied, applied, still_applied
+                );
+                return Ok(Response::builder(failed.status)
+                    .header("Content-Type", "application/json")
+                    .body(Body::from_json(&json!({
+                        "error": if still_applied.is_empty() { "Not applied on every shard, undone" } else { "Not applied on every shard, undone on some only" },
+                        "applied": still_applied,
+                        "not_applied": not_applied,
+                        "shards": shard_answers,
+                    }))?)
+                    .build());
+            }
+
+            tracing::warn!("Applied {} {} on shards {:?} but not on shards {:?}", method, target, applied, not_applied);
+            Ok(Response::builder(failed.status)
+                .header("Content-Type", "application/json")
+                .body(Body::from_json(&json!({
+                    "error": "Applied on some shards only",
+                    "applied": applied,
+                    "not_applied": not_applied,
+                    "shards": shard_answers,
+                }))?)
+                .build())
+        }
+    }
+}
+
+/// Whether a group applied a copy of a schema change, or had it already: a creation sent again
+/// after its answer was lost finds the space or version there, a deletion finds it gone.
+fn copy_applied(answer: &ShardAnswer, method: Method) -> bool {
+    answer.is_success()
+        || (method == Method::Post && answer.status == StatusCode::Conflict as u16)
+        || (method == Method::Delete && answer.status == StatusCode::NotFound as u16)
+}
+
+/// How to take back a schema change that did not reach every group.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Undo {
+    /// Delete the space created, by the API path prefix and the name of the space.
+    Space { prefix: String, space_name: String },
+    /// Delete the version created, found by its name.
+    Version { prefix: String, space_name: String, version_name: String },
+}
+
+/// The undo of a broadcast request to `path` with `body`, for the creation of a space or a
+/// version. Updates and deletions have none, their previous state is gone.
+pub fn undo_of(method: Method, path: &str, body: &Value) -> Option<Undo> {
+    if method != Method::Post {
+        return None;
+    }
+    let name = body.get("name").and_then(Value::as_str).filter(|name| !name.is_empty())?.to_string();
+
+    if let Some(prefix) = path.strip_suffix("/space").filter(|prefix| !prefix.contains("/space")) {
+        return Some(Undo::Space { prefix: prefix.to_string(), space_name: name });
+    }
+    let rest = path.strip_suffix("/version")?;
+    let (prefix, space_name) = rest.rsplit_once("/space/")?;
+    if space_name.is_empty() || space_name.contains('/') {
+        return None;
+    }
+    Some(Undo::Version {
+        prefix: prefix.to_string(),
+        space_name: space_name.to_string(),
+        version_name: name,
+    })
+}
+
+/// Undoes `undo` on the groups in `shards`, returning those it could not be undone on.
+async fn undo_on(app: &App, shard_map: &ShardMap, shards: &[usize], undo: &Undo, headers: &[(String, String)]) -> Vec<usize> {
+    let undo_one = |shard: usize| async move {
+        let target = match undo {
+            Undo::Space { prefix, space_name } => format!("{}/space/{}", prefix, space_name),
+            Undo::Version { prefix, space_name, version_name } => {
+                let lookup = format!("{}/space/{}/version/{}/by-name", prefix, space_name, version_name);
+                let found = send_to_group(app, shard_map, shard, Method::Get, &lookup, headers.to_vec(), Vec::new()).await;
+                match found.body.get("id").and_then(Value::as_u64) {
+                    Some(id) if found.is_success() => format!("{}/space/{}/version/{}", prefix, space_name, id),
+                    _ if found.status == StatusCode::NotFound as u16 => return None,
+                    _ => return Some(shard),
+                }
+            }
+        };
+        let answer = send_to_group(app, shard_map, shard, Method::Delete, &target, headers.to_vec(), Vec::new()).await;
+        (!copy_applied(&answer, Method::Delete)).then_some(shard)
+    };
+    join_all(shards.iter().map(|shard| undo_one(*shard))).await.into_iter().flatten().collect()
+}
+
+async fn call_local<E>(endpoint: &E, req: Request<Arc<App>>, shard: usize) -> tide::Result<ShardAnswer>
+where
+    E: Endpoint<Arc<App>>,
+{
+    let mut res = endpoint.call(req).await?;
+    let status = res.status() as u16;
+    let bytes = res.take_body().into_bytes().await?;
+    Ok(ShardAnswer { shard, status, body: parse_body(&bytes) })
+}
+
+/// Sends the request to the group of `shard`, signed with the cluster secret. A group this node
+/// serves gets it in process, the others from their nodes, tried in turn until one answers. A
+/// follower of that group hands writes on to its leader.
+async fn send_to_group(
+    app: &App,
+    shards: &ShardMap,
+    shard: usize,
+    method: Method,
+    target: &str,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+) -> ShardAnswer {
+    let mut headers = headers;
+    headers.push((SHARD_LOCAL_HEADER.to_string(), "1".to_string()));
+    headers.push((SHARD_HEADER.to_string(), shard.to_string()));
+
+    if let Some(server) = LOCAL_GROUPS.get().and_then(|servers| servers.get(&shard)) {
+        return call_group(server, shard, method, target, headers, body).await;
+    }
+
+    let mut last_error = String::new();
+    for addr in &shards.groups[shard] {
+        let url = format!("{}{}", tls::base_url(addr), target);
+        let builder = match forward::request(method, &url) {
+            Ok(builder) => cluster_auth::sign(builder).body(body.clone()),
+            Err(e) => return ShardAnswer { shard, status: 502, body: json!({"error": e}) },
+        };
+
+        match forward::send(app, builder, headers.clone()).await {
+            Ok(relayed) => return ShardAnswer { shard, status: relayed.status, body: parse_body(&relayed.body) },
+            Err(e) => {
+                tracing::debug!("Shard node {} is not reachable: {}", addr, e);
+                last_error = e;
+            }
+        }
+    }
+    ShardAnswer { shard, status: 502, body: json!({"error": format!("No node of the shard answered: {}", last_error)}) }
+}
+
+/// Serves the request by the server of a group of this node.
+async fn call_group(
+    server: &Server,
+    shard: usize,
+    method: Method,
+    target: &str,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+) -> ShardAnswer {
+    let url = match Url::parse(&format!("http://localhost{}", target)) {
+        Ok(url) => url,
+        Err(e) => return ShardAnswer { shard, status: 500, body: json!({"error": e.to_string()}) },
+    };
+    let mut req = tide::http::Request::new(method, url);
+    for (name, value) in &headers {
+        req.append_header(name.as_str(), value.as_str());
+    }
+    let secret = crate::Config::cluster_secret();
+    if !secret.is_empty() {
+        req.insert_header(cluster_auth::SECRET_HEADER, secret);
+    }
+    req.set_body(body);
+
+    match server.respond::<_, tide::http::Response>(req).await {
+        Ok(mut res) => {
+            let status = res.status() as u16;
+            match res.body_bytes().await {
+                Ok(bytes) => ShardAnswer { shard, status, body: parse_body(&bytes) },
+                Err(e) => ShardAnswer { shard, status: 500, body: json!({"error": e.to_string()}) },
+            }
+        }
+        Err(e) => ShardAnswer { shard, status: e.status() as u16, body: json!({"error": e.to_string()}) },
+    }
+}
+
+fn combined_response(answers: Vec<ShardAnswer>, success: Value) -> tide::Result {
+    if let Some(failed) = answers.iter().find(|answer| !answer.is_success()) {
+        return shard_error(failed.status, "Failed on a shard", &answers);
+    }
+    Ok(Response::builder(StatusCode::Ok)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&success)?)
+        .build())
+}
+
+fn shard_error(status: u16, message: &str, answers: &[ShardAnswer]) -> tide::Result {
+    let shards: Vec<Value> = answers.iter().map(ShardAnswer::to_json).collect();
+    Ok(Response::builder(status)
+        .header("Content-Type", "application/json")
+        .body(Body::from_json(&json!({"error": message, "shards": shards}))?)
+        .build())
+}
+
+fn parse_body(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+fn forward_headers(req: &Request<Arc<App>>) -> Vec<(String, String)> {
+    req.iter()
+        .filter(|(name, _)| {
+            let name = name.as_str().to_lowercase();
+            !forward::is_hop_header(&name) && !ROUTING_HEADERS.contains(&name.as_str())
+        })
+        .flat_map(|(name, values)| values.iter().map(move |v| (name.to_string(), v.to_string())))
+        .collect()
+}
+
+fn path_and_query(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
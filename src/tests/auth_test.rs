@@ -0,0 +1,58 @@
+use crate::service::auth::{token_record, Permissions, Principal, Resource, TokenPrincipal, READ, WRITE};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(space_id: Option<u64>, permissions: Permissions) -> Principal {
+        Principal::Token(TokenPrincipal { id: Some(7), space_id, permissions })
+    }
+
+    #[test]
+    fn test_token_record() {
+        let tokens = r#"[
+            {"id": 1, "space_id": 0, "token": "all", "expire_time_utc": 0},
+            {"id": 2, "space_id": 5, "token": "scoped", "expire_time_utc": 0}
+        ]"#;
+
+        assert_eq!(token_record(tokens, "all"), Some((1, None)));
+        assert_eq!(token_record(tokens, "scoped"), Some((2, Some(5))));
+        assert_eq!(token_record(tokens, "unknown"), None);
+        assert_eq!(token_record("not json", "all"), None);
+    }
+
+    #[test]
+    fn test_permission_levels() {
+        let principal = token(None, Permissions { search: READ, vector: WRITE, ..Default::default() });
+
+        assert!(principal.allows(Resource::Search, READ));
+        assert!(!principal.allows(Resource::Search, WRITE));
+        assert!(principal.allows(Resource::Vector, WRITE));
+        assert!(!principal.allows(Resource::Space, READ));
+
+        assert!(Principal::Anonymous.allows(Resource::System, WRITE));
+        assert!(Principal::Cluster.allows(Resource::Security, WRITE));
+        assert!(Principal::Admin.allows(Resource::Security, WRITE));
+        assert!(Principal::Admin.allows_space(None));
+    }
+
+    #[test]
+    fn test_space_scope() {
+        let unbound = token(None, Permissions::default());
+        assert!(unbound.allows_space(None));
+        assert!(unbound.allows_space(Some(5)));
+
+        let bound = token(Some(5), Permissions::default());
+        assert!(bound.allows_space(Some(5)));
+        assert!(!bound.allows_space(Some(6)));
+        // unknown spaces and routes spanning spaces
+        assert!(!bound.allows_space(None));
+    }
+
+    #[test]
+    fn test_principal_display_hides_token() {
+        assert_eq!(token(None, Permissions::default()).to_string(), "token 7");
+        assert_eq!(token(Some(5), Permissions::default()).to_string(), "token 7 (space 5)");
+        assert_eq!(Principal::Token(TokenPrincipal { id: None, space_id: None, permissions: Permissions::default() }).to_string(), "unknown token");
+    }
+}
@@ -0,0 +1,122 @@
+use serde_json::json;
+
+use crate::atinyvectors::atinyvectors_raft_command::{Command, COMMAND_SCHEMA_VERSION};
+use crate::raft_cluster::store::Request;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_vector_with_version_is_decoded() {
+        let value = json!({
+            "request": {
+                "command": "vector_with_version",
+                "space_name": "spacename",
+                "version_id": "3",
+                "value": {"vectors": [{"id": 1, "data": [0.1, 0.2]}]}
+            }
+        });
+        let req = Request::Set {
+            key: "vector_with_version".to_string(),
+            value: value.to_string(),
+        };
+
+        let command = req.into_command().unwrap();
+        assert_eq!(command, Command::UpsertVectors {
+            space_name: "spacename".to_string(),
+            version_id: 3,
+            value: json!({"vectors": [{"id": 1, "data": [0.1, 0.2]}]}),
+            index_name: None,
+        });
+    }
+
+    #[test]
+    fn test_unknown_legacy_command_is_rejected() {
+        let req = Request::Set {
+            key: "unknown".to_string(),
+            value: json!({"request": {"command": "unknown"}}).to_string(),
+        };
+        assert!(req.into_command().is_err());
+    }
+
+    #[test]
+    fn test_newer_schema_version_is_rejected() {
+        let req = Request::Command {
+            schema_version: COMMAND_SCHEMA_VERSION + 1,
+            command: Command::DeleteSnapshot { file_name: "snapshot-202401010000.zip".to_string() },
+        };
+        assert!(req.into_command().is_err());
+    }
+
+    #[test]
+    fn test_invalid_commands_are_not_proposed() {
+        assert!(Request::command(Command::UpsertVectors {
+            space_name: "spacename".to_string(),
+            version_id: 0,
+            value: json!({"vector": [0.1, 0.2]}),
+            index_name: None,
+        }).is_err());
+
+        assert!(Request::command(Command::StoragePutKey {
+            space_name: "spacename".to_string(),
+            key: "".to_string(),
+            value: "text".to_string(),
+        }).is_err());
+
+        assert!(Request::command(Command::DeleteVersion {
+            space_name: "spacename".to_string(),
+            version_id: 2,
+        }).is_ok());
+    }
+
+    #[test]
+    fn test_batch_is_reserved_as_index_name() {
+        let space = |indexes: serde_json::Value| Command::CreateSpace {
+            value: json!({"name": "spacename", "dimension": 4, "indexes": indexes}),
+        };
+        assert!(Request::command(space(json!({"batch": {"dimension": 4}}))).is_err());
+        assert!(Request::command(space(json!([{"name": "batch", "dimension": 4}]))).is_err());
+        assert!(Request::command(space(json!({"index1": {"dimension": 4}}))).is_ok());
+
+        assert!(Request::command(Command::CreateVersion {
+            space_name: "spacename".to_string(),
+            value: json!({"name": "v2", "indexes": {"batch": {"dimension": 4}}}),
+        }).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_file_names_are_validated() {
+        let sync = |file_name: &str| Command::SyncSnapshot {
+            file_name: file_name.to_string(),
+            leader_id: 1,
+            leader_addr: "127.0.0.1:21001".to_string(),
+            sha256: None,
+            size: None,
+        };
+
+        for file_name in ["../snapshot-202401011230.zip", "snapshot-202401011230.zip/../x", "snapshot-2024.zip", "/etc/passwd", ""] {
+            assert!(Request::command(Command::RestoreSnapshot { file_name: file_name.to_string() }).is_err());
+            assert!(Request::command(Command::DeleteSnapshot { file_name: file_name.to_string() }).is_err());
+            assert!(Request::command(sync(file_name)).is_err());
+        }
+
+        assert!(Request::command(Command::RestoreSnapshot { file_name: "snapshot-202401011230.zip".to_string() }).is_ok());
+        assert!(Request::command(sync("snapshot-202401011230.zip")).is_ok());
+    }
+
+    #[test]
+    fn test_token_commands_are_validated() {
+        assert!(Request::command(Command::DeleteRbacToken { token: "".to_string() }).is_err());
+        assert!(Request::command(Command::DeleteRbacToken { token: "abc".to_string() }).is_ok());
+
+        assert!(Request::command(Command::UpdateRbacToken {
+            token: "abc".to_string(),
+            value: "not json".to_string(),
+        }).is_err());
+        assert!(Request::command(Command::UpdateRbacToken {
+            token: "abc".to_string(),
+            value: json!({"space": 2}).to_string(),
+        }).is_ok());
+    }
+}
@@ -0,0 +1,152 @@
+use openraft::raft::AppendEntriesRequest;
+use openraft::Entry;
+use openraft::EntryPayload;
+use openraft::Vote;
+use serde_json::json;
+
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+use crate::raft_cluster::log_codec::{decode_entry, encode_entry, AppendPayload, LogFormat, MAGIC};
+use crate::raft_cluster::store::Request;
+use crate::raft_cluster::TypeConfig;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upsert_entry() -> Entry<TypeConfig> {
+        // k / 1024 is exact in f32, so the round trip can be compared as is
+        let data: Vec<f64> = (1..=64).map(|k| k as f64 / 1024.0).collect();
+
+        let mut entry = Entry::<TypeConfig>::default();
+        entry.log_id.index = 7;
+        entry.payload = EntryPayload::Normal(
+            Request::command(Command::UpsertVectors {
+                space_name: "spacename".to_string(),
+                version_id: 0,
+                value: json!({"vectors": [
+                    {"id": 1, "data": [0.5, 0.25, -1.0], "metadata": {"label": "first"}},
+                    {"id": 2, "data": data}
+                ]}),
+                index_name: None,
+            })
+            .unwrap(),
+        );
+        entry
+    }
+
+    fn vectors_of(entry: &Entry<TypeConfig>) -> serde_json::Value {
+        match &entry.payload {
+            EntryPayload::Normal(Request::Command { command: Command::UpsertVectors { value, .. }, .. }) => {
+                value["vectors"].clone()
+            }
+            _ => panic!("not an upsert"),
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let entry = upsert_entry();
+        let buf = encode_entry(&entry, LogFormat::Binary).unwrap();
+        assert!(buf.starts_with(MAGIC));
+        assert!(buf.len() < encode_entry(&entry, LogFormat::Json).unwrap().len());
+
+        let decoded = decode_entry(&buf).unwrap();
+        assert_eq!(decoded.log_id, entry.log_id);
+        assert_eq!(vectors_of(&decoded), vectors_of(&entry));
+    }
+
+    fn upsert_with(data: serde_json::Value) -> Entry<TypeConfig> {
+        let mut entry = upsert_entry();
+        if let EntryPayload::Normal(Request::Command { command: Command::UpsertVectors { value, .. }, .. }) = &mut entry.payload {
+            value["vectors"][0]["data"] = data;
+        }
+        entry
+    }
+
+    fn upsert_command(data: serde_json::Value) -> Command {
+        Command::UpsertVectors {
+            space_name: "spacename".to_string(),
+            version_id: 0,
+            value: json!({"vectors": [{"id": 1, "data": data}]}),
+            index_name: None,
+        }
+    }
+
+    #[test]
+    fn test_proposed_values_are_rounded_to_f32() {
+        // 0.1 has no exact f32, integers are stored as floats
+        for data in [json!([0.5, 0.1, 0.25]), json!([1, 2, 3])] {
+            let mut entry = Entry::<TypeConfig>::default();
+            entry.payload = EntryPayload::Normal(Request::command(upsert_command(data.clone())).unwrap());
+
+            let rounded: Vec<f64> = data.as_array().unwrap().iter().map(|v| v.as_f64().unwrap() as f32 as f64).collect();
+            assert_eq!(vectors_of(&entry)[0]["data"], json!(rounded));
+
+            let decoded = decode_entry(&encode_entry(&entry, LogFormat::Binary).unwrap()).unwrap();
+            assert_eq!(vectors_of(&decoded), vectors_of(&entry));
+        }
+    }
+
+    #[test]
+    fn test_values_beyond_f32_are_not_proposed() {
+        assert!(Request::command(upsert_command(json!([0.5, 1e300]))).is_err());
+        assert!(Request::command(upsert_command(json!([-1e39]))).is_err());
+    }
+
+    #[test]
+    fn test_older_inexact_entries_decode_rounded() {
+        // entries proposed before the rounding may hold values f32 cannot represent
+        let entry = upsert_with(json!([0.5, 0.1, 0.25]));
+        let decoded = decode_entry(&encode_entry(&entry, LogFormat::Binary).unwrap()).unwrap();
+        assert_eq!(vectors_of(&decoded)[0]["data"], json!([0.5, 0.1f32 as f64, 0.25]));
+    }
+
+    #[test]
+    fn test_non_finite_values_stay_in_json() {
+        // JSON has no NaN or infinity, serde_json turns them into null
+        let data = json!([f64::NAN, 0.5, f64::INFINITY]);
+        assert_eq!(data, json!([null, 0.5, null]));
+
+        let entry = upsert_with(data.clone());
+        let decoded = decode_entry(&encode_entry(&entry, LogFormat::Binary).unwrap()).unwrap();
+        assert_eq!(vectors_of(&decoded)[0]["data"], data);
+        assert_eq!(vectors_of(&decoded), vectors_of(&entry));
+    }
+
+    #[test]
+    fn test_json_entries_are_still_readable() {
+        let entry = upsert_entry();
+        let buf = serde_json::to_vec(&entry).unwrap();
+
+        let decoded = decode_entry(&buf).unwrap();
+        assert_eq!(vectors_of(&decoded), vectors_of(&entry));
+    }
+
+    #[test]
+    fn test_append_payload_round_trip() {
+        let req = AppendEntriesRequest::<TypeConfig> {
+            vote: Vote::new(1, 1),
+            prev_log_id: None,
+            entries: vec![upsert_entry()],
+            leader_commit: None,
+        };
+        for format in [LogFormat::Binary, LogFormat::Json] {
+            let text = serde_json::to_string(&AppendPayload::new(req.clone(), format).unwrap()).unwrap();
+            let decoded = serde_json::from_str::<AppendPayload>(&text).unwrap().into_request().unwrap();
+            assert_eq!(decoded.vote, req.vote);
+            assert_eq!(vectors_of(&decoded.entries[0]), vectors_of(&req.entries[0]));
+        }
+
+        // requests of nodes sending the entries as JSON are still read
+        let text = serde_json::to_string(&req).unwrap();
+        assert!(matches!(serde_json::from_str::<AppendPayload>(&text).unwrap(), AppendPayload::Plain(_)));
+    }
+
+    #[test]
+    fn test_newer_format_version_is_rejected() {
+        let mut buf = encode_entry(&upsert_entry(), LogFormat::Binary).unwrap();
+        buf[MAGIC.len()] = 0xff;
+
+        assert!(decode_entry(&buf).is_err());
+    }
+}
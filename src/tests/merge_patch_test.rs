@@ -0,0 +1,42 @@
+use serde_json::json;
+
+use crate::atinyvectors::merge_patch::merge_patch;
+use crate::service::handlers::vector_handler::{patch_fields, patched_vector};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_patch() {
+        let mut metadata = json!({"label": "first", "tags": ["a", "b"], "source": {"file": "a.txt", "page": 3}});
+        merge_patch(&mut metadata, &json!({"tags": ["c"], "source": {"page": null}, "lang": "en"}));
+
+        assert_eq!(metadata, json!({"label": "first", "tags": ["c"], "source": {"file": "a.txt"}, "lang": "en"}));
+    }
+
+    #[test]
+    fn test_merge_patch_replaces_non_objects() {
+        let mut metadata = serde_json::Value::Null;
+        merge_patch(&mut metadata, &json!({"label": "first", "removed": null}));
+        assert_eq!(metadata, json!({"label": "first"}));
+
+        let mut metadata = json!({"label": "first"});
+        merge_patch(&mut metadata, &json!("plain"));
+        assert_eq!(metadata, json!("plain"));
+    }
+
+    #[test]
+    fn test_patch_keeps_data_and_carries_index() {
+        let body = json!({"metadata": {"label": "updated", "draft": null}, "index_name": "index2"});
+        let (patch, index_name) = patch_fields(&body).unwrap();
+        assert_eq!(index_name.as_deref(), Some("index2"));
+
+        let stored = json!({"id": 7, "data": [0.5, 1.0], "metadata": {"label": "first", "draft": true}});
+        assert_eq!(patched_vector(stored, 7, &patch), json!({"id": 7, "data": [0.5, 1.0], "metadata": {"label": "updated"}}));
+
+        assert!(patch_fields(&json!({"metadata": {}, "data": [1.0]})).is_err());
+        assert!(patch_fields(&json!({"index_name": "index2"})).is_err());
+        assert_eq!(patch_fields(&json!({"metadata": {}})).unwrap().1, None);
+    }
+}
@@ -1 +1,14 @@
-pub mod config_test;
\ No newline at end of file
+pub mod config_test;
+mod command_test;
+mod snapshot_archive_test;
+mod log_codec_test;
+mod node_identity_test;
+mod sharding_test;
+mod snapshot_sync_test;
+mod auth_test;
+mod vector_index_test;
+mod merge_patch_test;
+mod search_test;
+mod recommend_test;
+mod tls_test;
+mod shard_journal_test;
@@ -0,0 +1,69 @@
+use std::fs;
+
+use crate::raft_cluster::node_identity;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_created_and_verified() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let created = node_identity::verify_or_create(dir.path(), 1, None).unwrap();
+        assert_eq!(created.node_id, 1);
+        assert_eq!(created.format_version, node_identity::DATA_FORMAT_VERSION);
+        assert!(dir.path().join(node_identity::IDENTITY_FILE).exists());
+
+        let verified = node_identity::verify_or_create(dir.path(), 1, None).unwrap();
+        assert_eq!(created, verified);
+    }
+
+    #[test]
+    fn test_other_node_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        node_identity::verify_or_create(dir.path(), 1, None).unwrap();
+
+        let err = node_identity::verify_or_create(dir.path(), 2, None).unwrap_err();
+        assert!(err.contains("belongs to node 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_cluster_id_recorded_then_enforced() {
+        let dir = tempfile::tempdir().unwrap();
+        node_identity::verify_or_create(dir.path(), 1, None).unwrap();
+
+        let identity = node_identity::verify_or_create(dir.path(), 1, Some("blue")).unwrap();
+        assert_eq!(identity.cluster_id.as_deref(), Some("blue"));
+
+        // running without a configured cluster id keeps the recorded one
+        assert!(node_identity::verify_or_create(dir.path(), 1, None).is_ok());
+
+        let err = node_identity::verify_or_create(dir.path(), 1, Some("green")).unwrap_err();
+        assert!(err.contains("belongs to cluster blue"), "{}", err);
+    }
+
+    #[test]
+    fn test_newer_format_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut identity = node_identity::verify_or_create(dir.path(), 1, None).unwrap();
+        identity.format_version = node_identity::DATA_FORMAT_VERSION + 1;
+        fs::write(dir.path().join(node_identity::IDENTITY_FILE), serde_json::to_vec(&identity).unwrap()).unwrap();
+
+        let err = node_identity::verify_or_create(dir.path(), 1, None).unwrap_err();
+        assert!(err.contains("format version"), "{}", err);
+    }
+
+    #[test]
+    fn test_legacy_raft_dir_moved() {
+        let cwd = tempfile::tempdir().unwrap();
+        let data = tempfile::tempdir().unwrap();
+        let legacy = cwd.path().join("0.0.0.0:22001.db");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("CURRENT"), b"MANIFEST-000001").unwrap();
+
+        node_identity::migrate_legacy_raft_dir(&legacy, data.path()).unwrap();
+        assert!(!legacy.exists());
+        assert!(node_identity::raft_dir(data.path()).join("CURRENT").exists());
+    }
+}
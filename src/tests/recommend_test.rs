@@ -0,0 +1,55 @@
+use std::collections::BTreeSet;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::service::handlers::recommend_handler::{average_query, best_score, exclude_ids};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(hits: &[Value]) -> Vec<u64> {
+        hits.iter().map(|hit| hit["label"].as_u64().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_average_query() {
+        let positives = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        assert_eq!(average_query(&positives, &[]).unwrap(), vec![0.5, 0.5]);
+
+        // moved away from the negatives by the difference of the means
+        let negatives = vec![vec![0.5, 0.0]];
+        assert_eq!(average_query(&positives, &negatives).unwrap(), vec![0.5, 1.0]);
+
+        assert!(average_query(&[], &[]).is_err());
+        assert!(average_query(&positives, &[vec![1.0, 2.0, 3.0]]).is_err());
+    }
+
+    #[test]
+    fn test_exclude_ids() {
+        let hits = vec![
+            json!({"label": 1, "distance": 0.0}),
+            json!({"label": 4, "distance": 0.1}),
+            json!({"label": 2, "distance": 0.2}),
+            json!({"label": 5, "distance": 0.3}),
+        ];
+        let exclude: BTreeSet<u64> = [1, 2].into_iter().collect();
+        assert_eq!(labels(&exclude_ids(hits, &exclude, 2)), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_best_score() {
+        let positive_hits = vec![
+            vec![json!({"label": 1, "distance": 0.0}), json!({"label": 3, "distance": 0.4}), json!({"label": 4, "distance": 0.5})],
+            vec![json!({"label": 2, "distance": 0.0}), json!({"label": 4, "distance": 0.1}), json!({"label": 5, "distance": 0.6})],
+        ];
+        // 5 is closer to the negative than to any positive
+        let negative_hits = vec![vec![json!({"label": 9, "distance": 0.0}), json!({"label": 5, "distance": 0.2}), json!({"label": 3, "distance": 0.9})]];
+        let exclude: BTreeSet<u64> = [1, 2, 9].into_iter().collect();
+
+        let hits = best_score(positive_hits, negative_hits, &exclude, 10);
+        assert_eq!(labels(&hits), vec![4, 3]);
+        assert_eq!(hits[0]["distance"].as_f64(), Some(0.1));
+    }
+}
@@ -0,0 +1,29 @@
+use serde_json::json;
+
+use crate::service::handlers::search_handler::{batch_queries, top_k, MAX_BATCH_QUERIES};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k() {
+        assert_eq!(top_k(&json!({"vector": [0.1], "top_k": 3})), 3);
+        assert_eq!(top_k(&json!({"vector": [0.1], "k": 5})), 5);
+        assert_eq!(top_k(&json!({"vector": [0.1], "top_k": 0})), 10);
+        assert_eq!(top_k(&json!({"vector": [0.1]})), 10);
+    }
+
+    #[test]
+    fn test_batch_queries() {
+        let query = json!({"vector": [0.1, 0.2], "top_k": 3});
+        assert_eq!(batch_queries(&json!({"queries": [query, query]})).unwrap().len(), 2);
+        assert_eq!(batch_queries(&json!([query])).unwrap().len(), 1);
+
+        assert!(batch_queries(&json!({"queries": []})).is_err());
+        assert!(batch_queries(&json!({"vector": [0.1]})).is_err());
+
+        let too_many: Vec<_> = (0..=MAX_BATCH_QUERIES).map(|_| query.clone()).collect();
+        assert!(batch_queries(&json!({"queries": too_many})).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use serde_json::json;
+
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+use crate::raft_cluster::shard_journal::{journal_ops, removal, JournalOp};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upserts_are_kept_per_vector_and_version() {
+        let command = Command::UpsertVectors {
+            space_name: "space".to_string(),
+            version_id: 0,
+            value: json!({"vectors": [{"id": 1, "data": [0.5]}, {"id": 2, "data": [1.0]}]}),
+            index_name: Some("index2".to_string()),
+        };
+        let ops = journal_ops(7, &command, |_| 3);
+
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0], JournalOp::Put(
+            "vector/space/3/1".to_string(),
+            Command::UpsertVectors {
+                space_name: "space".to_string(),
+                version_id: 3,
+                value: json!({"vectors": [{"id": 1, "data": [0.5]}]}),
+                index_name: Some("index2".to_string()),
+            },
+        ));
+        assert!(matches!(&ops[1], JournalOp::Put(key, _) if key == "vector/space/3/2"));
+    }
+
+    #[test]
+    fn test_schema_changes_keep_log_order_and_drop_what_they_delete() {
+        let create = Command::CreateSpace { value: json!({"name": "space"}) };
+        let delete = Command::DeleteSpace { space_name: "space".to_string(), value: json!({}) };
+
+        assert_eq!(journal_ops(9, &create, |_| 1), vec![JournalOp::Put("schema/00000000000000000009".to_string(), create)]);
+        assert_eq!(journal_ops(12, &delete, |_| 1), vec![
+            JournalOp::Put("schema/00000000000000000012".to_string(), delete.clone()),
+            JournalOp::DeletePrefix("vector/space/".to_string()),
+            JournalOp::DeletePrefix("kv/space/".to_string()),
+        ]);
+
+        let delete_version = Command::DeleteVersion { space_name: "space".to_string(), version_id: 2 };
+        assert_eq!(journal_ops(13, &delete_version, |_| 1)[1], JournalOp::DeletePrefix("vector/space/2/".to_string()));
+    }
+
+    #[test]
+    fn test_tokens_and_keys_keep_their_last_write() {
+        let update = Command::UpdateRbacToken { token: "t".to_string(), value: "{}".to_string() };
+        assert_eq!(journal_ops(1, &update, |_| 1), vec![JournalOp::Put(
+            "token/t".to_string(),
+            Command::CreateRbacToken { token: "t".to_string(), value: "{}".to_string() },
+        )]);
+
+        let remove = Command::StorageRemoveKey { space_name: "space".to_string(), key: "a/b".to_string() };
+        assert_eq!(journal_ops(2, &remove, |_| 1), vec![JournalOp::Delete("kv/space/a/b".to_string())]);
+        assert_eq!(removal("kv/space/a/b"), Some(remove));
+        assert_eq!(removal("token/t"), Some(Command::DeleteRbacToken { token: "t".to_string() }));
+        assert_eq!(removal("vector/space/3/1"), None);
+
+        assert!(journal_ops(3, &Command::CreateSnapshot { value: json!({}) }, |_| 1).is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use serde_json::json;
+use serde_json::Value;
+
+use tide::http::Method;
+
+use crate::service::forward::is_hop_header;
+use crate::service::sharding::{merge_batch, merge_listings, merge_reranked, merge_top_k, shard_of, split_vectors, undo_of, ShardMap, Undo};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_of_is_stable() {
+        // routing must not change between builds, or vectors end up on the wrong shard
+        let shards: Vec<usize> = (0..8).map(|id| shard_of(id, 3)).collect();
+        assert_eq!(shards, vec![1, 0, 0, 2, 0, 2, 2, 1]);
+        let shards: Vec<usize> = [1, 42, 1000, 123456789, u64::MAX].iter().map(|id| shard_of(*id, 4)).collect();
+        assert_eq!(shards, vec![0, 3, 0, 1, 1]);
+        assert!((0..1000).all(|id| shard_of(id, 3) < 3));
+        assert!((0..1000).all(|id| shard_of(id, 1) == 0));
+
+        let mut counts = [0usize; 4];
+        for id in 0..4000 {
+            counts[shard_of(id, 4)] += 1;
+        }
+        assert!(counts.iter().all(|count| *count > 800), "unbalanced shards: {:?}", counts);
+    }
+
+    #[test]
+    fn test_split_vectors() {
+        let vectors: Vec<Value> = (1..=20).map(|id| json!({"id": id, "data": [0.1, 0.2]})).collect();
+        let body = json!({"vectors": vectors, "index_name": "default"});
+
+        let parts = split_vectors(&body, 3).unwrap();
+        assert_eq!(parts.len(), 3);
+
+        let mut ids = Vec::new();
+        for (shard, part) in parts.iter().enumerate() {
+            let Some(part) = part else { continue };
+            assert_eq!(part["index_name"], "default");
+            for vector in part["vectors"].as_array().unwrap() {
+                let id = vector["id"].as_u64().unwrap();
+                assert_eq!(shard_of(id, 3), shard);
+                ids.push(id);
+            }
+        }
+        ids.sort();
+        assert_eq!(ids, (1..=20).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_split_vectors_needs_ids() {
+        assert!(split_vectors(&json!({"vectors": [{"data": [0.1]}]}), 2).is_err());
+        assert!(split_vectors(&json!({}), 2).is_err());
+    }
+
+    #[test]
+    fn test_shard_map() {
+        let groups = vec![
+            vec!["node1:21001".to_string(), "node2:21001".to_string()],
+            vec!["node2:21001".to_string(), "node3:21001".to_string()],
+            vec!["node4:21001".to_string()],
+        ];
+        let shards = ShardMap::new(groups.clone(), "http://node2:21001/");
+        assert_eq!(shards.local, vec![0, 1]);
+        assert!(ShardMap::new(groups.clone(), "node5:21001").local.is_empty());
+
+        // node2 serves shards 0 and 1, their nodes share its engine
+        assert!(shards.shares_engine(0));
+        assert!(shards.shares_engine(1));
+        assert!(!shards.shares_engine(2));
+    }
+
+    #[test]
+    fn test_merge_top_k() {
+        let shard1 = vec![json!({"label": 1, "distance": 0.1}), json!({"label": 4, "distance": 0.7})];
+        let shard2 = vec![json!({"label": 2, "distance": 0.3}), json!({"label": 5, "distance": 0.05})];
+        let shard3 = vec![];
+
+        let merged = merge_top_k(vec![shard1, shard2, shard3], 3);
+        let labels: Vec<u64> = merged.iter().map(|hit| hit["label"].as_u64().unwrap()).collect();
+        assert_eq!(labels, vec![5, 1, 2]);
+
+        // groups sharing an engine both report its vectors
+        let shard1 = vec![json!({"label": 1, "distance": 0.1}), json!({"label": 2, "distance": 0.3})];
+        let shard2 = vec![json!({"label": 2, "distance": 0.3}), json!({"label": 3, "distance": 0.5})];
+        let merged = merge_top_k(vec![shard1, shard2], 3);
+        let labels: Vec<u64> = merged.iter().map(|hit| hit["label"].as_u64().unwrap()).collect();
+        assert_eq!(labels, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_merge_batch() {
+        let shard1 = vec![
+            json!({"results": [{"label": 1, "distance": 0.1}, {"label": 3, "distance": 0.4}]}),
+            json!({"results": [{"label": 7, "distance": 0.2}]}),
+            json!({"results": []}),
+        ];
+        let shard2 = vec![
+            json!({"results": [{"label": 2, "distance": 0.2}]}),
+            json!({"error": "Vector index not found: dense"}),
+            json!({"results": [{"label": 9, "distance": 0.9}]}),
+        ];
+
+        let merged = merge_batch(vec![shard1, shard2], &[2, 10, 10]);
+        assert_eq!(merged.len(), 3);
+        let labels: Vec<u64> = merged[0]["results"].as_array().unwrap().iter().map(|hit| hit["label"].as_u64().unwrap()).collect();
+        assert_eq!(labels, vec![1, 2]);
+        // a query that failed on any shard reports the failure
+        assert_eq!(merged[1]["error"], "Vector index not found: dense");
+        assert_eq!(merged[2]["results"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_merge_reranked_keeps_best_scores() {
+        let merged = merge_reranked(vec![
+            vec![json!({"id": 1, "bm25_score": 2.5}), json!({"id": 2, "bm25_score": 0.5})],
+            vec![json!({"id": 3, "bm25_score": 1.5}), json!({"id": 1, "bm25_score": 2.5})],
+        ], 2);
+        let ids: Vec<u64> = merged.iter().map(|hit| hit["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_merge_listings_pages_by_id_and_skips_shared_vectors() {
+        // shard 0 answers from an engine shared with shard 1 and lists its vectors as well
+        let owned = |shard: usize| (0..20u64).filter(|id| shard_of(*id, 2) == shard).collect::<Vec<u64>>();
+        let listing = |ids: Vec<u64>| json!({"vectors": ids.iter().map(|id| json!({"id": id})).collect::<Vec<Value>>(), "total_count": ids.len()});
+        let merged = merge_listings(vec![(0, listing((0..20).collect())), (1, listing(owned(1)))], 2, 5, 10);
+
+        let ids: Vec<u64> = merged["vectors"].as_array().unwrap().iter().map(|v| v["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, (5..15).collect::<Vec<u64>>());
+        assert_eq!(merged["total_count"], json!(20 + owned(1).len()));
+    }
+
+    #[test]
+    fn test_undo_of_creations_only() {
+        assert_eq!(undo_of(Method::Post, "/api/space", &json!({"name": "docs"})), Some(Undo::Space {
+            prefix: "/api".to_string(),
+            space_name: "docs".to_string(),
+        }));
+        assert_eq!(undo_of(Method::Post, "/api/space/docs/version", &json!({"name": "v2"})), Some(Undo::Version {
+            prefix: "/api".to_string(),
+            space_name: "docs".to_string(),
+            version_name: "v2".to_string(),
+        }));
+        // updating a space named "space" is no creation
+        assert_eq!(undo_of(Method::Post, "/api/space/space", &json!({"name": "space"})), None);
+        assert_eq!(undo_of(Method::Delete, "/api/space/docs", &json!({})), None);
+        assert_eq!(undo_of(Method::Post, "/api/space", &json!({})), None);
+    }
+
+    #[test]
+    fn test_hop_headers_ignore_case() {
+        assert!(is_hop_header("content-length"));
+        assert!(is_hop_header("Content-Length"));
+        assert!(is_hop_header("Transfer-Encoding"));
+        assert!(!is_hop_header("Content-Type"));
+        assert!(!is_hop_header("Authorization"));
+    }
+}
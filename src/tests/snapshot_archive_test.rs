@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::Cursor;
+use std::io::Read;
+
+use crate::raft_cluster::snapshot_archive;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(data: &[u8]) -> std::io::Result<Vec<(String, Vec<u8>)>> {
+        let mut r = Cursor::new(data);
+        snapshot_archive::read_header(&mut r)?;
+
+        let mut entries = Vec::new();
+        while let Some((path, len)) = snapshot_archive::next_entry(&mut r)? {
+            let mut data = Vec::new();
+            (&mut r).take(len).read_to_end(&mut data)?;
+            entries.push((path, data));
+        }
+        Ok(entries)
+    }
+
+    #[test]
+    fn test_kv_storages_round_trip() {
+        let src = tempfile::tempdir().unwrap();
+        fs::create_dir_all(src.path().join("space/spacenamestorage.rocksdb")).unwrap();
+        fs::write(src.path().join("space/spacenamestorage.rocksdb/CURRENT"), b"MANIFEST-000001").unwrap();
+        fs::create_dir_all(src.path().join("space/other")).unwrap();
+        fs::write(src.path().join("space/other/index.bin"), b"not a kv storage").unwrap();
+
+        let mut data = Vec::new();
+        snapshot_archive::write_header(&mut data).unwrap();
+        snapshot_archive::write_entry(&mut data, snapshot_archive::KVS_ENTRY, b"{}").unwrap();
+        snapshot_archive::write_kv_storages(&mut data, src.path()).unwrap();
+        assert!(snapshot_archive::is_archive(&data));
+
+        let entries = read_all(&data).unwrap();
+        let paths: Vec<&str> = entries.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["kvs.json", "space/spacenamestorage.rocksdb/CURRENT"]);
+
+        let dst = tempfile::tempdir().unwrap();
+        for (path, data) in &entries[1..] {
+            snapshot_archive::restore_file(dst.path(), path, &mut data.as_slice()).unwrap();
+        }
+        assert_eq!(
+            fs::read(dst.path().join("space/spacenamestorage.rocksdb/CURRENT")).unwrap(),
+            b"MANIFEST-000001"
+        );
+    }
+
+    #[test]
+    fn test_entries_cannot_escape_data_path() {
+        let mut data = Vec::new();
+        snapshot_archive::write_header(&mut data).unwrap();
+        snapshot_archive::write_entry(&mut data, "space/../../etc/passwd", b"").unwrap();
+
+        assert!(read_all(&data).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs;
+
+use tempfile::tempdir;
+
+use crate::atinyvectors::atinyvectors_raft_command::Command;
+use crate::atinyvectors::snapshot_sync::{file_digest, snapshot_stamp, verify};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn test_snapshot_stamp_matches_upload_names() {
+        assert_eq!(snapshot_stamp("snapshot-202401011230.zip"), Some("202401011230".to_string()));
+        assert_eq!(snapshot_stamp("snapshot-20240101.zip"), None);
+        assert_eq!(snapshot_stamp("../snapshot-202401011230.zip"), None);
+    }
+
+    #[test]
+    fn test_file_digest() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshot-202401011230.zip");
+        fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(file_digest(&path).unwrap(), (ABC_SHA256.to_string(), 3));
+    }
+
+    #[test]
+    fn test_verify_rejects_a_different_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snapshot-202401011230.zip");
+        fs::write(&path, b"abc").unwrap();
+
+        assert!(verify(&path, Some(ABC_SHA256), Some(3)).is_ok());
+        assert!(verify(&path, None, None).is_ok());
+        assert!(verify(&path, Some(ABC_SHA256), Some(4)).is_err());
+
+        fs::write(&path, b"abd").unwrap();
+        assert!(verify(&path, Some(ABC_SHA256), Some(3)).is_err());
+    }
+
+    #[test]
+    fn test_sync_command_without_checksum_is_decoded() {
+        // entries written before the checksum was added
+        let json = r#"{"sync_snapshot":{"file_name":"snapshot-202401011230.zip","leader_id":1,"leader_addr":"127.0.0.1:21001"}}"#;
+        let command: Command = serde_json::from_str(json).unwrap();
+        assert_eq!(command, Command::SyncSnapshot {
+            file_name: "snapshot-202401011230.zip".to_string(),
+            leader_id: 1,
+            leader_addr: "127.0.0.1:21001".to_string(),
+            sha256: None,
+            size: None,
+        });
+    }
+}
@@ -0,0 +1,20 @@
+use crate::raft_cluster::tls::{base_url, host_of};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("node1:21002"), "node1");
+        assert_eq!(host_of("wss://node1.example.com:21002"), "node1.example.com");
+        assert_eq!(host_of("https://node1"), "node1");
+        assert_eq!(host_of("[::1]:21002"), "::1");
+    }
+
+    #[test]
+    fn test_base_url_keeps_a_given_scheme() {
+        assert_eq!(base_url("https://node1:21001/"), "https://node1:21001");
+        assert_eq!(base_url("http://node1:21001"), "http://node1:21001");
+    }
+}
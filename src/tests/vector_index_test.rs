@@ -0,0 +1,54 @@
+use serde_json::json;
+
+use crate::atinyvectors::vector_index::{check_index, dimension, find_index, IndexError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version() -> serde_json::Value {
+        json!({
+            "versionId": 1,
+            "vectorIndices": [
+                {"name": "index1", "dimension": 4, "is_default": false},
+                {"name": "dense", "dimension": 128, "is_default": true},
+                {"name": "index2", "dimension": 0, "is_default": false}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_find_named_index() {
+        let version = version();
+
+        assert_eq!(find_index(&version, Some("index1")).and_then(dimension), Some(4));
+        assert!(find_index(&version, Some("missing")).is_none());
+        // an index without a reported dimension is not checked
+        assert!(find_index(&version, Some("index2")).is_some());
+        assert_eq!(find_index(&version, Some("index2")).and_then(dimension), None);
+    }
+
+    #[test]
+    fn test_find_default_index() {
+        let version = version();
+        assert_eq!(find_index(&version, None).and_then(dimension), Some(128));
+        assert_eq!(find_index(&version, Some("default")).and_then(dimension), Some(128));
+
+        // spaces report their default version under "version"
+        let space = json!({"name": "spacename", "version": version});
+        assert_eq!(find_index(&space, Some("index1")).and_then(dimension), Some(4));
+
+        assert!(find_index(&json!({"vectorIndices": []}), None).is_none());
+    }
+
+    #[test]
+    fn test_only_the_default_index_is_addressable() {
+        let version = version();
+
+        assert!(check_index(&version, None).is_ok());
+        assert!(check_index(&version, Some("dense")).is_ok());
+        assert!(check_index(&version, Some("default")).is_ok());
+        assert_eq!(check_index(&version, Some("index1")), Err(IndexError::NotAddressable("index1".to_string())));
+        assert_eq!(check_index(&version, Some("missing")), Err(IndexError::NotFound("missing".to_string())));
+    }
+}